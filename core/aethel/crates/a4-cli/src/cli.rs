@@ -1,4 +1,5 @@
-use clap::{Parser, Subcommand};
+use a4_core::JournalSession;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -11,14 +12,49 @@ pub struct Cli {
     #[arg(short = 'v', long, action = clap::ArgAction::Count)]
     pub verbose: u8,
 
+    /// Output format; `json` prints structured results and errors
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
+    #[command(about = "Scaffold a vault and git repo (in --vault or the current directory)")]
+    Init(InitArgs),
+
     #[command(about = "Resolve path to today's daily note; create from template if absent")]
-    Today,
+    Today(TodayArgs),
+
+    #[command(about = "Resolve path to a week's plan note; create from template if absent")]
+    Week(WeekArgs),
+
+    #[command(
+        about = "Resolve path to a journal session file; create it and link it from the day's note if absent"
+    )]
+    Journal(JournalArgs),
+
+    #[command(about = "Resolve today's inbox paste zone, appending --text or --stdin if given")]
+    Inbox(InboxArgs),
+
+    #[command(
+        about = "List untriaged inbox blocks, or file one under a heading with a fresh anchor"
+    )]
+    Triage(TriageArgs),
+
+    #[command(about = "Convert saved HTML or Markdown into a source note linked from today")]
+    Ingest(IngestArgs),
+
+    #[command(about = "Accept authenticated JSON captures over HTTP on localhost")]
+    Serve(ServeArgs),
+
+    #[command(about = "Create, log to, or summarize a project hub under projects/<slug>/")]
+    Project(ProjectArgs),
+
+    #[command(about = "Add, complete, log and check the dominoes of project hubs")]
+    Domino(DominoArgs),
 
     #[command(about = "Append block under anchor; create heading if missing")]
     Append(AppendArgs),
@@ -28,22 +64,107 @@ pub enum Commands {
 
     #[command(about = "Print the resolved vault root path")]
     Root,
+
+    #[command(about = "Render ![[...]] transclusions into a standalone Markdown file")]
+    Stitch(StitchArgs),
+
+    #[command(about = "Print the block that follows an anchor")]
+    Get(GetArgs),
+
+    #[command(about = "Read or set one front matter field, leaving the rest of the note as is")]
+    Meta(MetaArgs),
+
+    #[command(about = "Search blocks by text, anchor prefix, date, kind or heading")]
+    Search(SearchArgs),
+
+    #[command(
+        about = "Gather anchored blocks of one prefix from daily notes over a week or date range"
+    )]
+    Collate(CollateArgs),
+}
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Parser)]
+pub struct InitArgs {
+    #[arg(long, value_name = "URL")]
+    pub remote: Option<String>,
+}
+
+/// Selects a daily note other than today's.
+#[derive(Args)]
+pub struct DayArgs {
+    /// Day of the note, as YYYY-MM-DD
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub date: Option<String>,
+
+    /// Days relative to --date or today, e.g. -1 for yesterday
+    #[arg(long, value_name = "DAYS", allow_hyphen_values = true)]
+    pub offset: Option<i64>,
+}
+
+impl DayArgs {
+    pub fn is_set(&self) -> bool {
+        self.date.is_some() || self.offset.is_some()
+    }
+}
+
+#[derive(Parser)]
+pub struct TodayArgs {
+    #[command(flatten)]
+    pub day: DayArgs,
+}
+
+#[derive(Parser)]
+pub struct WeekArgs {
+    /// Weeks relative to the current one, e.g. -1 for last week
+    #[arg(
+        long,
+        value_name = "WEEKS",
+        allow_hyphen_values = true,
+        conflicts_with = "date"
+    )]
+    pub offset: Option<i64>,
+
+    /// Any day in the week, as YYYY-MM-DD
+    #[arg(long, value_name = "YYYY-MM-DD", conflicts_with = "offset")]
+    pub date: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct JournalArgs {
+    /// morning or evening
+    #[arg(long, value_name = "SESSION")]
+    pub session: JournalSession,
+
+    /// Day of the session; defaults to the effective day, where evening
+    /// sessions before `time.day_rollover_hour` count toward the previous day
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub date: Option<String>,
 }
 
 #[derive(Parser)]
 pub struct AppendArgs {
     #[arg(long, value_name = "HEADING")]
-    pub heading: String,
+    pub heading: Option<String>,
 
     #[arg(long, value_name = "TOKEN")]
     pub anchor: String,
 
-    #[arg(long, value_name = "PATH", conflicts_with = "today")]
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["today", "date", "offset"])]
     pub file: Option<PathBuf>,
 
     #[arg(long, conflicts_with = "file")]
     pub today: bool,
 
+    #[command(flatten)]
+    pub day: DayArgs,
+
     #[arg(
         long,
         value_name = "TEXT",
@@ -56,12 +177,233 @@ pub struct AppendArgs {
     pub stdin: bool,
 }
 
+#[derive(Parser)]
+pub struct InboxArgs {
+    /// Day of the paste zone, as YYYY-MM-DD
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub date: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "TEXT",
+        conflicts_with = "stdin",
+        allow_hyphen_values = true
+    )]
+    pub text: Option<String>,
+
+    #[arg(long, conflicts_with = "text")]
+    pub stdin: bool,
+}
+
+#[derive(Parser)]
+pub struct TriageArgs {
+    /// Inbox block to file, e.g. ^paste-0930; lists untriaged blocks if absent
+    #[arg(long, value_name = "TOKEN", requires = "anchor")]
+    pub block: Option<String>,
+
+    /// Inbox file holding --block; defaults to today's paste zone
+    #[arg(long, value_name = "PATH", requires = "block")]
+    pub from: Option<PathBuf>,
+
+    /// Prefix of the filed block's fresh anchor, e.g. task for ^task-HHMM
+    #[arg(long, value_name = "PREFIX", requires = "block")]
+    pub anchor: Option<String>,
+
+    #[arg(long, value_name = "HEADING", requires = "block")]
+    pub heading: Option<String>,
+
+    #[arg(long, value_name = "PATH", requires = "block", conflicts_with_all = ["today", "date", "offset"])]
+    pub file: Option<PathBuf>,
+
+    #[arg(long, requires = "block", conflicts_with = "file")]
+    pub today: bool,
+
+    #[command(flatten)]
+    pub day: DayArgs,
+}
+
+#[derive(Parser)]
+pub struct IngestArgs {
+    #[command(subcommand)]
+    pub command: IngestCommand,
+}
+
+#[derive(Subcommand)]
+pub enum IngestCommand {
+    #[command(about = "Write sources/articles/<slug>.md and a ^read-HHMM block in today's note")]
+    Article(IngestArticleArgs),
+}
+
+#[derive(Parser)]
+pub struct IngestArticleArgs {
+    /// Saved page (.html) or Markdown (.md) to ingest
+    #[arg(long, value_name = "PATH")]
+    pub file: PathBuf,
+
+    /// Original URL, recorded as origin.url
+    #[arg(long, value_name = "URL")]
+    pub url: String,
+
+    /// Recorded as origin.via
+    #[arg(long, value_name = "NAME")]
+    pub via: Option<String>,
+
+    /// Title to use instead of the one found in the page
+    #[arg(long, value_name = "TITLE")]
+    pub title: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct ServeArgs {
+    /// Port to listen on; 0 picks a free one
+    #[arg(long, value_name = "PORT", default_value_t = 4747)]
+    pub port: u16,
+
+    /// Address to bind; anything but loopback exposes the endpoint to the
+    /// network
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1")]
+    pub host: String,
+
+    /// Shared secret clients send as `Authorization: Bearer <token>`
+    #[arg(
+        long,
+        value_name = "TOKEN",
+        env = "A4_SERVE_TOKEN",
+        hide_env_values = true
+    )]
+    pub token: String,
+}
+
+#[derive(Parser)]
+pub struct ProjectArgs {
+    #[command(subcommand)]
+    pub command: ProjectCommand,
+}
+
+#[derive(Subcommand)]
+pub enum ProjectCommand {
+    #[command(about = "Create projects/<slug>/index.md from the hub.project template")]
+    New {
+        #[arg(value_name = "SLUG")]
+        slug: String,
+
+        /// Heading for the index; defaults to the slug
+        #[arg(long, value_name = "TITLE")]
+        title: Option<String>,
+    },
+
+    #[command(about = "Append a dated entry to projects/<slug>/log.md")]
+    Log {
+        #[arg(value_name = "SLUG")]
+        slug: String,
+
+        #[arg(
+            long,
+            value_name = "TEXT",
+            conflicts_with = "stdin",
+            allow_hyphen_values = true
+        )]
+        text: Option<String>,
+
+        #[arg(long, conflicts_with = "text")]
+        stdin: bool,
+    },
+
+    #[command(about = "Show open dominoes and recent log entries")]
+    Show {
+        #[arg(value_name = "SLUG")]
+        slug: String,
+
+        /// Number of recent log entries to show
+        #[arg(long, value_name = "N", default_value_t = 5)]
+        limit: usize,
+    },
+}
+
+#[derive(Parser)]
+pub struct DominoArgs {
+    #[command(subcommand)]
+    pub command: DominoCommand,
+}
+
+#[derive(Subcommand)]
+pub enum DominoCommand {
+    #[command(about = "Add a task to the project's dominoes with a vault-unique ID")]
+    Add {
+        #[arg(value_name = "SLUG")]
+        slug: String,
+
+        #[arg(value_name = "TASK")]
+        task: String,
+    },
+
+    #[command(about = "Mark a domino done and log its completion to log.jsonl")]
+    Complete {
+        #[arg(value_name = "SLUG")]
+        slug: String,
+
+        #[arg(value_name = "ID")]
+        id: String,
+
+        /// Log message; defaults to "Completed: <task>"
+        #[arg(long, value_name = "TEXT")]
+        what: Option<String>,
+    },
+
+    #[command(about = "Append a timestamped entry to projects/<slug>/log.jsonl")]
+    Log {
+        #[arg(value_name = "SLUG")]
+        slug: String,
+
+        #[arg(value_name = "TEXT", allow_hyphen_values = true)]
+        what: String,
+
+        /// Domino the entry refers to; repeatable
+        #[arg(long = "domino", value_name = "ID")]
+        dominoes: Vec<String>,
+    },
+
+    #[command(about = "Show each domino as open or done; every project without a slug")]
+    Status {
+        #[arg(value_name = "SLUG")]
+        slug: Option<String>,
+    },
+
+    #[command(about = "List open dominoes whose `after` dominoes are done, in dependency order")]
+    Next {
+        #[arg(value_name = "SLUG")]
+        slug: String,
+    },
+
+    #[command(about = "Print the dominoes and their `after` edges as a Mermaid or DOT graph")]
+    Graph {
+        #[arg(value_name = "SLUG")]
+        slug: String,
+
+        /// Graphviz DOT instead of Mermaid
+        #[arg(long)]
+        dot: bool,
+    },
+
+    #[command(about = "Report malformed dominoes and log lines; every project without a slug")]
+    Lint {
+        #[arg(value_name = "SLUG")]
+        slug: Option<String>,
+    },
+
+    #[command(about = "Give ID-less dominoes IDs in place; every project without a slug")]
+    Migrate {
+        #[arg(value_name = "SLUG")]
+        slug: Option<String>,
+    },
+}
+
 #[derive(Parser)]
 pub struct SyncArgs {
     #[arg(long, value_name = "MSG")]
     pub message: Option<String>,
 
-    #[arg(long, value_name = "NAME", default_value = "origin")]
+    #[arg(long, value_name = "NAME")]
     pub remote: Option<String>,
 
     #[arg(long, value_name = "NAME")]
@@ -70,3 +412,100 @@ pub struct SyncArgs {
     #[arg(long, default_value = "true")]
     pub ff_only: bool,
 }
+
+#[derive(Parser)]
+pub struct StitchArgs {
+    #[arg(long, value_name = "PATH")]
+    pub file: PathBuf,
+
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+pub struct GetArgs {
+    #[arg(long, value_name = "PATH")]
+    pub file: PathBuf,
+
+    #[arg(long, value_name = "TOKEN")]
+    pub anchor: String,
+}
+
+#[derive(Parser)]
+pub struct MetaArgs {
+    #[command(subcommand)]
+    pub command: MetaCommand,
+}
+
+#[derive(Subcommand)]
+pub enum MetaCommand {
+    #[command(about = "Print a front matter field; nothing if it is unset")]
+    Get {
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        #[arg(value_name = "KEY")]
+        key: String,
+    },
+
+    #[command(about = "Set a front matter field, adding front matter if the note has none")]
+    Set {
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        #[arg(value_name = "KEY")]
+        key: String,
+
+        /// Parsed as YAML, so `[a, b]` sets a list; quote it to keep a string
+        #[arg(value_name = "VALUE", allow_hyphen_values = true)]
+        value: String,
+    },
+}
+
+#[derive(Parser)]
+pub struct SearchArgs {
+    #[arg(value_name = "TEXT")]
+    pub text: Option<String>,
+
+    #[arg(long, value_name = "PREFIX")]
+    pub prefix: Option<String>,
+
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub from: Option<String>,
+
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub to: Option<String>,
+
+    #[arg(long, value_name = "KIND")]
+    pub kind: Option<String>,
+
+    #[arg(long, value_name = "HEADING")]
+    pub heading: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct CollateArgs {
+    #[arg(long, value_name = "PREFIX")]
+    pub prefix: String,
+
+    #[arg(
+        long,
+        value_name = "YYYY-Www",
+        required_unless_present = "from",
+        conflicts_with_all = ["from", "to"]
+    )]
+    pub week: Option<String>,
+
+    #[arg(long, value_name = "YYYY-MM-DD", requires = "to")]
+    pub from: Option<String>,
+
+    #[arg(long, value_name = "YYYY-MM-DD", requires = "from")]
+    pub to: Option<String>,
+
+    /// Print `![[...]]` references instead of stitched content
+    #[arg(long)]
+    pub refs: bool,
+
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+}
@@ -12,6 +12,7 @@ pub fn init(verbosity: u8) {
 
     tracing_subscriber::fmt()
         .with_env_filter(filter)
+        .with_writer(std::io::stderr)
         .with_target(false)
         .with_thread_ids(false)
         .with_thread_names(false)
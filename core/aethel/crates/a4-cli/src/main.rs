@@ -1,67 +1,177 @@
 mod cli;
 mod env;
 mod logging;
-
-use a4_core::git_backend::{GitBackend, GixBackend};
-use a4_core::{append_block, AnchorToken, AppendOptions, LocalClock, Vault};
+mod output;
+mod serve;
+
+use a4_core::collate::{render_refs, render_stitched};
+use a4_core::dominoes;
+use a4_core::front_matter::{parse_value, render_value};
+use a4_core::git_backend::{FastForward, GitBackend, GixBackend, RebaseResult};
+use a4_core::inbox::paste_zone;
+use a4_core::notes::read_note;
+use a4_core::templates::create_from_template;
+use a4_core::{
+    add_domino, append_block, collate, complete_domino, create_project, find_block, find_project,
+    ingest_article, init_vault, lint_project, list_projects, log_project, migrate_project,
+    next_dominoes, open_journal, paste, project_status, project_summary, read_front_matter,
+    render_graph, render_status, search, set_front_matter_field, stitch_note, triage, untriaged,
+    A4Error, AnchorToken, AppendOptions, GraphSyntax, IngestOptions, InitOptions, IsoWeek,
+    LocalClock, Moment, Project, SearchQuery, TemplateContext, TriageTarget, UtcDay, Vault,
+    VaultRoot,
+};
 use anyhow::Result;
 use clap::Parser;
-use cli::{Cli, Commands};
-use std::io::{self, Read};
+use cli::{Cli, Commands, OutputFormat};
+use output::emit;
+use serde_json::json;
+use std::io::{self, Read, Write};
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
     logging::init(cli.verbose);
 
+    let format = cli.format;
     let result = match cli.command {
-        Commands::Today => handle_today(cli.vault),
-        Commands::Append(args) => handle_append(cli.vault, args),
-        Commands::Sync(args) => handle_sync(cli.vault, args),
-        Commands::Root => handle_root(cli.vault),
+        Commands::Init(args) => handle_init(cli.vault, args, format),
+        Commands::Today(args) => handle_today(cli.vault, args, format),
+        Commands::Week(args) => handle_week(cli.vault, args, format),
+        Commands::Journal(args) => handle_journal(cli.vault, args, format),
+        Commands::Inbox(args) => handle_inbox(cli.vault, args, format),
+        Commands::Triage(args) => handle_triage(cli.vault, args, format),
+        Commands::Ingest(args) => handle_ingest(cli.vault, args, format),
+        Commands::Serve(args) => handle_serve(cli.vault, args, format),
+        Commands::Project(args) => handle_project(cli.vault, args, format),
+        Commands::Domino(args) => handle_domino(cli.vault, args, format),
+        Commands::Append(args) => handle_append(cli.vault, args, format),
+        Commands::Sync(args) => handle_sync(cli.vault, args, format),
+        Commands::Root => handle_root(cli.vault, format),
+        Commands::Stitch(args) => handle_stitch(cli.vault, args, format),
+        Commands::Get(args) => handle_get(cli.vault, args, format),
+        Commands::Meta(args) => handle_meta(cli.vault, args, format),
+        Commands::Search(args) => handle_search(cli.vault, args, format),
+        Commands::Collate(args) => handle_collate(cli.vault, args, format),
     };
 
     if let Err(e) = result {
-        eprintln!("Error: {e}");
-        std::process::exit(1);
+        std::process::exit(output::report_error(format, &e));
     }
 
     Ok(())
 }
 
-fn handle_today(vault_override: Option<std::path::PathBuf>) -> Result<()> {
+fn handle_init(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::InitArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let root = match vault_override {
+        Some(path) => path,
+        None => std::env::current_dir()?,
+    };
+
+    let opts = InitOptions {
+        remote: args.remote.as_deref(),
+    };
+    let report = init_vault(&root, opts)?;
+
+    for path in &report.created {
+        tracing::info!("Created {}", path.display());
+    }
+    if report.git_initialized {
+        tracing::info!("Initialized git repository");
+    }
+    if report.remote_added {
+        tracing::info!("Added remote origin");
+    }
+
+    let vault = Vault::open(&root, Default::default())?;
+    let value = json!({
+        "root": vault.root(),
+        "created": report.created,
+        "git_initialized": report.git_initialized,
+        "remote_added": report.remote_added,
+    });
+    emit(format, value, || format!("{}\n", vault.root().display()))
+}
+
+fn handle_today(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::TodayArgs,
+    format: OutputFormat,
+) -> Result<()> {
     let (vault, _) = Vault::resolve_with_override(vault_override)?;
-    let today = LocalClock::today_utc();
+    let now = vault.now();
+    let today = resolve_day(&vault, &now, &args.day)?;
     let daily_path = vault.capture_day_path(today.clone());
 
-    vault.ensure_parents(&daily_path)?;
+    let ctx = TemplateContext::for_day(&vault, &now, &today)?;
+    let created = create_from_template(&vault, &daily_path, &vault.template_path(), "\n", &ctx)?;
 
-    if !daily_path.exists() {
-        let template_path = vault.template_path();
+    let value = json!({ "path": daily_path, "day": today.to_string(), "created": created });
+    emit(format, value, || format!("{}\n", daily_path.display()))
+}
 
-        let mut content = if template_path.exists() {
-            std::fs::read_to_string(&template_path)?
-        } else {
-            "\n".to_string()
-        };
+fn resolve_day(vault: &Vault, now: &Moment, args: &cli::DayArgs) -> Result<UtcDay> {
+    let date = args.date.as_deref().map(UtcDay::parse).transpose()?;
+    Ok(vault.resolve_day(now, date, args.offset.unwrap_or(0))?)
+}
+
+fn handle_week(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::WeekArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    let now = vault.now();
+    let day = match (&args.date, args.offset) {
+        (Some(date), _) => UtcDay::parse(date)?,
+        (None, Some(offset)) => now.day().offset_days(offset * 7)?,
+        (None, None) => now.day(),
+    };
+    let week = LocalClock::iso_week(&day)?;
+    let plan_path = vault.weekly_plan_path(week.clone());
+
+    let ctx = TemplateContext::for_week(&vault, &now, &week)?;
+    let template_path = vault.weekly_template_path();
+    let created = create_from_template(&vault, &plan_path, &template_path, "\n", &ctx)?;
+
+    let value = json!({ "path": plan_path, "week": week.to_string(), "created": created });
+    emit(format, value, || format!("{}\n", plan_path.display()))
+}
 
-        // Fill in template variables
-        content = fill_templates(content, &today);
+fn handle_journal(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::JournalArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    let now = vault.now();
+    let day = args.date.as_deref().map(UtcDay::parse).transpose()?;
 
-        std::fs::write(&daily_path, content)?;
+    let entry = open_journal(&vault, &now, args.session, day)?;
+    if let Some(backref) = &entry.backref {
+        tracing::info!("Linked from capture note as {}", backref.to_marker());
     }
 
-    println!("{}", daily_path.display());
-    Ok(())
+    let value = json!({
+        "path": entry.path,
+        "day": entry.day.to_string(),
+        "session": entry.session.as_str(),
+        "created": entry.created,
+        "backref": entry.backref.as_ref().map(|a| a.to_marker()),
+    });
+    emit(format, value, || format!("{}\n", entry.path.display()))
 }
 
-fn parse_anchor_with_auto_hhmm(anchor_str: &str) -> Result<AnchorToken> {
+fn parse_anchor_with_auto_hhmm(anchor_str: &str, now: &Moment) -> Result<AnchorToken> {
     // First try to parse as-is
     match AnchorToken::parse(anchor_str) {
         Ok(token) => Ok(token),
         Err(_) => {
             // If it fails, try to auto-append HHMM
-            let hhmm = LocalClock::now_local_hhmm();
+            let hhmm = now.local_hhmm();
 
             // Check if the anchor has a suffix separator
             let modified_anchor = if let Some(pos) = anchor_str.find("__") {
@@ -75,48 +185,408 @@ fn parse_anchor_with_auto_hhmm(anchor_str: &str) -> Result<AnchorToken> {
 
             // Try to parse the modified anchor
             AnchorToken::parse(&modified_anchor).map_err(|e| {
-                // If it still fails, provide a helpful error
-                if anchor_str.len() < 2 || anchor_str.len() > 25 {
-                    anyhow::anyhow!("Invalid anchor prefix '{}': must be between 2 and 25 characters", anchor_str)
-                } else if !anchor_str.chars().next().is_some_and(|c| c.is_ascii_lowercase()) {
-                    anyhow::anyhow!("Invalid anchor prefix '{}': must start with a lowercase letter", anchor_str)
-                } else if !anchor_str.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_') {
-                    anyhow::anyhow!("Invalid anchor prefix '{}': must contain only lowercase letters, digits, hyphens, and underscores", anchor_str)
+                // If it still fails, explain what is wrong with the prefix
+                let reason = if anchor_str.len() < 2 || anchor_str.len() > 25 {
+                    "must be between 2 and 25 characters"
+                } else if !anchor_str
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_ascii_lowercase())
+                {
+                    "must start with a lowercase letter"
+                } else if !anchor_str
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+                {
+                    "must contain only lowercase letters, digits, hyphens, and underscores"
                 } else {
-                    anyhow::anyhow!("Invalid anchor: {}", e)
+                    return e.into();
+                };
+                A4Error::InvalidAnchorToken {
+                    token: anchor_str.to_string(),
+                    reason: reason.to_string(),
                 }
+                .into()
             })
         }
     }
 }
 
-fn fill_templates(mut content: String, today: &a4_core::date::UtcDay) -> String {
-    use time::OffsetDateTime;
+fn handle_inbox(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::InboxArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    let now = vault.now();
+    let day = match args.date.as_deref() {
+        Some(date) => UtcDay::parse(date)?,
+        None => now.day(),
+    };
 
-    // Replace {{now_utc}} with current UTC timestamp in ISO 8601 format
-    let now_utc = OffsetDateTime::now_utc();
-    let now_utc_str = now_utc
-        .format(&time::format_description::well_known::Iso8601::DEFAULT)
-        .unwrap_or_else(|_| now_utc.to_string());
-    content = content.replace("{{now_utc}}", &now_utc_str);
+    let content = if args.stdin {
+        let mut buffer = String::new();
+        io::stdin().read_to_string(&mut buffer)?;
+        Some(buffer)
+    } else {
+        args.text
+    };
 
-    // Replace {{hhmm}} with current local time as HHMM
-    let hhmm = LocalClock::now_local_hhmm();
-    content = content.replace("{{hhmm}}", &hhmm);
+    match content {
+        Some(content) => {
+            let (path, written) = paste(&vault, &now, &day, &content)?;
+            let value = json!({ "path": path, "marker": written.to_marker() });
+            emit(format, value, || format!("{}\n", written.to_marker()))
+        }
+        None => {
+            let path = paste_zone(&vault, &now, &day)?;
+            let value = json!({ "path": path, "marker": null });
+            emit(format, value, || format!("{}\n", path.display()))
+        }
+    }
+}
+
+fn handle_triage(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::TriageArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    let now = vault.now();
+
+    let Some(block) = args.block else {
+        let pending = untriaged(&vault)?;
+        let rel = |path: &std::path::Path| {
+            path.strip_prefix(vault.root())
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        };
+        let value = json!(pending
+            .iter()
+            .map(|b| json!({
+                "path": rel(&b.path),
+                "day": b.day.as_ref().map(|d| d.to_string()),
+                "marker": b.block.token.to_marker(),
+                "content": b.block.content,
+            }))
+            .collect::<Vec<_>>());
+        return emit(format, value, || {
+            pending
+                .iter()
+                .map(|b| {
+                    let first = b.block.content.lines().next().unwrap_or_default();
+                    format!(
+                        "{} {}\t{}\n",
+                        rel(&b.path),
+                        b.block.token.to_marker(),
+                        first
+                    )
+                })
+                .collect()
+        });
+    };
 
-    // Replace {{YYYY-MM-DD}} with today's date
-    let date_str = format!("{:04}-{:02}-{:02}", today.year, today.month, today.day);
-    content = content.replace("{{YYYY-MM-DD}}", &date_str);
+    let marker = AnchorToken::parse(&block)?;
+    let inbox_file = match args.from {
+        Some(file) if file.is_absolute() => file,
+        Some(file) => vault.root().join(file),
+        None => vault.inbox_path(now.day()),
+    };
 
-    content
+    let target_path = if args.today || args.day.is_set() {
+        vault.capture_day_path(resolve_day(&vault, &now, &args.day)?)
+    } else if let Some(file) = args.file {
+        if file.is_absolute() {
+            file
+        } else {
+            vault.root().join(file)
+        }
+    } else {
+        anyhow::bail!("Must specify either --file, --today or --date/--offset");
+    };
+
+    let prefix = args
+        .anchor
+        .ok_or_else(|| anyhow::anyhow!("Must specify --anchor with --block"))?;
+    let heading = match args.heading.as_deref() {
+        Some(heading) => heading,
+        None => vault.config().heading_for(&prefix).ok_or_else(|| {
+            anyhow::anyhow!(
+                "No --heading given and no default heading for '{prefix}' in .a4/config.toml"
+            )
+        })?,
+    };
+
+    let target = TriageTarget {
+        file: &target_path,
+        heading,
+        prefix: &prefix,
+    };
+    let filed = triage(&vault, &now, &inbox_file, &marker, target)?;
+
+    let value = json!({
+        "from": inbox_file,
+        "block": marker.to_marker(),
+        "path": target_path,
+        "marker": filed.to_marker(),
+    });
+    emit(format, value, || format!("{}\n", filed.to_marker()))
 }
 
-fn handle_append(vault_override: Option<std::path::PathBuf>, args: cli::AppendArgs) -> Result<()> {
+fn handle_ingest(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::IngestArgs,
+    format: OutputFormat,
+) -> Result<()> {
     let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    let now = vault.now();
+
+    match args.command {
+        cli::IngestCommand::Article(args) => {
+            // The saved page usually lives outside the vault, so a relative
+            // --file is taken from the working directory
+            let source = args.file;
+            let opts = IngestOptions {
+                url: &args.url,
+                via: args.via.as_deref(),
+                title: args.title.as_deref(),
+            };
+            let ingested = ingest_article(&vault, &now, &source, opts)?;
+
+            let value = json!({
+                "path": ingested.path,
+                "slug": ingested.slug,
+                "title": ingested.title,
+                "backref": ingested.backref.to_marker(),
+            });
+            emit(format, value, || format!("{}\n", ingested.path.display()))
+        }
+    }
+}
+
+fn handle_serve(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::ServeArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    if args.token.is_empty() {
+        anyhow::bail!("--token (or A4_SERVE_TOKEN) must not be empty");
+    }
+
+    let server = tiny_http::Server::http((args.host.as_str(), args.port))
+        .map_err(|e| anyhow::anyhow!("Failed to bind {}:{}: {e}", args.host, args.port))?;
+    let addr = server
+        .server_addr()
+        .to_ip()
+        .ok_or_else(|| anyhow::anyhow!("Server is not bound to an IP address"))?;
+
+    let value = json!({ "listening": format!("http://{addr}/capture") });
+    emit(format, value, || {
+        format!("Listening on http://{addr}/capture\n")
+    })?;
+    io::stdout().flush()?;
+
+    serve::run(&vault, server, &args.token)
+}
+
+fn handle_project(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::ProjectArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    let now = vault.now();
+
+    match args.command {
+        cli::ProjectCommand::New { slug, title } => {
+            let (project, created) = create_project(&vault, &now, &slug, title.as_deref())?;
+            let value = json!({ "slug": project.slug, "path": project.index, "created": created });
+            emit(format, value, || format!("{}\n", project.index.display()))
+        }
+        cli::ProjectCommand::Log { slug, text, stdin } => {
+            let project = Project::open(&vault, &slug)?;
+            let text = if stdin {
+                let mut buffer = String::new();
+                io::stdin().read_to_string(&mut buffer)?;
+                buffer
+            } else if let Some(text) = text {
+                text
+            } else {
+                anyhow::bail!("Must specify either --text or --stdin");
+            };
+
+            let written = log_project(&vault, &now, &project, text.trim_end())?;
+            let value = json!({ "path": project.log, "marker": written.to_marker() });
+            emit(format, value, || format!("{}\n", written.to_marker()))
+        }
+        cli::ProjectCommand::Show { slug, limit } => {
+            let summary = project_summary(&Project::open(&vault, &slug)?, &now, limit)?;
+            let value = json!({
+                "slug": summary.project.slug,
+                "title": summary.title,
+                "status": summary.status,
+                "open": summary.open.iter().map(|d| json!({ "id": d.id, "task": d.task })).collect::<Vec<_>>(),
+                "recent": summary.recent.iter().map(|e| json!({
+                    "at": e.ts(),
+                    "what": e.what,
+                    "dominoes": e.dominoes,
+                })).collect::<Vec<_>>(),
+            });
+            emit(format, value, || {
+                let mut out = format!("# {}", summary.title);
+                if let Some(status) = &summary.status {
+                    out.push_str(&format!(" ({status})"));
+                }
+                out.push_str("\n\nOpen dominoes:\n");
+                if summary.open.is_empty() {
+                    out.push_str("  (none)\n");
+                }
+                for domino in &summary.open {
+                    match &domino.id {
+                        Some(id) => out.push_str(&format!("  {id}  {}\n", domino.task)),
+                        None => out.push_str(&format!("  -  {}\n", domino.task)),
+                    }
+                }
+                out.push_str("\nRecent log:\n");
+                if summary.recent.is_empty() {
+                    out.push_str("  (none)\n");
+                }
+                for entry in &summary.recent {
+                    let what = entry.what.lines().next().unwrap_or_default();
+                    let at = entry.at.to_offset(now.local_zone.offset_at(entry.at));
+                    out.push_str(&format!(
+                        "  {} {:02}:{:02}  {what}\n",
+                        at.date(),
+                        at.hour(),
+                        at.minute()
+                    ));
+                }
+                out
+            })
+        }
+    }
+}
+
+fn handle_domino(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::DominoArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    let now = vault.now();
+    let projects = |slug: Option<String>| -> Result<Vec<Project>> {
+        match slug {
+            Some(slug) => Ok(vec![find_project(&vault, &slug)?]),
+            None => Ok(list_projects(&vault)?),
+        }
+    };
 
-    let target_path = if args.today {
-        let today = LocalClock::today_utc();
-        vault.capture_day_path(today)
+    match args.command {
+        cli::DominoCommand::Add { slug, task } => {
+            let project = find_project(&vault, &slug)?;
+            let id = add_domino(Some(&vault), &project, &task, &now)?;
+            let value = json!({ "id": id, "task": task, "path": project.index });
+            emit(format, value, || format!("{id}\n"))
+        }
+        cli::DominoCommand::Complete { slug, id, what } => {
+            let project = find_project(&vault, &slug)?;
+            let line = complete_domino(&project, &id, what.as_deref(), &now)?;
+            let value: serde_json::Value = serde_json::from_str(&line)?;
+            emit(format, value, || format!("{line}\n"))
+        }
+        cli::DominoCommand::Log {
+            slug,
+            what,
+            dominoes,
+        } => {
+            let project = find_project(&vault, &slug)?;
+            let line = dominoes::append_log(&project, &what, &dominoes, &now)?;
+            let value: serde_json::Value = serde_json::from_str(&line)?;
+            emit(format, value, || format!("{line}\n"))
+        }
+        cli::DominoCommand::Status { slug } => {
+            let statuses = projects(slug)?
+                .iter()
+                .map(|project| project_status(project, &now))
+                .collect::<Result<Vec<_>, _>>()?;
+            emit(format, json!(statuses), || render_status(&statuses))
+        }
+        cli::DominoCommand::Next { slug } => {
+            let next = next_dominoes(&find_project(&vault, &slug)?)?;
+            let value = json!(next
+                .iter()
+                .map(|d| json!({ "id": d.id, "task": d.task, "estimate": d.estimate }))
+                .collect::<Vec<_>>());
+            emit(format, value, || {
+                next.iter()
+                    .map(|d| {
+                        let estimate = d.estimate.as_ref().map(|e| format!(" ({e})"));
+                        format!(
+                            "{}  {}{}\n",
+                            d.id.as_deref().unwrap_or("-"),
+                            d.task,
+                            estimate.unwrap_or_default()
+                        )
+                    })
+                    .collect()
+            })
+        }
+        cli::DominoCommand::Graph { slug, dot } => {
+            let syntax = if dot {
+                GraphSyntax::Dot
+            } else {
+                GraphSyntax::Mermaid
+            };
+            let graph = render_graph(&find_project(&vault, &slug)?, syntax)?;
+            emit(format, json!({ "graph": graph }), || graph.clone())
+        }
+        cli::DominoCommand::Lint { slug } => {
+            let mut issues = Vec::new();
+            for project in projects(slug)? {
+                issues.extend(lint_project(&project)?);
+            }
+            emit(format, json!(issues), || {
+                issues.iter().map(|issue| format!("{issue}\n")).collect()
+            })?;
+            if !issues.is_empty() {
+                anyhow::bail!("{} problem(s) found", issues.len());
+            }
+            Ok(())
+        }
+        cli::DominoCommand::Migrate { slug } => {
+            let mut assigned = Vec::new();
+            for project in projects(slug)? {
+                for (id, task) in migrate_project(Some(&vault), &project, &now)? {
+                    assigned.push((project.slug.clone(), id, task));
+                }
+            }
+            let value = json!(assigned
+                .iter()
+                .map(|(slug, id, task)| json!({ "slug": slug, "id": id, "task": task }))
+                .collect::<Vec<_>>());
+            emit(format, value, || {
+                assigned
+                    .iter()
+                    .map(|(slug, id, task)| format!("{slug}\t{id}\t{task}\n"))
+                    .collect()
+            })
+        }
+    }
+}
+
+fn handle_append(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::AppendArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+    // One instant for both the target day and the anchor's HHMM
+    let now = vault.now();
+
+    let target_path = if args.today || args.day.is_set() {
+        vault.capture_day_path(resolve_day(&vault, &now, &args.day)?)
     } else if let Some(file) = args.file {
         if file.is_absolute() {
             file
@@ -124,7 +594,7 @@ fn handle_append(vault_override: Option<std::path::PathBuf>, args: cli::AppendAr
             vault.root().join(file)
         }
     } else {
-        anyhow::bail!("Must specify either --file or --today");
+        anyhow::bail!("Must specify either --file, --today or --date/--offset");
     };
 
     let content = if args.stdin {
@@ -137,20 +607,37 @@ fn handle_append(vault_override: Option<std::path::PathBuf>, args: cli::AppendAr
         anyhow::bail!("Must specify either --text or --stdin");
     };
 
-    let anchor = parse_anchor_with_auto_hhmm(&args.anchor)?;
+    let anchor = parse_anchor_with_auto_hhmm(&args.anchor, &now)?;
+    let device = vault.device_name();
+
+    let heading = match args.heading.as_deref() {
+        Some(heading) => heading,
+        None => vault.config().heading_for(&anchor.prefix).ok_or_else(|| {
+            anyhow::anyhow!(
+                "No --heading given and no default heading for '{}' in .a4/config.toml",
+                anchor.prefix
+            )
+        })?,
+    };
 
     let opts = AppendOptions {
-        heading: &args.heading,
+        heading,
         anchor,
         content: &content,
+        device: device.as_deref(),
     };
 
-    append_block(&vault, &target_path, opts)?;
+    let written = append_block(&vault, &target_path, opts)?;
 
-    Ok(())
+    let value = json!({ "path": target_path, "marker": written.to_marker() });
+    emit(format, value, || format!("{}\n", written.to_marker()))
 }
 
-fn handle_sync(vault_override: Option<std::path::PathBuf>, args: cli::SyncArgs) -> Result<()> {
+fn handle_sync(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::SyncArgs,
+    format: OutputFormat,
+) -> Result<()> {
     let (vault, _) = Vault::resolve_with_override(vault_override)?;
 
     let mut backend = GixBackend::open(vault.root())?;
@@ -158,15 +645,19 @@ fn handle_sync(vault_override: Option<std::path::PathBuf>, args: cli::SyncArgs)
     // Stage and commit any local changes
     backend.stage_all()?;
 
-    let message = args.message.as_deref().unwrap_or("a4: sync");
-    let committed = backend.commit_if_needed(message)?;
+    let config = &vault.config().sync;
+    let message = match args.message {
+        Some(message) => message,
+        None => config.commit_message(vault.device_name().as_deref(), &vault.today()),
+    };
+    let committed = backend.commit_if_needed(&message)?;
 
     if committed {
         tracing::info!("Created commit: {}", message);
     }
 
-    let remote = args.remote.as_deref().unwrap_or("origin");
-    let branch = args.branch.as_deref();
+    let remote = args.remote.as_deref().unwrap_or(&config.remote);
+    let branch = args.branch.as_deref().or(config.branch.as_deref());
 
     // Fetch latest from remote
     backend.fetch(remote, branch)?;
@@ -177,54 +668,281 @@ fn handle_sync(vault_override: Option<std::path::PathBuf>, args: cli::SyncArgs)
         let current_branch = backend.head_branch()?;
         format!("refs/remotes/{remote}/{current_branch}")
     };
+    let (ahead, behind) = backend.ahead_behind(&remote_ref)?;
 
     // Try fast-forward first
-    let fast_forwarded = backend.fast_forward_current_branch(&remote_ref)?;
+    let fast_forward = backend.fast_forward_current_branch(&remote_ref)?;
 
-    if fast_forwarded {
+    let mut outcome = match (ahead, behind) {
+        (0, 0) => "up_to_date",
+        (0, _) => "fast_forwarded",
+        _ => "pushed",
+    };
+    let mut force = false;
+    if fast_forward == FastForward::FastForwarded {
         tracing::info!("Fast-forwarded to {}", remote_ref);
-    } else if backend.diverged(&remote_ref)? {
+    } else if fast_forward == FastForward::Diverged {
         // We have diverged - try to rebase
         tracing::info!("Detected divergence, attempting automatic rebase...");
 
         match backend.rebase_onto(&remote_ref)? {
-            a4_core::git_backend::RebaseResult::Success => {
+            RebaseResult::Success => {
                 tracing::info!("Successfully rebased onto {}", remote_ref);
                 // Force push after successful rebase
-                backend.push(remote, branch, true)?;
-                tracing::info!("Pushed rebased changes to {}", remote);
-                return Ok(());
+                outcome = "rebased";
+                force = true;
             }
-            a4_core::git_backend::RebaseResult::Conflict => {
-                // There's a conflict - inform the user
-                anyhow::bail!(
-                    "Rebase conflict detected when syncing with {}. \
-                    Please resolve the conflicts manually:\n\
-                    1. Run 'git rebase {}' in the vault directory\n\
-                    2. Resolve any conflicts\n\
-                    3. Run 'git rebase --continue' after resolving\n\
-                    4. Run 'a4 sync' again to push changes",
-                    remote_ref,
-                    remote_ref
-                );
+            RebaseResult::Conflict => {
+                // Concurrent appends to the same note merge cleanly; anything
+                // else needs a human
+                let merge_message = format!("a4: merge {remote_ref}");
+                let rollover_hour = vault.config().time.day_rollover_hour;
+                if !backend.merge_append_only(&remote_ref, &merge_message, rollover_hour)? {
+                    return Err(A4Error::SyncConflict { remote_ref }.into());
+                }
+                tracing::info!("Merged appended blocks from {}", remote_ref);
+                outcome = "merged";
             }
-            a4_core::git_backend::RebaseResult::NoRebaseNeeded => {
+            RebaseResult::NoRebaseNeeded => {
                 // We're already up to date or ahead
                 tracing::info!("No rebase needed");
             }
         }
     }
 
-    // Push changes (normal push, not force)
-    backend.push(remote, branch, false)?;
+    backend.push(remote, branch, force)?;
+    if force {
+        tracing::info!("Pushed rebased changes to {}", remote);
+    }
 
-    println!("Sync completed successfully");
+    let value = json!({
+        "remote": remote,
+        "remote_ref": remote_ref,
+        "committed": committed,
+        "ahead": ahead,
+        "behind": behind,
+        "outcome": outcome,
+    });
+    emit(format, value, || {
+        "Sync completed successfully\n".to_string()
+    })
+}
 
-    Ok(())
+fn handle_root(vault_override: Option<std::path::PathBuf>, format: OutputFormat) -> Result<()> {
+    let (vault, source) = Vault::resolve_with_override(vault_override)?;
+    let source = match source {
+        VaultRoot::FromCli(_) => "cli",
+        VaultRoot::FromEnv(_) => "env",
+        VaultRoot::FromMarker(_) => "marker",
+        VaultRoot::Default(_) => "default",
+    };
+    let value = json!({ "root": vault.root(), "source": source });
+    emit(format, value, || format!("{}\n", vault.root().display()))
 }
 
-fn handle_root(vault_override: Option<std::path::PathBuf>) -> Result<()> {
+fn handle_stitch(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::StitchArgs,
+    format: OutputFormat,
+) -> Result<()> {
     let (vault, _) = Vault::resolve_with_override(vault_override)?;
-    println!("{}", vault.root().display());
-    Ok(())
+
+    let source = if args.file.is_absolute() {
+        args.file
+    } else {
+        vault.root().join(args.file)
+    };
+
+    let rendered = stitch_note(&vault, &source)?;
+    write_rendered(
+        &vault,
+        args.output,
+        rendered,
+        json!({ "source": source }),
+        format,
+    )
+}
+
+fn handle_get(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::GetArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+
+    let path = if args.file.is_absolute() {
+        args.file
+    } else {
+        vault.root().join(args.file)
+    };
+
+    let token = AnchorToken::parse(&args.anchor)?;
+    let note = read_note(&path)?;
+    let block = find_block(&note, &token).ok_or_else(|| A4Error::BlockNotFound {
+        path: path.clone(),
+        fragment: token.to_marker(),
+    })?;
+
+    let value = json!({
+        "path": path,
+        "anchor": token.to_marker(),
+        "heading_path": block.heading_path,
+        "text": block.content,
+    });
+    emit(format, value, || format!("{}\n", block.content))
+}
+
+fn handle_meta(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::MetaArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+
+    match args.command {
+        cli::MetaCommand::Get { file, key } => {
+            let path = if file.is_absolute() {
+                file
+            } else {
+                vault.root().join(file)
+            };
+            let value = read_front_matter(&path)?.get(&key).cloned();
+            let text = value.as_ref().map(|v| format!("{}\n", render_value(v)));
+            let value = json!({ "path": path, "key": key, "value": value });
+            emit(format, value, || text.unwrap_or_default())
+        }
+        cli::MetaCommand::Set { file, key, value } => {
+            let path = if file.is_absolute() {
+                file
+            } else {
+                vault.root().join(file)
+            };
+            let front_matter = set_front_matter_field(&path, &key, parse_value(&value))?;
+            let value = json!({ "path": path, "key": key, "value": front_matter.get(&key) });
+            emit(format, value, || format!("{}\n", path.display()))
+        }
+    }
+}
+
+fn handle_search(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::SearchArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+
+    let query = SearchQuery {
+        text: args.text,
+        prefix: args.prefix,
+        from: args.from.as_deref().map(UtcDay::parse).transpose()?,
+        to: args.to.as_deref().map(UtcDay::parse).transpose()?,
+        kind: args.kind,
+        heading: args.heading,
+    };
+
+    let hits = search(&vault, &query)?;
+
+    let value = json!(hits
+        .iter()
+        .map(|hit| {
+            json!({
+                "file": hit.path,
+                "line": hit.line,
+                "heading_path": hit.heading_path,
+                "anchor": hit.anchor.as_ref().map(|a| a.to_marker()),
+                "day": hit.day.as_ref().map(|d| d.to_string()),
+                "kind": hit.kind,
+                "text": hit.text,
+            })
+        })
+        .collect::<Vec<_>>());
+    emit(format, value, || {
+        let mut out = String::new();
+        for hit in &hits {
+            out.push_str(&format!("{}:{}", hit.path.display(), hit.line));
+            if let Some(day) = &hit.day {
+                out.push_str(&format!(" [{day}]"));
+            }
+            if !hit.heading_path.is_empty() {
+                out.push_str(&format!(" {}", hit.heading_path.join(" > ")));
+            }
+            if let Some(anchor) = &hit.anchor {
+                out.push_str(&format!(" {}", anchor.to_marker()));
+            }
+            out.push('\n');
+            for line in hit.text.lines() {
+                out.push_str(&format!("    {line}\n"));
+            }
+        }
+        out
+    })
+}
+
+fn handle_collate(
+    vault_override: Option<std::path::PathBuf>,
+    args: cli::CollateArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let (vault, _) = Vault::resolve_with_override(vault_override)?;
+
+    let days = match (&args.week, &args.from, &args.to) {
+        (Some(week), _, _) => IsoWeek::parse(week)?.days()?,
+        (None, Some(from), Some(to)) => UtcDay::range(&UtcDay::parse(from)?, &UtcDay::parse(to)?)?,
+        _ => anyhow::bail!("Must specify either --week or --from and --to"),
+    };
+
+    let blocks = collate(&vault, &args.prefix, &days)?;
+    let rendered = if args.refs {
+        render_refs(&blocks)
+    } else {
+        render_stitched(&vault, &blocks)?
+    };
+
+    let blocks: Vec<_> = blocks
+        .iter()
+        .map(|b| {
+            json!({
+                "day": b.day.to_string(),
+                "path": b.path,
+                "anchor": b.block.token.to_marker(),
+                "embed": b.embed(),
+                "text": b.block.content,
+            })
+        })
+        .collect();
+    write_rendered(
+        &vault,
+        args.output,
+        rendered,
+        json!({ "blocks": blocks }),
+        format,
+    )
+}
+
+/// Write `rendered` to `output` (relative to the vault) and print its path, or
+/// print it. In JSON mode `value` gains `output` or `content`.
+fn write_rendered(
+    vault: &Vault,
+    output: Option<std::path::PathBuf>,
+    rendered: String,
+    mut value: serde_json::Value,
+    format: OutputFormat,
+) -> Result<()> {
+    match output {
+        Some(output) => {
+            let output = if output.is_absolute() {
+                output
+            } else {
+                vault.root().join(output)
+            };
+            vault.ensure_parents(&output)?;
+            std::fs::write(&output, rendered)?;
+            value["output"] = json!(output);
+            emit(format, value, || format!("{}\n", output.display()))
+        }
+        None => {
+            value["content"] = json!(rendered);
+            emit(format, value, || rendered)
+        }
+    }
 }
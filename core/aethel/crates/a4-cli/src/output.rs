@@ -0,0 +1,50 @@
+use crate::cli::OutputFormat;
+use a4_core::error::exit_code;
+use a4_core::A4Error;
+use serde_json::{json, Value};
+
+/// Print a command's result: `text` as-is, or `value` for `--format json`.
+pub fn emit(
+    format: OutputFormat,
+    value: Value,
+    text: impl FnOnce() -> String,
+) -> anyhow::Result<()> {
+    match format {
+        OutputFormat::Text => print!("{}", text()),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&value)?),
+    }
+    Ok(())
+}
+
+/// Print `err` to stderr and return the process exit code for it. In JSON
+/// mode the error is `{"error": {"code", "message", "details"}}`.
+pub fn report_error(format: OutputFormat, err: &anyhow::Error) -> i32 {
+    let (error, exit) = describe_error(err);
+
+    match format {
+        OutputFormat::Text => eprintln!("Error: {err}"),
+        OutputFormat::Json => eprintln!("{error}"),
+    }
+
+    exit
+}
+
+/// The `{"error": {...}}` body for `err` and its process exit code.
+pub fn describe_error(err: &anyhow::Error) -> (Value, i32) {
+    let (code, details, exit) = if let Some(e) = err.downcast_ref::<A4Error>() {
+        (e.code(), e.details(), e.exit_code())
+    } else if let Some(e) = err.downcast_ref::<std::io::Error>() {
+        ("io", json!({ "kind": e.kind().to_string() }), exit_code::IO)
+    } else {
+        ("error", json!({}), exit_code::GENERAL)
+    };
+
+    let error = json!({
+        "error": {
+            "code": code,
+            "message": err.to_string(),
+            "details": details,
+        }
+    });
+    (error, exit)
+}
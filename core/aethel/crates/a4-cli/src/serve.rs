@@ -0,0 +1,222 @@
+use crate::output::describe_error;
+use a4_core::error::exit_code;
+use a4_core::ingest::html_to_article;
+use a4_core::{
+    append_block, append_highlight, write_article, A4Error, AppendOptions, IngestOptions, UtcDay,
+    Vault,
+};
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Largest request body accepted, in bytes.
+const MAX_BODY: u64 = 4 * 1024 * 1024;
+
+/// A capture posted to `/capture`, tagged by `kind`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum Capture {
+    /// Quote `text` in the source note for `url`.
+    Highlight {
+        url: String,
+        text: String,
+        title: Option<String>,
+        via: Option<String>,
+    },
+    /// Convert a page to a source note linked from today.
+    Article {
+        url: String,
+        html: String,
+        title: Option<String>,
+        via: Option<String>,
+    },
+    /// `a4 append` into today's note, `date`'s note or a vault-relative `file`.
+    Append {
+        anchor: String,
+        text: String,
+        heading: Option<String>,
+        file: Option<PathBuf>,
+        date: Option<String>,
+    },
+}
+
+/// Answer `POST /capture` requests on `server` until the process is stopped.
+/// Requests must carry `Authorization: Bearer <token>`.
+pub fn run(vault: &Vault, server: Server, token: &str) -> Result<()> {
+    for mut request in server.incoming_requests() {
+        let (status, body) = match handle(vault, &mut request, token) {
+            Ok(value) => (200, value),
+            Err((status, err)) => {
+                tracing::warn!("{} {}: {err}", request.method(), request.url());
+                (status, describe_error(&err).0)
+            }
+        };
+
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(
+                Header::from_bytes("Content-Type", "application/json").expect("valid header"),
+            );
+        if let Err(e) = request.respond(response) {
+            tracing::warn!("Failed to send response: {e}");
+        }
+    }
+    Ok(())
+}
+
+fn handle(
+    vault: &Vault,
+    request: &mut Request,
+    token: &str,
+) -> Result<Value, (u16, anyhow::Error)> {
+    if request.url() != "/capture" {
+        return Err((404, anyhow::anyhow!("Unknown path {}", request.url())));
+    }
+    if *request.method() != Method::Post {
+        return Err((405, anyhow::anyhow!("Use POST for /capture")));
+    }
+    if !authorized(request, token) {
+        return Err((401, anyhow::anyhow!("Missing or invalid bearer token")));
+    }
+
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| (400, e.into()))?;
+    if body.len() as u64 > MAX_BODY {
+        return Err((413, anyhow::anyhow!("Body exceeds {MAX_BODY} bytes")));
+    }
+
+    let capture: Capture = serde_json::from_str(&body).map_err(|e| (400, e.into()))?;
+    tracing::debug!("Capture: {capture:?}");
+    capture_into(vault, capture).map_err(|err| {
+        let (_, exit) = describe_error(&err);
+        let status = if exit == exit_code::IO { 500 } else { 400 };
+        (status, err)
+    })
+}
+
+fn authorized(request: &Request, token: &str) -> bool {
+    let expected = format!("Bearer {token}");
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .is_some_and(|h| constant_time_eq(h.value.as_str().as_bytes(), expected.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Write `capture` into the vault and describe what was written.
+fn capture_into(vault: &Vault, capture: Capture) -> Result<Value> {
+    let now = vault.now();
+
+    let (path, marker) = match capture {
+        Capture::Highlight {
+            url,
+            text,
+            title,
+            via,
+        } => {
+            let opts = IngestOptions {
+                url: &url,
+                via: via.as_deref(),
+                title: title.as_deref(),
+            };
+            append_highlight(vault, &now, &text, opts)?
+        }
+        Capture::Article {
+            url,
+            html,
+            title,
+            via,
+        } => {
+            let article = html_to_article(&html);
+            if article.markdown.is_empty() {
+                anyhow::bail!("No readable content found in the posted HTML");
+            }
+            let opts = IngestOptions {
+                url: &url,
+                via: via.as_deref(),
+                title: title.as_deref(),
+            };
+            let ingested = write_article(vault, &now, article, opts)?;
+            (ingested.path, ingested.backref)
+        }
+        Capture::Append {
+            anchor,
+            text,
+            heading,
+            file,
+            date,
+        } => {
+            let target = match (file, date) {
+                (Some(file), _) => vault_relative(vault, &file)?,
+                (None, Some(date)) => vault.capture_day_path(UtcDay::parse(&date)?),
+                (None, None) => vault.capture_day_path(now.day()),
+            };
+            let anchor = super::parse_anchor_with_auto_hhmm(&anchor, &now)?;
+            let heading = match heading.as_deref() {
+                Some(heading) => heading,
+                None => vault.config().heading_for(&anchor.prefix).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "No heading given and no default heading for '{}'",
+                        anchor.prefix
+                    )
+                })?,
+            };
+            let device = vault.device_name();
+            let opts = AppendOptions {
+                heading,
+                anchor,
+                content: &text,
+                device: device.as_deref(),
+            };
+            let written = append_block(vault, &target, opts)?;
+            (target, written)
+        }
+    };
+
+    let rel = path.strip_prefix(vault.root()).unwrap_or(&path);
+    Ok(json!({ "path": rel, "marker": marker.to_marker() }))
+}
+
+/// Join a client-supplied path onto the vault root, refusing anything that
+/// could leave it or reach `.git`/`.a4`, and anything but a Markdown note.
+fn vault_relative(vault: &Vault, file: &Path) -> Result<PathBuf, A4Error> {
+    let traversal = || A4Error::PathTraversal {
+        path: file.to_path_buf(),
+    };
+    let allowed = |c: Component| match c {
+        Component::Normal(name) => name != ".git" && name != ".a4",
+        Component::CurDir => true,
+        _ => false,
+    };
+    if !file.components().all(allowed) {
+        return Err(traversal());
+    }
+    if file.extension().is_none_or(|ext| ext != "md") {
+        return Err(A4Error::NotANote {
+            path: file.to_path_buf(),
+        });
+    }
+
+    // Symlinks inside the vault must not lead out of it: resolve the
+    // deepest part of the target that exists, dangling links included
+    let target = vault.root().join(file);
+    let mut existing = target.as_path();
+    while existing.symlink_metadata().is_err() {
+        existing = existing.parent().ok_or_else(traversal)?;
+    }
+    match existing.canonicalize() {
+        Ok(resolved) if resolved.starts_with(vault.root()) => Ok(target),
+        _ => Err(traversal()),
+    }
+}
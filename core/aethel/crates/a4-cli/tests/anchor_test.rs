@@ -146,3 +146,40 @@ fn test_append_validates_invalid_prefix() {
         "Should show validation error"
     );
 }
+
+#[test]
+fn test_append_prints_suffixed_marker_on_collision() {
+    let temp_dir = TempDir::new().unwrap();
+    let test_file = temp_dir.path().join("test.md");
+    std::fs::write(&test_file, "## Focus\n\n^focus-0930\n\nEarlier\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .env("A4_DEVICE", "Work Laptop")
+        .arg("append")
+        .arg("--heading")
+        .arg("Focus")
+        .arg("--anchor")
+        .arg("focus-0930")
+        .arg("--file")
+        .arg("test.md")
+        .arg("--text")
+        .arg("Later")
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "^focus-0930__work-laptop\n"
+    );
+
+    let content = std::fs::read_to_string(&test_file).unwrap();
+    assert_eq!(content.matches("^focus-0930\n").count(), 1);
+    assert!(content.contains("^focus-0930__work-laptop\n\nLater\n"));
+}
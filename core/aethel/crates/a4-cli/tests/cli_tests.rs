@@ -86,3 +86,144 @@ fn test_append_with_text_double_dash() {
     let content = std::fs::read_to_string(&test_file).unwrap();
     assert!(content.contains("-- Remember to check this"));
 }
+
+#[test]
+fn test_get_prints_anchored_block() {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("day.md"),
+        "## End of Day\n\n^eod-2215\n\n- Shipped it\n- Early night\n\n^eod-2300\n\nNot this\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("get")
+        .arg("--file")
+        .arg("day.md")
+        .arg("--anchor")
+        .arg("^eod-2215")
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout, "- Shipped it\n- Early night\n");
+}
+
+#[test]
+fn test_get_missing_anchor_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join("day.md"), "^eod-2215\n\nText\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("get")
+        .arg("--file")
+        .arg("day.md")
+        .arg("--anchor")
+        .arg("focus-0930")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("^focus-0930"), "Unexpected error: {stderr}");
+}
+
+#[test]
+fn test_init_scaffolds_vault_with_remote() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path().join("vault");
+
+    for _ in 0..2 {
+        let mut cmd = Command::cargo_bin("a4").unwrap();
+        let output = cmd
+            .arg("--vault")
+            .arg(&vault)
+            .arg("init")
+            .arg("--remote")
+            .arg("git@example.com:me/a4-core.git")
+            .output()
+            .unwrap();
+
+        assert!(
+            output.status.success(),
+            "Command failed with output: {:?}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(
+            stdout.trim(),
+            std::fs::canonicalize(&vault).unwrap().to_str().unwrap()
+        );
+    }
+
+    assert!(vault.join(".a4/version").is_file());
+    assert!(vault.join("routines/templates/daily.md").is_file());
+    assert!(vault.join("sources/articles").is_dir());
+
+    let remote = std::process::Command::new("git")
+        .args(["remote", "get-url", "origin"])
+        .current_dir(&vault)
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&remote.stdout).trim(),
+        "git@example.com:me/a4-core.git"
+    );
+}
+
+#[test]
+fn test_today_and_append_with_date_and_offset() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .args(["today", "--date", "2025-09-01", "--offset", "-1"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout
+        .trim()
+        .ends_with("capture/2025/2025-08/2025-08-31.md"));
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", temp_dir.path())
+        .args([
+            "append",
+            "--heading",
+            "End of Day",
+            "--anchor",
+            "eod-0130",
+            "--date",
+            "2025-08-31",
+            "--text",
+            "Late night",
+        ])
+        .assert()
+        .success();
+    let content =
+        std::fs::read_to_string(temp_dir.path().join("capture/2025/2025-08/2025-08-31.md"))
+            .unwrap();
+    assert!(content.contains("^eod-0130\n\nLate night\n"));
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", temp_dir.path())
+        .args(["today", "--date", "2025-13-01"])
+        .assert()
+        .failure();
+}
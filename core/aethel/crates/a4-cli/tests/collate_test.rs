@@ -0,0 +1,74 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn setup() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let capture_dir = temp_dir.path().join("capture").join("2025").join("2025-09");
+    std::fs::create_dir_all(&capture_dir).unwrap();
+    std::fs::write(
+        capture_dir.join("2025-09-02.md"),
+        "## End of Day\n\n^eod-2215\n\n- Finished the PPU renderer\n",
+    )
+    .unwrap();
+    std::fs::write(
+        capture_dir.join("2025-09-06.md"),
+        "## End of Day\n\n^eod-2230\n\nQuiet Saturday\n",
+    )
+    .unwrap();
+    temp_dir
+}
+
+#[test]
+fn test_collate_week_as_refs() {
+    let temp_dir = setup();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("collate")
+        .arg("--prefix")
+        .arg("eod")
+        .arg("--week")
+        .arg("2025-W36")
+        .arg("--refs")
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "- ![[capture/2025-09-02#^eod-2215]]\n- ![[capture/2025-09-06#^eod-2230]]\n"
+    );
+}
+
+#[test]
+fn test_collate_date_range_stitched() {
+    let temp_dir = setup();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("collate")
+        .arg("--prefix")
+        .arg("eod")
+        .arg("--from")
+        .arg("2025-09-01")
+        .arg("--to")
+        .arg("2025-09-03")
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "### 2025-09-02\n\n- Finished the PPU renderer\n"
+    );
+}
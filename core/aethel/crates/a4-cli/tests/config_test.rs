@@ -0,0 +1,215 @@
+use assert_cmd::Command;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_config(vault: &Path, config: &str) {
+    std::fs::create_dir_all(vault.join(".a4")).unwrap();
+    std::fs::write(vault.join(".a4").join("config.toml"), config).unwrap();
+}
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+#[test]
+fn test_append_uses_configured_heading_and_device() {
+    let temp_dir = TempDir::new().unwrap();
+    write_config(
+        temp_dir.path(),
+        "device = \"desk\"\n\n[headings]\neod = \"End of Day\"\n",
+    );
+    std::fs::write(temp_dir.path().join("day.md"), "^eod-2215\n\nFirst\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .env_remove("A4_DEVICE")
+        .arg("append")
+        .arg("--anchor")
+        .arg("eod-2215")
+        .arg("--file")
+        .arg("day.md")
+        .arg("--text")
+        .arg("Second")
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "^eod-2215__desk\n");
+
+    let content = std::fs::read_to_string(temp_dir.path().join("day.md")).unwrap();
+    assert_eq!(
+        content,
+        "^eod-2215\n\nFirst\n\n## End of Day\n\n^eod-2215__desk\n\nSecond\n"
+    );
+}
+
+#[test]
+fn test_append_without_heading_or_default_fails() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("append")
+        .arg("--anchor")
+        .arg("focus-0930")
+        .arg("--file")
+        .arg("day.md")
+        .arg("--text")
+        .arg("Text")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--heading"), "Unexpected error: {stderr}");
+}
+
+#[test]
+fn test_today_uses_configured_template() {
+    let temp_dir = TempDir::new().unwrap();
+    write_config(temp_dir.path(), "[templates]\ndaily = \"tpl/day.md\"\n");
+    std::fs::create_dir_all(temp_dir.path().join("tpl")).unwrap();
+    std::fs::write(temp_dir.path().join("tpl/day.md"), "# {{YYYY-MM-DD}}\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("today")
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let content = std::fs::read_to_string(stdout.trim()).unwrap();
+    assert!(content.starts_with("# 20"), "Unexpected content: {content}");
+}
+
+#[test]
+fn test_invalid_config_is_reported() {
+    let temp_dir = TempDir::new().unwrap();
+    write_config(temp_dir.path(), "[time]\nfilename_tz = \"mars\"\n");
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("root")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("config.toml"), "Unexpected error: {stderr}");
+}
+
+#[test]
+fn test_sync_uses_configured_remote_and_message() {
+    let temp_dir = TempDir::new().unwrap();
+    git(
+        temp_dir.path(),
+        &["init", "--bare", "-b", "main", "remote.git"],
+    );
+
+    let vault = temp_dir.path().join("vault");
+    std::fs::create_dir(&vault).unwrap();
+    git(&vault, &["init", "-b", "main"]);
+    git(&vault, &["config", "user.email", "test@example.com"]);
+    git(&vault, &["config", "user.name", "Test User"]);
+    git(&vault, &["remote", "add", "upstream", "../remote.git"]);
+    write_config(
+        &vault,
+        "device = \"desk\"\n\n[sync]\nremote = \"upstream\"\nbranch = \"main\"\nmessage = \"sync from {{device}}\"\n",
+    );
+    std::fs::write(vault.join("note.md"), "hello\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", &vault)
+        .env_remove("A4_DEVICE")
+        .arg("sync")
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let remote = temp_dir.path().join("remote.git");
+    assert_eq!(
+        git(&remote, &["log", "--format=%s", "main"]),
+        "sync from desk"
+    );
+}
+
+fn today_path(vault: &Path, envs: &[(&str, &str)]) -> (String, String) {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", vault).env_remove("A4_TZ");
+    for (key, value) in envs {
+        cmd.env(key, value);
+    }
+    let output = cmd.arg("today").output().unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    (
+        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    )
+}
+
+#[test]
+fn test_filename_tz_zone_policy() {
+    // UTC+14 and UTC-11 are always on different calendar days
+    let east = TempDir::new().unwrap();
+    write_config(
+        east.path(),
+        "[time]\nfilename_tz = \"Pacific/Kiritimati\"\n",
+    );
+    let west = TempDir::new().unwrap();
+    write_config(west.path(), "[time]\nfilename_tz = \"Pacific/Pago_Pago\"\n");
+
+    let (east_path, _) = today_path(east.path(), &[]);
+    let (west_path, _) = today_path(west.path(), &[]);
+    let name = |p: &str| Path::new(p).file_name().unwrap().to_owned();
+    assert_ne!(name(&east_path), name(&west_path));
+
+    let bad = TempDir::new().unwrap();
+    write_config(bad.path(), "[time]\nfilename_tz = \"Mars/Olympus_Mons\"\n");
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", bad.path())
+        .arg("today")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("Unknown timezone 'Mars/Olympus_Mons'")
+    );
+}
+
+#[test]
+fn test_unknown_device_zone_warns() {
+    let temp_dir = TempDir::new().unwrap();
+    write_config(temp_dir.path(), "[time]\nfilename_tz = \"local\"\n");
+
+    let (path, stderr) = today_path(temp_dir.path(), &[("A4_TZ", "Nowhere/Special")]);
+    assert!(path.ends_with(".md"));
+    assert!(stderr.contains("Unknown timezone 'Nowhere/Special'"));
+}
@@ -0,0 +1,118 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn a4(vault: &TempDir, args: &[&str]) -> std::process::Output {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", vault.path())
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+#[test]
+fn test_domino_add_complete_status() {
+    let temp_dir = TempDir::new().unwrap();
+    a4(&temp_dir, &["project", "new", "gb-ppu"]);
+    a4(&temp_dir, &["project", "new", "a4"]);
+
+    let output = a4(&temp_dir, &["domino", "add", "gb-ppu", "Render tiles"]);
+    let tiles = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    assert!(tiles.starts_with("d-"));
+    let output = a4(&temp_dir, &["domino", "add", "a4", "Render tiles"]);
+    let other = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    assert_ne!(tiles, other);
+
+    let index = std::fs::read_to_string(temp_dir.path().join("projects/gb-ppu/index.md")).unwrap();
+    assert!(index.contains(&format!(
+        "dominoes:\n  - id: {tiles}\n    task: \"Render tiles\"\n"
+    )));
+
+    let output = a4(&temp_dir, &["domino", "complete", "gb-ppu", &tiles]);
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("\"what\":\"Completed: Render tiles\"")
+    );
+    a4(
+        &temp_dir,
+        &[
+            "domino",
+            "log",
+            "gb-ppu",
+            "Read the pan docs",
+            "--domino",
+            &tiles,
+        ],
+    );
+
+    let output = a4(&temp_dir, &["--format", "json", "domino", "status"]);
+    let statuses: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(statuses[0]["project"], "a4");
+    assert_eq!(statuses[0]["dominoes"][0]["done"], false);
+    assert_eq!(statuses[1]["project"], "gb-ppu");
+    assert_eq!(statuses[1]["dominoes"][0]["done"], true);
+    assert_eq!(statuses[1]["dominoes"][0]["log_refs"], 2);
+
+    a4(&temp_dir, &["domino", "lint"]);
+}
+
+#[test]
+fn test_domino_lint_fails_until_migrated() {
+    let temp_dir = TempDir::new().unwrap();
+    a4(&temp_dir, &["project", "new", "gb-ppu"]);
+    let index_path = temp_dir.path().join("projects/gb-ppu/index.md");
+    let index = std::fs::read_to_string(&index_path).unwrap();
+    std::fs::write(
+        &index_path,
+        index.replace("dominoes: []", "dominoes:\n  - Timing notes"),
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("a4")
+        .unwrap()
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .args(["domino", "lint", "gb-ppu"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Domino \"Timing notes\" has no ID"));
+
+    let output = a4(&temp_dir, &["domino", "migrate"]);
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("gb-ppu\td-"));
+    a4(&temp_dir, &["domino", "lint", "gb-ppu"]);
+}
+
+#[test]
+fn test_domino_next_and_graph() {
+    let temp_dir = TempDir::new().unwrap();
+    a4(&temp_dir, &["project", "new", "gb-ppu"]);
+    let index_path = temp_dir.path().join("projects/gb-ppu/index.md");
+    let index = std::fs::read_to_string(&index_path).unwrap();
+    std::fs::write(
+        &index_path,
+        index.replace(
+            "dominoes: []",
+            "dominoes:\n  - id: d-spr\n    task: Sprites\n    after: [d-til]\n  - id: d-til\n    task: Tiles\n    estimate: 2h",
+        ),
+    )
+    .unwrap();
+
+    let output = a4(&temp_dir, &["domino", "next", "gb-ppu"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "d-til  Tiles (2h)\n"
+    );
+
+    let output = a4(&temp_dir, &["domino", "graph", "gb-ppu"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("  d_til --> d_spr\n"));
+
+    a4(&temp_dir, &["domino", "complete", "gb-ppu", "d-til"]);
+    let output = a4(&temp_dir, &["--format", "json", "domino", "next", "gb-ppu"]);
+    let next: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(next[0]["id"], "d-spr");
+}
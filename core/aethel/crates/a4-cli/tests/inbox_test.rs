@@ -0,0 +1,79 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn a4(vault: &TempDir, args: &[&str]) -> std::process::Output {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", vault.path())
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+#[test]
+fn test_inbox_then_triage_into_daily_note() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let output = a4(
+        &temp_dir,
+        &["inbox", "--date", "2025-09-04", "--text", "Renew passport"],
+    );
+    let marker = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    assert!(marker.starts_with("^paste-"));
+
+    let inbox_path = temp_dir
+        .path()
+        .join("inbox/2025/2025-09/2025-09-04--paste-zone.md");
+    let inbox = std::fs::read_to_string(&inbox_path).unwrap();
+    assert!(inbox.starts_with("# Paste Zone 2025-09-04\n"));
+    assert!(inbox.contains("## Paste Zone\n\n^paste-"));
+
+    let output = a4(&temp_dir, &["--format", "json", "triage"]);
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert_eq!(listed[0]["marker"], marker.as_str());
+    assert_eq!(
+        listed[0]["path"],
+        "inbox/2025/2025-09/2025-09-04--paste-zone.md"
+    );
+
+    let output = a4(
+        &temp_dir,
+        &[
+            "triage",
+            "--block",
+            &marker,
+            "--from",
+            "inbox/2025/2025-09/2025-09-04--paste-zone.md",
+            "--date",
+            "2025-09-04",
+            "--heading",
+            "Tasks",
+            "--anchor",
+            "task",
+        ],
+    );
+    let filed = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    assert!(filed.starts_with("^task-"));
+
+    let capture =
+        std::fs::read_to_string(temp_dir.path().join("capture/2025/2025-09/2025-09-04.md"))
+            .unwrap();
+    assert!(capture.contains(&format!("## Tasks\n\n{filed}\n\nRenew passport\n")));
+
+    // The original block stays, followed by a record of where it went
+    let inbox = std::fs::read_to_string(&inbox_path).unwrap();
+    assert!(inbox.contains(&format!("{marker}\n\nRenew passport\n")));
+    assert!(inbox.contains(&format!(
+        "filed {marker} -> [[capture/2025/2025-09/2025-09-04#{filed}]]"
+    )));
+
+    let output = a4(&temp_dir, &["triage"]);
+    assert!(output.stdout.is_empty());
+}
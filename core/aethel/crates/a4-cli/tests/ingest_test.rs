@@ -0,0 +1,63 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+#[test]
+fn test_ingest_article_from_html() {
+    let temp_dir = TempDir::new().unwrap();
+    let downloads = TempDir::new().unwrap();
+    let page = downloads.path().join("page.html");
+    std::fs::write(
+        &page,
+        "<html><head><title>Rust &amp; Knowledge Bases</title></head>\
+         <body><nav>Menu</nav><main><p>Plain text wins.</p></main></body></html>",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .args(["--format", "json", "ingest", "article", "--file"])
+        .arg(&page)
+        .args(["--url", "https://example.com/kb", "--via", "Reader"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["slug"], "rust-knowledge-bases");
+    assert!(result["backref"].as_str().unwrap().starts_with("^read-"));
+
+    let note = std::fs::read_to_string(
+        temp_dir
+            .path()
+            .join("sources/articles/rust-knowledge-bases.md"),
+    )
+    .unwrap();
+    assert!(note.starts_with("---\nkind: source.article\n"));
+    assert!(note.contains("origin: { url: \"https://example.com/kb\", via: \"Reader\" }\n"));
+    assert!(note.ends_with(
+        "# Rust & Knowledge Bases\n\nSource: <https://example.com/kb>\n\nPlain text wins.\n"
+    ));
+}
+
+#[test]
+fn test_ingest_article_rejects_empty_page() {
+    let temp_dir = TempDir::new().unwrap();
+    let page = temp_dir.path().join("empty.html");
+    std::fs::write(&page, "<html><body><script>x()</script></body></html>").unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .args(["ingest", "article", "--file"])
+        .arg(&page)
+        .args(["--url", "https://example.com/empty"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No readable content"));
+}
@@ -0,0 +1,65 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+#[test]
+fn test_journal_creates_session_file_with_template() {
+    let temp_dir = TempDir::new().unwrap();
+    let templates = temp_dir.path().join("routines").join("templates");
+    std::fs::create_dir_all(&templates).unwrap();
+    std::fs::write(
+        templates.join("journal-morning.md"),
+        "---\nkind: {{kind}}\n---\n# {{weekday}} morning pages\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .args(["journal", "--session", "morning", "--date", "2025-09-04"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let path = stdout.trim();
+    assert!(path.ends_with("collections/journals/2025/09/journal-2025-09-04-morning.md"));
+    assert_eq!(
+        std::fs::read_to_string(path).unwrap(),
+        "---\nkind: journal.morning\n---\n# Thursday morning pages\n"
+    );
+
+    let capture =
+        std::fs::read_to_string(temp_dir.path().join("capture/2025/2025-09/2025-09-04.md"))
+            .unwrap();
+    assert!(capture.contains("## Journal\n\n^journal-"));
+    assert!(capture
+        .contains("[[collections/journals/2025/09/journal-2025-09-04-morning|Morning journal]]"));
+
+    // A second run resolves the same file without another back-reference
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", temp_dir.path())
+        .args(["journal", "--session", "morning", "--date", "2025-09-04"])
+        .assert()
+        .success();
+    let again = std::fs::read_to_string(temp_dir.path().join("capture/2025/2025-09/2025-09-04.md"))
+        .unwrap();
+    assert_eq!(again, capture);
+}
+
+#[test]
+fn test_journal_rejects_unknown_session() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .args(["journal", "--session", "noon"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("expected morning or evening"));
+}
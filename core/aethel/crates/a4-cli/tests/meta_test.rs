@@ -0,0 +1,95 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn a4(vault: &TempDir, args: &[&str]) -> std::process::Output {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", vault.path())
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+#[test]
+fn test_meta_get_and_set() {
+    let temp_dir = TempDir::new().unwrap();
+    let note = temp_dir.path().join("sources/articles/gpt.md");
+    std::fs::create_dir_all(note.parent().unwrap()).unwrap();
+    std::fs::write(
+        &note,
+        "---\nkind: source.article\nx-app: {keep: me}  # unknown\n---\n# GPT\n",
+    )
+    .unwrap();
+
+    let output = a4(
+        &temp_dir,
+        &["meta", "get", "sources/articles/gpt.md", "kind"],
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "source.article\n");
+    let output = a4(
+        &temp_dir,
+        &["meta", "get", "sources/articles/gpt.md", "aliases"],
+    );
+    assert!(output.stdout.is_empty());
+
+    a4(
+        &temp_dir,
+        &[
+            "meta",
+            "set",
+            "sources/articles/gpt.md",
+            "aliases",
+            "[gpt, sog]",
+        ],
+    );
+    a4(
+        &temp_dir,
+        &[
+            "meta",
+            "set",
+            "sources/articles/gpt.md",
+            "kind",
+            "memo.research",
+        ],
+    );
+    assert_eq!(
+        std::fs::read_to_string(&note).unwrap(),
+        "---\nkind: memo.research\nx-app: {keep: me}  # unknown\naliases:\n- gpt\n- sog\n---\n# GPT\n"
+    );
+
+    let output = a4(
+        &temp_dir,
+        &[
+            "--format",
+            "json",
+            "meta",
+            "get",
+            "sources/articles/gpt.md",
+            "aliases",
+        ],
+    );
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(value["value"], serde_json::json!(["gpt", "sog"]));
+}
+
+#[test]
+fn test_meta_reports_broken_front_matter() {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join("broken.md"), "---\nkind: [oops\n---\n").unwrap();
+
+    let output = Command::cargo_bin("a4")
+        .unwrap()
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .args(["--format", "json", "meta", "get", "broken.md", "kind"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["error"]["code"], "front_matter_parse");
+}
@@ -0,0 +1,175 @@
+use assert_cmd::Command;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn a4(vault: &Path, args: &[&str]) -> std::process::Output {
+    Command::cargo_bin("a4")
+        .unwrap()
+        .env("A4_VAULT_DIR", vault)
+        .env_remove("A4_DEVICE")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn json(bytes: &[u8]) -> serde_json::Value {
+    serde_json::from_slice(bytes).unwrap_or_else(|e| {
+        panic!("not JSON ({e}): {}", String::from_utf8_lossy(bytes));
+    })
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success(), "git {args:?} failed");
+}
+
+#[test]
+fn test_json_results() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path();
+
+    let output = a4(
+        vault,
+        &["--format", "json", "today", "--date", "2025-09-04"],
+    );
+    assert!(output.status.success());
+    let today = json(&output.stdout);
+    assert_eq!(today["day"], "2025-09-04");
+    assert_eq!(today["created"], true);
+    assert!(today["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("capture/2025/2025-09/2025-09-04.md"));
+
+    // The flag is global, so it may follow the subcommand
+    let output = a4(
+        vault,
+        &[
+            "append",
+            "--date",
+            "2025-09-04",
+            "--heading",
+            "End of Day",
+            "--anchor",
+            "eod-2215",
+            "--text",
+            "Done",
+            "--format",
+            "json",
+        ],
+    );
+    assert!(output.status.success());
+    let appended = json(&output.stdout);
+    assert_eq!(appended["marker"], "^eod-2215");
+    assert_eq!(appended["path"], today["path"]);
+
+    let output = a4(
+        vault,
+        &[
+            "--format",
+            "json",
+            "get",
+            "--file",
+            today["path"].as_str().unwrap(),
+            "--anchor",
+            "eod-2215",
+        ],
+    );
+    assert!(output.status.success());
+    assert_eq!(json(&output.stdout)["text"], "Done");
+
+    // A4_VAULT_DIR is read by clap as the --vault flag
+    let output = a4(vault, &["--format", "json", "root"]);
+    assert_eq!(json(&output.stdout)["source"], "cli");
+}
+
+#[test]
+fn test_json_errors_and_exit_codes() {
+    let temp_dir = TempDir::new().unwrap();
+    let vault = temp_dir.path();
+
+    let output = a4(
+        vault,
+        &[
+            "--format",
+            "json",
+            "append",
+            "--today",
+            "--heading",
+            "Notes",
+            "--anchor",
+            "Bad!",
+            "--text",
+            "x",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(4));
+    assert!(output.stdout.is_empty());
+    let error = json(&output.stderr);
+    assert_eq!(error["error"]["code"], "invalid_anchor");
+    assert_eq!(error["error"]["details"]["token"], "Bad!");
+    assert!(error["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("must start with a lowercase letter"));
+
+    let missing = vault.join("missing");
+    let output = Command::cargo_bin("a4")
+        .unwrap()
+        .args([
+            "--vault",
+            missing.to_str().unwrap(),
+            "--format",
+            "json",
+            "root",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(json(&output.stderr)["error"]["code"], "invalid_vault_path");
+
+    // Without a git repo, sync fails with a general error
+    let output = a4(vault, &["sync"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Error: "));
+}
+
+#[test]
+fn test_json_sync_outcome() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    git(root, &["init", "--bare", "-b", "main", "remote.git"]);
+    let local = root.join("local");
+    std::fs::create_dir(&local).unwrap();
+    git(&local, &["init", "-b", "main"]);
+    git(&local, &["config", "user.email", "test@example.com"]);
+    git(&local, &["config", "user.name", "Test User"]);
+    git(&local, &["remote", "add", "origin", "../remote.git"]);
+    std::fs::write(local.join("a.md"), "first\n").unwrap();
+    git(&local, &["add", "."]);
+    git(&local, &["commit", "-m", "Initial commit"]);
+    git(&local, &["push", "-u", "origin", "main"]);
+
+    std::fs::write(local.join("b.md"), "second\n").unwrap();
+    let output = a4(&local, &["--format", "json", "sync"]);
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let sync = json(&output.stdout);
+    assert_eq!(sync["committed"], true);
+    assert_eq!(sync["ahead"], 1);
+    assert_eq!(sync["behind"], 0);
+    assert_eq!(sync["outcome"], "pushed");
+
+    let output = a4(&local, &["--format", "json", "sync"]);
+    let sync = json(&output.stdout);
+    assert_eq!(sync["committed"], false);
+    assert_eq!(sync["outcome"], "up_to_date");
+}
@@ -0,0 +1,83 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn a4(vault: &TempDir, args: &[&str]) -> std::process::Output {
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", vault.path())
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+#[test]
+fn test_project_new_log_show() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let output = a4(
+        &temp_dir,
+        &["project", "new", "gb-ppu", "--title", "Game Boy PPU"],
+    );
+    let index = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    assert!(index.ends_with("projects/gb-ppu/index.md"));
+    let content = std::fs::read_to_string(&index).unwrap();
+    assert!(content.starts_with("---\nkind: hub.project\n"));
+    std::fs::write(
+        &index,
+        content.replace(
+            "dominoes: []",
+            "dominoes:\n  - id: d-abc\n    task: Render tiles",
+        ),
+    )
+    .unwrap();
+
+    let output = a4(
+        &temp_dir,
+        &["project", "log", "gb-ppu", "--text", "Tile fetcher works"],
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("^log-"));
+
+    let output = a4(&temp_dir, &["project", "show", "gb-ppu"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("# Game Boy PPU (active)\n"));
+    assert!(stdout.contains("  d-abc  Render tiles\n"));
+    assert!(stdout.contains("  Tile fetcher works\n"));
+
+    let output = a4(
+        &temp_dir,
+        &["--format", "json", "project", "show", "gb-ppu"],
+    );
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["open"][0]["id"], "d-abc");
+    assert_eq!(summary["recent"][0]["what"], "Tile fetcher works");
+}
+
+#[test]
+fn test_project_rejects_bad_slug_and_missing_project() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .args(["--format", "json", "project", "new", "Bad Slug"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["error"]["code"], "invalid_slug");
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .args(["project", "log", "nope", "--text", "x"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(!temp_dir.path().join("projects/nope").exists());
+}
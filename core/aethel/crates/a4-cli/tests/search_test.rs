@@ -0,0 +1,97 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn setup() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let capture_dir = temp_dir.path().join("capture").join("2025").join("2025-09");
+    std::fs::create_dir_all(&capture_dir).unwrap();
+    std::fs::write(
+        capture_dir.join("2025-09-14.md"),
+        "## End of Day\n\n^eod-2215\n\nFinished the PPU renderer\n\n## Focus\n\n^focus-0930\n\nPPU timing\n",
+    )
+    .unwrap();
+    std::fs::write(
+        capture_dir.join("2025-09-20.md"),
+        "## End of Day\n\n^eod-2230\n\nQuiet day\n",
+    )
+    .unwrap();
+    temp_dir
+}
+
+#[test]
+fn test_search_prints_text_results() {
+    let temp_dir = setup();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("search")
+        .arg("ppu")
+        .arg("--heading")
+        .arg("End of Day")
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "capture/2025/2025-09/2025-09-14.md:5 [2025-09-14] End of Day ^eod-2215\n    Finished the PPU renderer\n"
+    );
+}
+
+#[test]
+fn test_search_json_with_prefix_and_date_range() {
+    let temp_dir = setup();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("search")
+        .arg("--prefix")
+        .arg("eod")
+        .arg("--from")
+        .arg("2025-09-15")
+        .arg("--to")
+        .arg("2025-09-30")
+        .arg("--format")
+        .arg("json")
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let results: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let results = results.as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["file"], "capture/2025/2025-09/2025-09-20.md");
+    assert_eq!(results[0]["anchor"], "^eod-2230");
+    assert_eq!(results[0]["day"], "2025-09-20");
+    assert_eq!(results[0]["heading_path"][0], "End of Day");
+    assert_eq!(results[0]["text"], "Quiet day");
+}
+
+#[test]
+fn test_search_rejects_bad_date() {
+    let temp_dir = setup();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("search")
+        .arg("--from")
+        .arg("last week")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("YYYY-MM-DD"), "Unexpected error: {stderr}");
+}
@@ -0,0 +1,160 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, Command, Stdio};
+use tempfile::TempDir;
+
+struct Server {
+    child: Child,
+    addr: String,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn start(vault: &TempDir) -> Server {
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin("a4"))
+        .env("A4_VAULT_DIR", vault.path())
+        .env("A4_SERVE_TOKEN", "s3cret")
+        .args(["serve", "--port", "0"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let addr = line
+        .trim()
+        .strip_prefix("Listening on http://")
+        .and_then(|rest| rest.strip_suffix("/capture"))
+        .unwrap_or_else(|| panic!("unexpected banner: {line:?}"))
+        .to_string();
+    Server { child, addr }
+}
+
+fn post(server: &Server, token: &str, body: &str) -> (u16, serde_json::Value) {
+    let mut stream = TcpStream::connect(&server.addr).unwrap();
+    write!(
+        stream,
+        "POST /capture HTTP/1.1\r\nHost: {}\r\nAuthorization: Bearer {token}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        server.addr,
+        body.len()
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let status = response[9..12].parse().unwrap();
+    let (_, json) = response.split_once("\r\n\r\n").unwrap();
+    (status, serde_json::from_str(json).unwrap())
+}
+
+#[test]
+fn test_serve_captures_highlights_and_appends() {
+    let temp_dir = TempDir::new().unwrap();
+    let server = start(&temp_dir);
+
+    let (status, body) = post(
+        &server,
+        "s3cret",
+        r#"{"kind":"highlight","url":"https://example.com/post","title":"A Post","text":"Quote me"}"#,
+    );
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["path"], "sources/articles/a-post.md");
+    assert!(body["marker"].as_str().unwrap().starts_with("^hl-"));
+    let note = std::fs::read_to_string(temp_dir.path().join("sources/articles/a-post.md")).unwrap();
+    assert!(note.contains("## Highlights\n\n^hl-"));
+    assert!(note.contains("> Quote me\n"));
+
+    let (status, body) = post(
+        &server,
+        "s3cret",
+        r#"{"kind":"append","date":"2025-09-04","heading":"Focus","anchor":"focus-0930","text":"Ship it"}"#,
+    );
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["path"], "capture/2025/2025-09/2025-09-04.md");
+    assert_eq!(body["marker"], "^focus-0930");
+    let capture =
+        std::fs::read_to_string(temp_dir.path().join("capture/2025/2025-09/2025-09-04.md"))
+            .unwrap();
+    assert!(capture.contains("## Focus\n\n^focus-0930\n\nShip it\n"));
+}
+
+#[test]
+fn test_serve_rejects_bad_token_and_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    let server = start(&temp_dir);
+
+    let (status, body) = post(
+        &server,
+        "wrong",
+        r#"{"kind":"append","heading":"Focus","anchor":"focus","text":"x"}"#,
+    );
+    assert_eq!(status, 401);
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("bearer token"));
+
+    let (status, body) = post(
+        &server,
+        "s3cret",
+        r#"{"kind":"append","file":"../escape.md","heading":"Focus","anchor":"focus","text":"x"}"#,
+    );
+    assert_eq!(status, 400);
+    assert_eq!(body["error"]["code"], "path_traversal");
+    assert!(!temp_dir.path().join("../escape.md").exists());
+
+    let (status, _) = post(&server, "s3cret", r#"{"kind":"unknown"}"#);
+    assert_eq!(status, 400);
+}
+
+#[test]
+fn test_serve_appends_only_to_notes_inside_the_vault() {
+    let temp_dir = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+    std::fs::create_dir(temp_dir.path().join(".git")).unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(outside.path(), temp_dir.path().join("elsewhere")).unwrap();
+    let server = start(&temp_dir);
+
+    let append = |file: &str| {
+        post(
+            &server,
+            "s3cret",
+            &format!(
+                r#"{{"kind":"append","file":"{file}","heading":"Focus","anchor":"focus","text":"x"}}"#
+            ),
+        )
+    };
+
+    for file in [".git/config", ".git/hooks.md", "notes/.a4/config.md"] {
+        let (status, body) = append(file);
+        assert_eq!(status, 400, "{file}");
+        assert_eq!(body["error"]["code"], "path_traversal", "{file}");
+    }
+    assert!(!temp_dir.path().join(".git/hooks.md").exists());
+
+    let (status, body) = append("notes/todo.txt");
+    assert_eq!(status, 400);
+    assert_eq!(body["error"]["code"], "not_a_note");
+    assert!(!temp_dir.path().join("notes").exists());
+
+    #[cfg(unix)]
+    {
+        let (status, body) = append("elsewhere/escape.md");
+        assert_eq!(status, 400);
+        assert_eq!(body["error"]["code"], "path_traversal");
+        assert!(!outside.path().join("escape.md").exists());
+    }
+
+    let (status, body) = append("notes/inside.md");
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["path"], "notes/inside.md");
+}
@@ -0,0 +1,71 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+#[test]
+fn test_stitch_writes_rendered_file_without_transcludes() {
+    let temp_dir = TempDir::new().unwrap();
+    let capture_dir = temp_dir.path().join("capture").join("2025").join("2025-09");
+    std::fs::create_dir_all(&capture_dir).unwrap();
+    std::fs::write(
+        capture_dir.join("2025-09-14.md"),
+        "## End of Day\n\n^eod-2215\n\n- Finished the PPU renderer\n",
+    )
+    .unwrap();
+
+    let plan_dir = temp_dir
+        .path()
+        .join("collections")
+        .join("weekly-plans")
+        .join("2025");
+    std::fs::create_dir_all(&plan_dir).unwrap();
+    std::fs::write(
+        plan_dir.join("2025-W37.md"),
+        "## Reflection on W37\n\n- ![[capture/2025-09-14#^eod-2215]]\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("stitch")
+        .arg("--file")
+        .arg("collections/weekly-plans/2025/2025-W37.md")
+        .arg("--output")
+        .arg("publish/2025-W37.md")
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let rendered = std::fs::read_to_string(stdout.trim()).unwrap();
+    assert_eq!(
+        rendered,
+        "## Reflection on W37\n\n- Finished the PPU renderer\n"
+    );
+    assert!(!rendered.contains("![["));
+}
+
+#[test]
+fn test_stitch_fails_on_missing_block() {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join("source.md"), "^eod-2215\n\nText\n").unwrap();
+    std::fs::write(temp_dir.path().join("plan.md"), "![[source#^eod-0800]]\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("stitch")
+        .arg("--file")
+        .arg("plan.md")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("^eod-0800"), "Unexpected error: {stderr}");
+}
@@ -1,4 +1,4 @@
-use a4_core::git_backend::{GitBackend, GixBackend, RebaseResult};
+use a4_core::git_backend::{FastForward, GitBackend, GixBackend, RebaseResult};
 use std::fs;
 use std::path::Path;
 use tempfile::TempDir;
@@ -220,6 +220,521 @@ fn test_no_divergence_when_ahead() -> Result<(), Box<dyn std::error::Error>> {
         !has_diverged,
         "Should not detect divergence when we're ahead"
     );
+    assert_eq!(backend.ahead_behind("remote-branch")?, (1, 0));
+
+    Ok(())
+}
+
+fn git_output(dir: &Path, args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()?;
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+/// A bare remote plus a clone of it with an initial commit pushed to `main`.
+fn init_remote_and_clone(root: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    std::process::Command::new("git")
+        .args(["init", "--bare", "-b", "main", "remote.git"])
+        .current_dir(root)
+        .output()?;
+
+    let local = root.join("local");
+    fs::create_dir(&local)?;
+    init_test_repo(&local)?;
+    git_output(&local, &["remote", "add", "origin", "../remote.git"])?;
+    create_commit(&local, "file1.txt", "initial content", "Initial commit")?;
+    git_output(&local, &["push", "-u", "origin", "main"])?;
+
+    Ok(())
+}
+
+#[test]
+fn test_stage_and_commit_respects_gitignore() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path();
+    init_test_repo(repo_path)?;
+    create_commit(repo_path, "file1.txt", "initial content", "Initial commit")?;
+
+    fs::write(repo_path.join(".gitignore"), "*.tmp\nbuild/\n")?;
+    fs::write(repo_path.join("file1.txt"), "changed content")?;
+    fs::create_dir_all(repo_path.join("notes/nested"))?;
+    fs::write(repo_path.join("notes/nested/note.md"), "# Note\n")?;
+    fs::write(repo_path.join("scratch.tmp"), "ignored")?;
+    fs::create_dir_all(repo_path.join("build"))?;
+    fs::write(repo_path.join("build/out.txt"), "ignored")?;
+
+    let mut backend = GixBackend::open(repo_path)?;
+    assert!(backend.has_uncommitted_changes()?);
+
+    backend.stage_all()?;
+    assert!(backend.commit_if_needed("a4: sync")?);
+    assert!(!backend.has_uncommitted_changes()?);
+    assert!(!backend.commit_if_needed("a4: sync")?);
+
+    let files = git_output(repo_path, &["ls-files"])?;
+    assert_eq!(files, ".gitignore\nfile1.txt\nnotes/nested/note.md");
+    assert_eq!(git_output(repo_path, &["status", "--porcelain"])?, "");
+    assert_eq!(
+        git_output(repo_path, &["log", "-1", "--format=%s"])?,
+        "a4: sync"
+    );
+
+    // Deletions are staged too
+    fs::remove_file(repo_path.join("notes/nested/note.md"))?;
+    backend.stage_all()?;
+    assert!(backend.commit_if_needed("a4: remove note")?);
+    assert_eq!(
+        git_output(repo_path, &["ls-files"])?,
+        ".gitignore\nfile1.txt"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_commit_on_unborn_branch() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path();
+    init_test_repo(repo_path)?;
+    fs::write(repo_path.join("note.md"), "hello\n")?;
+
+    let mut backend = GixBackend::open(repo_path)?;
+    assert_eq!(backend.head_branch()?, "main");
+    backend.stage_all()?;
+    assert!(backend.commit_if_needed("a4: first")?);
+    assert_eq!(git_output(repo_path, &["log", "--format=%s"])?, "a4: first");
+
+    Ok(())
+}
+
+#[test]
+fn test_fetch_and_fast_forward() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    init_remote_and_clone(temp_dir.path())?;
+    let local = temp_dir.path().join("local");
+
+    // Another device pushes a new commit
+    let other = temp_dir.path().join("other");
+    git_output(temp_dir.path(), &["clone", "remote.git", "other"])?;
+    git_output(&other, &["config", "user.email", "test@example.com"])?;
+    git_output(&other, &["config", "user.name", "Test User"])?;
+    fs::create_dir_all(other.join("capture"))?;
+    create_commit(
+        &other,
+        "capture/day.md",
+        "from other device",
+        "Other commit",
+    )?;
+    git_output(&other, &["push", "origin", "main"])?;
+
+    let mut backend = GixBackend::open(&local)?;
+    backend.fetch("origin", Some("main"))?;
+    assert!(!backend.diverged("refs/remotes/origin/main")?);
+    assert_eq!(
+        backend.fast_forward_current_branch("refs/remotes/origin/main")?,
+        FastForward::FastForwarded
+    );
+    assert_eq!(
+        backend.fast_forward_current_branch("refs/remotes/origin/main")?,
+        FastForward::UpToDate
+    );
+
+    assert_eq!(
+        fs::read_to_string(local.join("capture/day.md"))?,
+        "from other device"
+    );
+    assert_eq!(
+        git_output(&local, &["log", "-1", "--format=%s"])?,
+        "Other commit"
+    );
+    assert_eq!(git_output(&local, &["status", "--porcelain"])?, "");
+
+    Ok(())
+}
+
+#[test]
+fn test_fetch_from_missing_remote() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    init_test_repo(temp_dir.path())?;
+
+    let mut backend = GixBackend::open(temp_dir.path())?;
+    let err = backend.fetch("origin", None).unwrap_err();
+    assert!(matches!(err, a4_core::A4Error::NoRemote));
+
+    Ok(())
+}
+
+#[test]
+fn test_push_to_local_remote() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    init_remote_and_clone(temp_dir.path())?;
+    let local = temp_dir.path().join("local");
+    let remote = temp_dir.path().join("remote.git");
+
+    fs::write(local.join("file2.txt"), "new content")?;
+    let mut backend = GixBackend::open(&local)?;
+    backend.stage_all()?;
+    assert!(backend.commit_if_needed("a4: sync")?);
+    backend.push("origin", None, false)?;
+
+    let local_head = git_output(&local, &["rev-parse", "HEAD"])?;
+    assert_eq!(git_output(&remote, &["rev-parse", "main"])?, local_head);
+    assert_eq!(
+        git_output(&local, &["rev-parse", "refs/remotes/origin/main"])?,
+        local_head
+    );
+    assert_eq!(
+        git_output(&remote, &["show", "main:file2.txt"])?,
+        "new content"
+    );
+    assert!(std::process::Command::new("git")
+        .args(["fsck", "--strict"])
+        .current_dir(&remote)
+        .status()?
+        .success());
+
+    Ok(())
+}
+
+#[test]
+fn test_push_rejects_non_fast_forward() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    init_remote_and_clone(temp_dir.path())?;
+    let local = temp_dir.path().join("local");
+
+    let other = temp_dir.path().join("other");
+    git_output(temp_dir.path(), &["clone", "remote.git", "other"])?;
+    git_output(&other, &["config", "user.email", "test@example.com"])?;
+    git_output(&other, &["config", "user.name", "Test User"])?;
+    create_commit(&other, "other.txt", "other", "Other commit")?;
+    git_output(&other, &["push", "origin", "main"])?;
+
+    create_commit(&local, "file2.txt", "local", "Local commit")?;
+    let mut backend = GixBackend::open(&local)?;
+    assert!(backend.push("origin", None, false).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_merge_append_only_unions_daily_blocks() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path();
+    init_test_repo(repo_path)?;
+
+    let base = "## Focus\n\n^focus-0900\n\nPlan\n";
+    create_commit(repo_path, "day.md", base, "Initial commit")?;
+
+    git_output(repo_path, &["checkout", "-b", "remote-branch"])?;
+    create_commit(
+        repo_path,
+        "day.md",
+        &format!("{base}\n^focus-0930__iphone\n\nPhone\n"),
+        "Remote commit",
+    )?;
+
+    git_output(repo_path, &["checkout", "main"])?;
+    create_commit(
+        repo_path,
+        "day.md",
+        &format!("{base}\n^focus-1000__laptop\n\nLaptop\n"),
+        "Local commit",
+    )?;
+
+    let mut backend = GixBackend::open(repo_path)?;
+    assert_eq!(
+        backend.rebase_onto("remote-branch")?,
+        RebaseResult::Conflict
+    );
+    assert!(backend.merge_append_only("remote-branch", "a4: merge", 0)?);
+
+    assert_eq!(
+        fs::read_to_string(repo_path.join("day.md"))?,
+        format!("{base}\n^focus-0930__iphone\n\nPhone\n\n^focus-1000__laptop\n\nLaptop\n")
+    );
+    assert_eq!(git_output(repo_path, &["status", "--porcelain"])?, "");
+    assert_eq!(
+        git_output(repo_path, &["log", "-1", "--format=%s %p"])?
+            .split(' ')
+            .count(),
+        4
+    );
+    assert!(!backend.diverged("remote-branch")?);
+
+    Ok(())
+}
+
+#[test]
+fn test_merge_append_only_rejects_rewrites() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let repo_path = temp_dir.path();
+    init_test_repo(repo_path)?;
+    create_commit(
+        repo_path,
+        "day.md",
+        "^focus-0900\n\nPlan\n",
+        "Initial commit",
+    )?;
+
+    git_output(repo_path, &["checkout", "-b", "remote-branch"])?;
+    create_commit(
+        repo_path,
+        "day.md",
+        "^focus-0900\n\nNew plan\n",
+        "Remote commit",
+    )?;
+
+    git_output(repo_path, &["checkout", "main"])?;
+    create_commit(
+        repo_path,
+        "day.md",
+        "^focus-0900\n\nPlan\n\n^focus-1000\n\nMore\n",
+        "Local commit",
+    )?;
+
+    let head = git_output(repo_path, &["rev-parse", "HEAD"])?;
+    let mut backend = GixBackend::open(repo_path)?;
+    assert!(!backend.merge_append_only("remote-branch", "a4: merge", 0)?);
+    assert_eq!(git_output(repo_path, &["rev-parse", "HEAD"])?, head);
+
+    Ok(())
+}
+
+#[test]
+fn test_sync_diverged_without_git_binary() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    init_remote_and_clone(temp_dir.path())?;
+    let local = temp_dir.path().join("local");
+    let remote = temp_dir.path().join("remote.git");
+
+    let other = temp_dir.path().join("other");
+    git_output(temp_dir.path(), &["clone", "remote.git", "other"])?;
+    git_output(&other, &["config", "user.email", "test@example.com"])?;
+    git_output(&other, &["config", "user.name", "Test User"])?;
+    create_commit(&other, "other.txt", "other", "Other commit")?;
+    git_output(&other, &["push", "origin", "main"])?;
+    create_commit(&local, "file2.txt", "local", "Local commit")?;
+
+    // No git on PATH: everything, including the divergent case, runs on gix
+    let output = assert_cmd::Command::cargo_bin("a4")?
+        .env("PATH", "")
+        .env("A4_VAULT_DIR", &local)
+        .args(["sync", "--message", "a4: sync"])
+        .output()?;
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    assert_eq!(fs::read_to_string(local.join("other.txt"))?, "other");
+    assert_eq!(
+        git_output(&remote, &["rev-parse", "main"])?,
+        git_output(&local, &["rev-parse", "HEAD"])?
+    );
+    assert_eq!(git_output(&remote, &["show", "main:file2.txt"])?, "local");
+    assert_eq!(git_output(&local, &["status", "--porcelain"])?, "");
+
+    Ok(())
+}
+
+/// `git daemon` serving the repositories under a directory over `git://`,
+/// with pushes enabled; stopped on drop.
+struct GitDaemon {
+    child: std::process::Child,
+    port: u16,
+}
+
+impl GitDaemon {
+    fn start(base: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")?
+            .local_addr()?
+            .port();
+        // Run git-daemon itself: `git daemon` would leave it behind on kill
+        let exec_path = git_output(base, &["--exec-path"])?;
+        let child = std::process::Command::new(Path::new(&exec_path).join("git-daemon"))
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .arg("--reuseaddr")
+            .arg("--listen=127.0.0.1")
+            .arg(format!("--port={port}"))
+            .arg(format!("--base-path={}", base.display()))
+            .args(["--export-all", "--enable=receive-pack"])
+            .arg(base)
+            .spawn()?;
+        let daemon = GitDaemon { child, port };
+
+        for _ in 0..100 {
+            if std::net::TcpStream::connect(("127.0.0.1", port)).is_ok() {
+                return Ok(daemon);
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        Err("git daemon did not start".into())
+    }
+
+    fn url(&self, repo: &str) -> String {
+        format!("git://127.0.0.1:{}/{repo}", self.port)
+    }
+}
+
+impl Drop for GitDaemon {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn test_sync_over_network_without_git_binary() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    init_remote_and_clone(temp_dir.path())?;
+    let local = temp_dir.path().join("local");
+    let remote = temp_dir.path().join("remote.git");
+
+    let other = temp_dir.path().join("other");
+    git_output(temp_dir.path(), &["clone", "remote.git", "other"])?;
+    git_output(&other, &["config", "user.email", "test@example.com"])?;
+    git_output(&other, &["config", "user.name", "Test User"])?;
+    create_commit(&other, "other.txt", "other", "Other commit")?;
+    git_output(&other, &["push", "origin", "main"])?;
+    create_commit(&local, "file2.txt", "local", "Local commit")?;
+
+    let daemon = GitDaemon::start(temp_dir.path())?;
+    git_output(
+        &local,
+        &["remote", "set-url", "origin", &daemon.url("remote.git")],
+    )?;
+
+    // Fetch and push both go through gix's transport
+    let output = assert_cmd::Command::cargo_bin("a4")?
+        .env("PATH", "")
+        .env("A4_VAULT_DIR", &local)
+        .args(["sync", "--message", "a4: sync"])
+        .output()?;
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let local_head = git_output(&local, &["rev-parse", "HEAD"])?;
+    assert_eq!(git_output(&remote, &["rev-parse", "main"])?, local_head);
+    assert_eq!(
+        git_output(&local, &["rev-parse", "refs/remotes/origin/main"])?,
+        local_head
+    );
+    assert_eq!(git_output(&remote, &["show", "main:file2.txt"])?, "local");
+    assert!(std::process::Command::new("git")
+        .args(["fsck", "--strict"])
+        .current_dir(&remote)
+        .status()?
+        .success());
+
+    // A push the remote would lose commits to is refused before sending
+    create_commit(&other, "more.txt", "more", "More")?;
+    git_output(&other, &["pull", "--no-rebase", "origin", "main"])?;
+    git_output(&other, &["push", "origin", "main"])?;
+    create_commit(&local, "file3.txt", "local", "Another local commit")?;
+    let mut backend = GixBackend::open(&local)?;
+    assert!(backend.push("origin", None, false).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_fetch_single_branch() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    init_remote_and_clone(temp_dir.path())?;
+    let local = temp_dir.path().join("local");
+    git_output(&local, &["push", "origin", "main:drafts"])?;
+    git_output(&local, &["update-ref", "-d", "refs/remotes/origin/drafts"])?;
+
+    let mut backend = GixBackend::open(&local)?;
+    backend.fetch("origin", Some("main"))?;
+    assert_eq!(
+        git_output(&local, &["branch", "-r", "--format=%(refname)"])?,
+        "refs/remotes/origin/main"
+    );
+
+    backend.fetch("origin", None)?;
+    assert!(git_output(&local, &["branch", "-r"])?.contains("origin/drafts"));
+
+    Ok(())
+}
+
+fn a4(vault: &Path, args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    let output = assert_cmd::Command::cargo_bin("a4")?
+        .env("A4_VAULT_DIR", vault)
+        .args(args)
+        .output()?;
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+#[test]
+fn test_sync_merges_appends_from_two_devices() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    init_remote_and_clone(temp_dir.path())?;
+    let laptop = temp_dir.path().join("local");
+    let remote = temp_dir.path().join("remote.git");
+    create_commit(
+        &laptop,
+        "day.md",
+        "## Focus\n\n^focus-0900\n\nPlan\n",
+        "Day",
+    )?;
+    git_output(&laptop, &["push", "origin", "main"])?;
+
+    let phone = temp_dir.path().join("other");
+    git_output(temp_dir.path(), &["clone", "remote.git", "other"])?;
+    git_output(&phone, &["config", "user.email", "test@example.com"])?;
+    git_output(&phone, &["config", "user.name", "Test User"])?;
+
+    // Both devices append under the same heading before either syncs
+    let append = |vault: &Path, anchor: &str, text: &str| {
+        a4(
+            vault,
+            &[
+                "append",
+                "--file",
+                "day.md",
+                "--heading",
+                "Focus",
+                "--anchor",
+                anchor,
+                "--text",
+                text,
+            ],
+        )
+    };
+    append(&phone, "focus-0930", "Phone")?;
+    append(&laptop, "focus-1000", "Laptop")?;
+
+    a4(&phone, &["sync"])?;
+    let sync: serde_json::Value =
+        serde_json::from_str(&a4(&laptop, &["--format", "json", "sync"])?)?;
+    assert_eq!(sync["outcome"], "merged");
+
+    let merged = fs::read_to_string(laptop.join("day.md"))?;
+    let phone_at = merged.find("^focus-0930").expect("phone block kept");
+    let laptop_at = merged.find("^focus-1000").expect("laptop block kept");
+    assert!(phone_at < laptop_at);
+    assert_eq!(merged.matches("## Focus").count(), 1);
+    assert_eq!(
+        git_output(&remote, &["rev-parse", "main"])?,
+        git_output(&laptop, &["rev-parse", "HEAD"])?
+    );
+
+    // The phone fast-forwards to the merge
+    a4(&phone, &["sync"])?;
+    assert_eq!(fs::read_to_string(phone.join("day.md"))?, merged);
 
     Ok(())
 }
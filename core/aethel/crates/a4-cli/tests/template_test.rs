@@ -138,3 +138,109 @@ Date again: {{YYYY-MM-DD}}
         "No template markers should remain in the file"
     );
 }
+
+#[test]
+fn test_week_command_creates_plan_from_template() {
+    let temp_dir = TempDir::new().unwrap();
+    let template_path = temp_dir
+        .path()
+        .join("routines")
+        .join("templates")
+        .join("weekly.md");
+    std::fs::create_dir_all(template_path.parent().unwrap()).unwrap();
+    std::fs::write(
+        &template_path,
+        "# Week {{week}} ({{monday}} to {{sunday}})\n\n{{day_links}}\n\n## Reflection on W{{week_number}}\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("week")
+        .arg("--date")
+        .arg("2025-09-04")
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "Command failed with output: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let plan_path = stdout.trim();
+    assert!(plan_path.ends_with("collections/weekly-plans/2025/week-2025-W36.md"));
+
+    let content = std::fs::read_to_string(plan_path).unwrap();
+    assert!(content.starts_with("# Week 2025-W36 (2025-09-01 to 2025-09-07)\n"));
+    assert!(content.contains("- [[capture/2025-09-01]]\n"));
+    assert!(content.contains("- [[capture/2025-09-07]]\n"));
+    assert!(content.contains("## Reflection on W36\n"));
+
+    // An existing plan is left alone
+    std::fs::write(plan_path, "Mine\n").unwrap();
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    cmd.env("A4_VAULT_DIR", temp_dir.path())
+        .args(["week", "--date", "2025-09-07"])
+        .assert()
+        .success();
+    assert_eq!(std::fs::read_to_string(plan_path).unwrap(), "Mine\n");
+}
+
+#[test]
+fn test_week_command_offset() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .args(["week", "--offset", "-1"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.trim().contains("collections/weekly-plans/"));
+    assert!(std::path::Path::new(stdout.trim()).exists());
+}
+
+#[test]
+fn test_today_rejects_unknown_placeholder() {
+    let temp_dir = TempDir::new().unwrap();
+    let templates = temp_dir.path().join("routines").join("templates");
+    std::fs::create_dir_all(templates.join("partials")).unwrap();
+    std::fs::write(
+        templates.join("daily.md"),
+        "# {{weekday}} {{date}}\n{{> header}}\n{{mystery}}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        templates.join("partials").join("header.md"),
+        "Yesterday: {{yesterday_link}}\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("a4").unwrap();
+    let output = cmd
+        .env("A4_VAULT_DIR", temp_dir.path())
+        .arg("today")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Unknown template variable '{{mystery}}' on line 3"));
+
+    // Nothing is written when rendering fails
+    assert!(!temp_dir
+        .path()
+        .join("capture")
+        .read_dir()
+        .unwrap()
+        .any(|e| {
+            let e = e.unwrap();
+            e.path().is_file()
+        }));
+}
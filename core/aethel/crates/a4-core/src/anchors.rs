@@ -64,6 +64,15 @@ impl AnchorToken {
         })
     }
 
+    /// The same prefix and time with a different suffix.
+    pub fn with_suffix(&self, suffix: &str) -> Self {
+        AnchorToken {
+            prefix: self.prefix.clone(),
+            hhmm: self.hhmm.clone(),
+            suffix: Some(suffix.to_string()),
+        }
+    }
+
     pub fn to_marker(&self) -> String {
         match &self.suffix {
             Some(s) => format!("^{}-{}__{}", self.prefix, self.hhmm, s),
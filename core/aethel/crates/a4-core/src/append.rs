@@ -1,4 +1,5 @@
 use crate::anchors::AnchorToken;
+use crate::blocks::anchored_blocks;
 use crate::error::A4Error;
 use crate::headings::ensure_h2_heading;
 use crate::notes::{join_front_matter, read_note, write_note};
@@ -9,9 +10,18 @@ pub struct AppendOptions<'a> {
     pub heading: &'a str,
     pub anchor: AnchorToken,
     pub content: &'a str,
+    /// Suffix used to disambiguate the anchor if the file already has it.
+    pub device: Option<&'a str>,
 }
 
-pub fn append_block(vault: &Vault, file: &Path, opts: AppendOptions) -> Result<(), A4Error> {
+/// Append `opts.content` under `opts.anchor` and return the marker that was
+/// actually written, which carries a device suffix when the requested anchor
+/// already exists in the file.
+pub fn append_block(
+    vault: &Vault,
+    file: &Path,
+    opts: AppendOptions,
+) -> Result<AnchorToken, A4Error> {
     vault.ensure_parents(file)?;
 
     let (front_matter, mut body) = if file.exists() {
@@ -21,6 +31,8 @@ pub fn append_block(vault: &Vault, file: &Path, opts: AppendOptions) -> Result<(
         (None, String::new())
     };
 
+    let anchor = unique_anchor(&body, opts.anchor, opts.device);
+
     let (updated_body, _) = ensure_h2_heading(&body, opts.heading);
     body = updated_body;
 
@@ -43,14 +55,40 @@ pub fn append_block(vault: &Vault, file: &Path, opts: AppendOptions) -> Result<(
     // Add anchor with double newline after it, then content
     body.push_str(&format!(
         "{}\n\n{}\n",
-        opts.anchor.to_marker(),
+        anchor.to_marker(),
         interpreted_content
     ));
 
     let final_content = join_front_matter(front_matter.as_deref(), &body);
     write_note(file, &final_content)?;
 
-    Ok(())
+    Ok(anchor)
+}
+
+/// Pick a marker not yet used in `body`: the requested anchor, then the anchor
+/// with the device suffix, then numbered variants (`__laptop-2`, or `__2`
+/// without a device).
+fn unique_anchor(body: &str, anchor: AnchorToken, device: Option<&str>) -> AnchorToken {
+    let existing: Vec<AnchorToken> = anchored_blocks(body).into_iter().map(|b| b.token).collect();
+    if !existing.contains(&anchor) {
+        return anchor;
+    }
+
+    let base = anchor.suffix.clone().or_else(|| device.map(str::to_string));
+    if let Some(base) = &base {
+        let candidate = anchor.with_suffix(base);
+        if !existing.contains(&candidate) {
+            return candidate;
+        }
+    }
+
+    (2..)
+        .map(|n| match &base {
+            Some(base) => anchor.with_suffix(&format!("{base}-{n}")),
+            None => anchor.with_suffix(&n.to_string()),
+        })
+        .find(|candidate| !existing.contains(candidate))
+        .expect("unbounded suffix search")
 }
 
 #[cfg(test)]
@@ -70,6 +108,7 @@ mod tests {
             heading: "Focus",
             anchor,
             content: "Test content",
+            device: None,
         };
 
         append_block(&vault, &file, opts).unwrap();
@@ -90,6 +129,7 @@ mod tests {
             heading: "Focus",
             anchor: anchor1,
             content: "First block",
+            device: None,
         };
         append_block(&vault, &file, opts1).unwrap();
 
@@ -98,6 +138,7 @@ mod tests {
             heading: "Focus",
             anchor: anchor2,
             content: "Second block",
+            device: None,
         };
         append_block(&vault, &file, opts2).unwrap();
 
@@ -127,6 +168,7 @@ mod tests {
             heading: "Focus",
             anchor,
             content: "New content",
+            device: None,
         };
 
         append_block(&vault, &file, opts).unwrap();
@@ -153,6 +195,7 @@ mod tests {
             heading: "Focus",
             anchor,
             content: "New content",
+            device: None,
         };
 
         append_block(&vault, &file, opts).unwrap();
@@ -176,6 +219,7 @@ mod tests {
             heading: "Tasks",
             anchor,
             content: "Line one\\nLine two\\n\\nLine three with double newline before",
+            device: None,
         };
 
         append_block(&vault, &file, opts).unwrap();
@@ -199,6 +243,7 @@ mod tests {
             heading: "Notes",
             anchor,
             content: "Path: C:\\\\Users\\\\Documents\\nTab here:\\tvalue",
+            device: None,
         };
 
         append_block(&vault, &file, opts).unwrap();
@@ -210,4 +255,53 @@ mod tests {
             "\n\n## Notes\n\n^note-1100\n\nPath: C:\\Users\\Documents\nTab here:\tvalue\n"
         );
     }
+
+    #[test]
+    fn test_append_suffixes_colliding_anchor() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let file = temp_dir.path().join("test.md");
+
+        let mut written = Vec::new();
+        for content in ["First", "Second", "Third"] {
+            let opts = AppendOptions {
+                heading: "Focus",
+                anchor: AnchorToken::parse("focus-0930").unwrap(),
+                content,
+                device: Some("laptop"),
+            };
+            written.push(append_block(&vault, &file, opts).unwrap().to_marker());
+        }
+
+        assert_eq!(
+            written,
+            vec![
+                "^focus-0930",
+                "^focus-0930__laptop",
+                "^focus-0930__laptop-2"
+            ]
+        );
+        let content = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(
+            content,
+            "\n\n## Focus\n\n^focus-0930\n\nFirst\n\n^focus-0930__laptop\n\nSecond\n\n^focus-0930__laptop-2\n\nThird\n"
+        );
+    }
+
+    #[test]
+    fn test_append_numeric_suffix_without_device() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let file = temp_dir.path().join("test.md");
+        std::fs::write(&file, "^eod-2215\n\nDone\n\n^eod-2215__2\n\nAgain\n").unwrap();
+
+        let opts = AppendOptions {
+            heading: "End of Day",
+            anchor: AnchorToken::parse("eod-2215").unwrap(),
+            content: "Once more",
+            device: None,
+        };
+        let written = append_block(&vault, &file, opts).unwrap();
+        assert_eq!(written.to_marker(), "^eod-2215__3");
+    }
 }
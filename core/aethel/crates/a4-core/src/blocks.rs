@@ -0,0 +1,232 @@
+use crate::anchors::AnchorToken;
+use crate::notes::Note;
+use regex::Regex;
+use std::sync::OnceLock;
+
+static HEADING_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// One structural piece of a note body. Line numbers are 1-based and relative
+/// to the body (front matter excluded).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Segment {
+    Heading {
+        line: usize,
+        level: usize,
+        text: String,
+    },
+    Anchor {
+        line: usize,
+        token: AnchorToken,
+    },
+    Block {
+        line: usize,
+        text: String,
+    },
+}
+
+/// An anchor marker together with the block it precedes and the headings it
+/// sits under (outermost first).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnchoredBlock {
+    pub token: AnchorToken,
+    pub line: usize,
+    pub heading_path: Vec<String>,
+    pub content: String,
+}
+
+/// Parse a Markdown heading line into `(level, text)`.
+pub fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let re = HEADING_REGEX.get_or_init(|| Regex::new(r"^(#{1,6})\s+(.+?)(?:\s+#+)?\s*$").unwrap());
+    re.captures(line).map(|caps| {
+        let level = caps.get(1).unwrap().as_str().len();
+        let text = caps.get(2).unwrap().as_str();
+        (level, text)
+    })
+}
+
+/// Parse a standalone `^token` marker line.
+pub fn parse_anchor_line(line: &str) -> Option<AnchorToken> {
+    let trimmed = line.trim();
+    if !trimmed.starts_with('^') {
+        return None;
+    }
+    AnchorToken::parse(trimmed).ok()
+}
+
+/// Split a note body into headings, anchors and blocks. A block runs until a
+/// blank line, the next heading or the next anchor; fenced code is kept whole.
+pub fn parse_segments(body: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut block: Option<(usize, Vec<&str>)> = None;
+    let mut in_fence = false;
+
+    fn flush(block: &mut Option<(usize, Vec<&str>)>, segments: &mut Vec<Segment>) {
+        if let Some((line, lines)) = block.take() {
+            segments.push(Segment::Block {
+                line,
+                text: lines.join("\n"),
+            });
+        }
+    }
+
+    for (idx, line) in body.lines().enumerate() {
+        let line_no = idx + 1;
+        let is_fence = {
+            let trimmed = line.trim_start();
+            trimmed.starts_with("```") || trimmed.starts_with("~~~")
+        };
+
+        if in_fence {
+            block.get_or_insert((line_no, Vec::new())).1.push(line);
+            in_fence = !is_fence;
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            flush(&mut block, &mut segments);
+        } else if let Some((level, text)) = parse_heading(line) {
+            flush(&mut block, &mut segments);
+            segments.push(Segment::Heading {
+                line: line_no,
+                level,
+                text: text.to_string(),
+            });
+        } else if let Some(token) = parse_anchor_line(line) {
+            flush(&mut block, &mut segments);
+            segments.push(Segment::Anchor {
+                line: line_no,
+                token,
+            });
+        } else {
+            block.get_or_insert((line_no, Vec::new())).1.push(line);
+            in_fence = is_fence;
+        }
+    }
+    flush(&mut block, &mut segments);
+
+    segments
+}
+
+/// Every anchor in `body` with the block that follows it. An anchor directly
+/// followed by a heading, another anchor or EOF has empty content.
+pub fn anchored_blocks(body: &str) -> Vec<AnchoredBlock> {
+    let mut result: Vec<AnchoredBlock> = Vec::new();
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    let mut pending: Option<AnchoredBlock> = None;
+
+    for segment in parse_segments(body) {
+        match segment {
+            Segment::Heading { level, text, .. } => {
+                result.extend(pending.take());
+                headings.retain(|(l, _)| *l < level);
+                headings.push((level, text));
+            }
+            Segment::Anchor { line, token } => {
+                result.extend(pending.take());
+                pending = Some(AnchoredBlock {
+                    token,
+                    line,
+                    heading_path: headings.iter().map(|(_, t)| t.clone()).collect(),
+                    content: String::new(),
+                });
+            }
+            Segment::Block { text, .. } => {
+                if let Some(mut anchored) = pending.take() {
+                    anchored.content = text;
+                    result.push(anchored);
+                }
+            }
+        }
+    }
+    result.extend(pending);
+
+    result
+}
+
+/// Read back the block written under `token`.
+pub fn find_block(note: &Note, token: &AnchorToken) -> Option<AnchoredBlock> {
+    anchored_blocks(&note.body)
+        .into_iter()
+        .find(|block| &block.token == token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn note(body: &str) -> Note {
+        Note {
+            path: PathBuf::from("test.md"),
+            body: body.to_string(),
+            front_matter: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_segments() {
+        let body = "# Day\n\n## Focus\n\n^focus-0930\n\n- one\n- two\n\nloose\n";
+        let segments = parse_segments(body);
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Heading {
+                    line: 1,
+                    level: 1,
+                    text: "Day".to_string()
+                },
+                Segment::Heading {
+                    line: 3,
+                    level: 2,
+                    text: "Focus".to_string()
+                },
+                Segment::Anchor {
+                    line: 5,
+                    token: AnchorToken::parse("focus-0930").unwrap()
+                },
+                Segment::Block {
+                    line: 7,
+                    text: "- one\n- two".to_string()
+                },
+                Segment::Block {
+                    line: 10,
+                    text: "loose".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_block_ends_at_heading_or_anchor() {
+        let body = "^eod-2215\ntext\n^eod-2230\nmore\n## Next\n";
+        let blocks = anchored_blocks(body);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].content, "text");
+        assert_eq!(blocks[1].content, "more");
+    }
+
+    #[test]
+    fn test_fenced_code_stays_in_block() {
+        let body = "^note-1100\n\n```\n# not a heading\n\n^fake-0000\n```\n";
+        let blocks = anchored_blocks(body);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].content, "```\n# not a heading\n\n^fake-0000\n```");
+    }
+
+    #[test]
+    fn test_find_block_with_heading_path() {
+        let body = "# Day\n\n## End of Day\n\n^eod-2215\n\nWrapped up\n\n## Focus\n\n^focus-0930__iphone\n\nDeep work\n";
+        let note = note(body);
+
+        let token = AnchorToken::parse("focus-0930__iphone").unwrap();
+        let block = find_block(&note, &token).unwrap();
+        assert_eq!(block.content, "Deep work");
+        assert_eq!(block.heading_path, vec!["Day", "Focus"]);
+
+        let block = find_block(&note, &AnchorToken::parse("eod-2215").unwrap()).unwrap();
+        assert_eq!(block.content, "Wrapped up");
+        assert_eq!(block.line, 5);
+
+        assert!(find_block(&note, &AnchorToken::parse("focus-0930").unwrap()).is_none());
+    }
+}
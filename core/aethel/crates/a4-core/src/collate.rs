@@ -0,0 +1,142 @@
+use crate::blocks::{anchored_blocks, AnchoredBlock};
+use crate::date::UtcDay;
+use crate::error::A4Error;
+use crate::notes::read_note;
+use crate::stitch::stitch_text;
+use crate::vault::Vault;
+use std::path::PathBuf;
+
+/// An anchored block found in a daily note.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollatedBlock {
+    pub day: UtcDay,
+    pub path: PathBuf,
+    pub block: AnchoredBlock,
+}
+
+impl CollatedBlock {
+    /// The transclusion for this block, using the `capture/YYYY-MM-DD`
+    /// shorthand: `![[capture/2025-09-14#^eod-2215]]`.
+    pub fn embed(&self) -> String {
+        format!("![[capture/{}#{}]]", self.day, self.block.token.to_marker())
+    }
+}
+
+/// Every block anchored with `prefix` in the daily notes for `days`, in day
+/// order and then in the order they were written.
+pub fn collate(
+    vault: &Vault,
+    prefix: &str,
+    days: &[UtcDay],
+) -> Result<Vec<CollatedBlock>, A4Error> {
+    let mut collated = Vec::new();
+
+    for day in days {
+        let path = vault.capture_day_path(day.clone());
+        if !path.is_file() {
+            continue;
+        }
+
+        let note = read_note(&path)?;
+        collated.extend(
+            anchored_blocks(&note.body)
+                .into_iter()
+                .filter(|block| block.token.prefix == prefix)
+                .map(|block| CollatedBlock {
+                    day: day.clone(),
+                    path: path.clone(),
+                    block,
+                }),
+        );
+    }
+
+    Ok(collated)
+}
+
+/// A bullet list of embeds, ready to paste into a weekly plan (protocol §7.2).
+pub fn render_refs(blocks: &[CollatedBlock]) -> String {
+    blocks
+        .iter()
+        .map(|block| format!("- {}\n", block.embed()))
+        .collect()
+}
+
+/// A standalone document with one `###` section per day holding the stitched
+/// blocks.
+pub fn render_stitched(vault: &Vault, blocks: &[CollatedBlock]) -> Result<String, A4Error> {
+    let mut doc = String::new();
+    let mut current_day: Option<&UtcDay> = None;
+
+    for block in blocks {
+        if current_day != Some(&block.day) {
+            if !doc.is_empty() {
+                doc.push('\n');
+            }
+            doc.push_str(&format!("### {}\n", block.day));
+            current_day = Some(&block.day);
+        }
+        doc.push_str(&format!("\n{}\n", block.embed()));
+    }
+
+    stitch_text(vault, &doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::IsoWeek;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, Vault) {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let notes = [
+            (
+                "2025-09-01",
+                "## End of Day\n\n^eod-2215\n\nMonday wrap\n\n^focus-0930\n\nNot eod\n",
+            ),
+            (
+                "2025-09-03",
+                "^eod-2100\n\n- Early\n- Tired\n\n^eod-2300__iphone\n\nLate note\n",
+            ),
+            ("2025-09-08", "^eod-2200\n\nNext week\n"),
+        ];
+        for (day, content) in notes {
+            let path = vault.capture_day_path(UtcDay::parse(day).unwrap());
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        (temp_dir, vault)
+    }
+
+    #[test]
+    fn test_collate_week_refs() {
+        let (_temp_dir, vault) = setup();
+        let days = IsoWeek::parse("2025-W36").unwrap().days().unwrap();
+
+        let blocks = collate(&vault, "eod", &days).unwrap();
+        assert_eq!(
+            render_refs(&blocks),
+            "- ![[capture/2025-09-01#^eod-2215]]\n\
+             - ![[capture/2025-09-03#^eod-2100]]\n\
+             - ![[capture/2025-09-03#^eod-2300__iphone]]\n"
+        );
+    }
+
+    #[test]
+    fn test_collate_stitched() {
+        let (_temp_dir, vault) = setup();
+        let days = UtcDay::range(
+            &UtcDay::parse("2025-09-03").unwrap(),
+            &UtcDay::parse("2025-09-08").unwrap(),
+        )
+        .unwrap();
+
+        let blocks = collate(&vault, "eod", &days).unwrap();
+        assert_eq!(
+            render_stitched(&vault, &blocks).unwrap(),
+            "### 2025-09-03\n\n- Early\n- Tired\n\nLate note\n\n### 2025-09-08\n\nNext week\n"
+        );
+    }
+}
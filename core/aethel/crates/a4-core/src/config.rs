@@ -0,0 +1,261 @@
+use crate::date::{UtcDay, Zone};
+use crate::error::A4Error;
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Settings read from `<vault>/.a4/config.toml`. Every field is optional in
+/// the file; missing values fall back to the protocol defaults.
+///
+/// ```toml
+/// device = "laptop"
+///
+/// [sync]
+/// remote = "upstream"
+/// branch = "main"
+/// message = "a4: sync from {{device}} on {{date}}"
+///
+/// [templates]
+/// dir = "routines/templates"
+/// daily = "routines/templates/daily.md"
+/// weekly = "routines/templates/weekly.md"
+///
+/// [time]
+/// filename_tz = "utc"            # or "local", or an IANA zone
+/// zone = "America/Los_Angeles"
+/// day_rollover_hour = 3
+///
+/// [headings]
+/// focus = "Focus"
+/// eod = "End of Day"
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct VaultConfig {
+    /// Suffix for colliding anchors. `A4_DEVICE` takes precedence, since the
+    /// config file is shared by every clone of the vault.
+    pub device: Option<String>,
+    pub sync: SyncConfig,
+    pub templates: TemplatesConfig,
+    pub time: TimeConfig,
+    /// Heading used by `a4 append` when `--heading` is omitted, keyed by
+    /// anchor prefix.
+    pub headings: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub remote: String,
+    /// Branch to sync; the checked-out branch when unset.
+    pub branch: Option<String>,
+    /// Commit message template; supports `{{device}}` and `{{date}}`.
+    pub message: String,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        SyncConfig {
+            remote: "origin".to_string(),
+            branch: None,
+            message: "a4: sync".to_string(),
+        }
+    }
+}
+
+impl SyncConfig {
+    pub fn commit_message(&self, device: Option<&str>, day: &UtcDay) -> String {
+        self.message
+            .replace("{{device}}", device.unwrap_or("unknown"))
+            .replace(
+                "{{date}}",
+                &format!("{:04}-{:02}-{:02}", day.year, day.month, day.day),
+            )
+    }
+}
+
+/// Template locations, relative to the vault root.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct TemplatesConfig {
+    pub dir: PathBuf,
+    pub daily: PathBuf,
+    pub weekly: PathBuf,
+}
+
+impl Default for TemplatesConfig {
+    fn default() -> Self {
+        TemplatesConfig {
+            dir: PathBuf::from("routines/templates"),
+            daily: PathBuf::from("routines/templates/daily.md"),
+            weekly: PathBuf::from("routines/templates/weekly.md"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct TimeConfig {
+    /// Which calendar day names the daily note file.
+    pub filename_tz: TimezonePolicy,
+    /// IANA zone for local times such as anchor `HHMM`s. `A4_TZ` takes
+    /// precedence; the system zone is used when both are unset.
+    pub zone: Option<String>,
+    /// Hour (0-23, on the local clock of `zone`) at which "today" moves to
+    /// the next day, so late-night entries still land in the note that was
+    /// current the evening before, whatever `filename_tz` is.
+    pub day_rollover_hour: u8,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub enum TimezonePolicy {
+    /// UTC date, per the protocol, so devices in different zones agree.
+    #[default]
+    Utc,
+    /// The device's local date.
+    Local,
+    /// The date in a fixed IANA zone, e.g. `America/Los_Angeles`.
+    Zone(String),
+}
+
+impl TryFrom<String> for TimezonePolicy {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "utc" => Ok(TimezonePolicy::Utc),
+            "local" => Ok(TimezonePolicy::Local),
+            name => Zone::named(name)
+                .map(|_| TimezonePolicy::Zone(name.to_string()))
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl From<TimezonePolicy> for String {
+    fn from(policy: TimezonePolicy) -> Self {
+        match policy {
+            TimezonePolicy::Utc => "utc".to_string(),
+            TimezonePolicy::Local => "local".to_string(),
+            TimezonePolicy::Zone(name) => name,
+        }
+    }
+}
+
+impl VaultConfig {
+    pub fn path(root: &Path) -> PathBuf {
+        root.join(".a4").join("config.toml")
+    }
+
+    /// Load the config for the vault at `root`, or defaults if there is none.
+    pub fn load(root: &Path) -> Result<Self, A4Error> {
+        let path = Self::path(root);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let raw = fs::read_to_string(&path)?;
+        Self::parse(&raw).map_err(|message| A4Error::Config { path, message })
+    }
+
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(raw).map_err(|e| e.message().to_string())?;
+        if let Some(zone) = &config.time.zone {
+            Zone::named(zone).map_err(|e| e.to_string())?;
+        }
+        if config.time.day_rollover_hour > 23 {
+            return Err(format!(
+                "time.day_rollover_hour must be 0-23, got {}",
+                config.time.day_rollover_hour
+            ));
+        }
+        Ok(config)
+    }
+
+    /// The default heading for blocks with this anchor prefix.
+    pub fn heading_for(&self, prefix: &str) -> Option<&str> {
+        self.headings.get(prefix).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_when_empty() {
+        let config = VaultConfig::parse("").unwrap();
+        assert_eq!(config, VaultConfig::default());
+        assert_eq!(config.sync.remote, "origin");
+        assert_eq!(config.sync.message, "a4: sync");
+        assert_eq!(
+            config.templates.daily,
+            PathBuf::from("routines/templates/daily.md")
+        );
+        assert_eq!(config.time.filename_tz, TimezonePolicy::Utc);
+    }
+
+    #[test]
+    fn test_parse_full_config() {
+        let raw = r#"
+device = "laptop"
+
+[sync]
+remote = "upstream"
+branch = "vault"
+message = "a4: {{device}} {{date}}"
+
+[templates]
+daily = "templates/day.md"
+
+[time]
+filename_tz = "local"
+day_rollover_hour = 3
+
+[headings]
+eod = "End of Day"
+"#;
+        let config = VaultConfig::parse(raw).unwrap();
+        assert_eq!(config.device.as_deref(), Some("laptop"));
+        assert_eq!(config.sync.remote, "upstream");
+        assert_eq!(config.sync.branch.as_deref(), Some("vault"));
+        assert_eq!(config.templates.daily, PathBuf::from("templates/day.md"));
+        assert_eq!(config.templates.dir, PathBuf::from("routines/templates"));
+        assert_eq!(config.time.filename_tz, TimezonePolicy::Local);
+        assert_eq!(config.time.day_rollover_hour, 3);
+        assert_eq!(config.heading_for("eod"), Some("End of Day"));
+        assert_eq!(config.heading_for("focus"), None);
+
+        let day = UtcDay {
+            year: 2025,
+            month: 9,
+            day: 4,
+        };
+        assert_eq!(
+            config.sync.commit_message(Some("laptop"), &day),
+            "a4: laptop 2025-09-04"
+        );
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(VaultConfig::parse("[time]\nfilename_tz = \"mars\"\n").is_err());
+        assert!(VaultConfig::parse("device = [").is_err());
+        assert!(VaultConfig::parse("[time]\nday_rollover_hour = 24\n").is_err());
+        assert!(VaultConfig::parse("[time]\nzone = \"Nowhere/Special\"\n").is_err());
+    }
+
+    #[test]
+    fn test_zone_policy() {
+        let config =
+            VaultConfig::parse("[time]\nfilename_tz = \"Asia/Tokyo\"\nzone = \"Europe/Paris\"\n")
+                .unwrap();
+        assert_eq!(
+            config.time.filename_tz,
+            TimezonePolicy::Zone("Asia/Tokyo".to_string())
+        );
+        assert_eq!(config.time.zone.as_deref(), Some("Europe/Paris"));
+    }
+}
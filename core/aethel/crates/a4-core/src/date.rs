@@ -1,54 +1,540 @@
-use time::{OffsetDateTime, UtcOffset};
+use crate::error::A4Error;
+use std::sync::OnceLock;
+use time::{OffsetDateTime, PrimitiveDateTime, UtcOffset};
+use time_tz::{Offset, TimeZone, Tz};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+static SYSTEM_ZONE: OnceLock<Zone> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct UtcDay {
     pub year: i32,
     pub month: u8,
     pub day: u8,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct IsoWeek {
     pub year: i32,
     pub week: u8,
 }
 
+/// Source of the current instant.
+pub trait Clock {
+    fn now_utc(&self) -> OffsetDateTime;
+}
+
+/// The system clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_utc(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc()
+    }
+}
+
+/// A clock stopped at one instant, for tests and replays.
+pub struct FixedClock(pub OffsetDateTime);
+
+impl Clock for FixedClock {
+    fn now_utc(&self) -> OffsetDateTime {
+        self.0
+    }
+}
+
+/// A timezone: UTC, an IANA zone, or a bare offset when only that is known.
+#[derive(Clone, Copy, Debug)]
+pub enum Zone {
+    Utc,
+    Iana(&'static Tz),
+    Fixed(UtcOffset),
+}
+
+impl Zone {
+    /// `UTC` or an IANA name such as `America/Los_Angeles`.
+    pub fn named(name: &str) -> Result<Self, A4Error> {
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("utc") {
+            return Ok(Zone::Utc);
+        }
+        time_tz::timezones::get_by_name(name)
+            .map(Zone::Iana)
+            .ok_or_else(|| A4Error::UnknownTimezone {
+                name: name.to_string(),
+            })
+    }
+
+    /// The device's zone from `TZ` or the system configuration. Falls back
+    /// to the current UTC offset, then to UTC with a warning.
+    pub fn system() -> Self {
+        *SYSTEM_ZONE.get_or_init(|| {
+            if let Some(zone) = std::env::var("TZ")
+                .ok()
+                .and_then(|tz| Zone::named(tz.trim_start_matches(':')).ok())
+            {
+                return zone;
+            }
+            if let Ok(tz) = time_tz::system::get_timezone() {
+                return Zone::Iana(tz);
+            }
+            if let Ok(offset) = UtcOffset::current_local_offset() {
+                return Zone::Fixed(offset);
+            }
+
+            tracing::warn!(
+                "Could not determine the local timezone from TZ or the system settings; using UTC. Set TZ to an IANA name such as America/Los_Angeles"
+            );
+            Zone::Utc
+        })
+    }
+
+    /// The instant at which this zone's clock reads `local`. Around a DST
+    /// change, a skipped or repeated time takes one of the nearby offsets.
+    pub fn instant_at(&self, local: PrimitiveDateTime) -> OffsetDateTime {
+        let offset = self.offset_at(local.assume_utc());
+        let offset = self.offset_at(local.assume_offset(offset));
+        local.assume_offset(offset).to_offset(UtcOffset::UTC)
+    }
+
+    pub fn offset_at(&self, instant: OffsetDateTime) -> UtcOffset {
+        match self {
+            Zone::Utc => UtcOffset::UTC,
+            Zone::Iana(tz) => tz.get_offset_utc(&instant).to_utc(),
+            Zone::Fixed(offset) => *offset,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Zone::Utc => "UTC".to_string(),
+            Zone::Iana(tz) => tz.name().to_string(),
+            Zone::Fixed(offset) => offset.to_string(),
+        }
+    }
+}
+
+/// One instant seen through a vault's timezone policy, so that a command's
+/// daily note, anchors and template times all agree.
+#[derive(Clone, Copy, Debug)]
+pub struct Moment {
+    pub instant: OffsetDateTime,
+    /// Zone whose calendar day names the daily note.
+    pub filename_zone: Zone,
+    /// Zone for times written into content, such as `^eod-2215`.
+    pub local_zone: Zone,
+    pub rollover_hour: u8,
+}
+
+impl Moment {
+    /// `instant` seen in UTC with days starting at midnight, for callers
+    /// with no vault to take a timezone policy from.
+    pub fn utc(instant: OffsetDateTime) -> Self {
+        Moment {
+            instant,
+            filename_zone: Zone::Utc,
+            local_zone: Zone::Utc,
+            rollover_hour: 0,
+        }
+    }
+
+    /// The daily note day, after the rollover hour.
+    pub fn day(&self) -> UtcDay {
+        self.day_with_rollover(self.rollover_hour)
+    }
+
+    /// The daily note day for days that start at `rollover_hour` on the local
+    /// clock instead of midnight. Before that hour the instant counts as the
+    /// end of the previous local day, so at 01:30 with a rollover hour of 3
+    /// it maps to the note that was current at 23:59 the evening before.
+    pub fn day_with_rollover(&self, rollover_hour: u8) -> UtcDay {
+        let local = self
+            .instant
+            .to_offset(self.local_zone.offset_at(self.instant));
+        let instant = if local.hour() < rollover_hour {
+            local.replace_time(time::Time::MIDNIGHT) - time::Duration::SECOND
+        } else {
+            self.instant
+        };
+        instant
+            .to_offset(self.filename_zone.offset_at(instant))
+            .date()
+            .into()
+    }
+
+    /// The instant filed under daily note `day` at local time `hhmm`, the
+    /// inverse of [`Moment::day`] and [`Moment::local_hhmm`] under this
+    /// moment's zones and rollover. `None` if `hhmm` is not a time.
+    pub fn instant_for(&self, day: &UtcDay, hhmm: &str) -> Option<OffsetDateTime> {
+        let hour = hhmm.get(..2)?.parse().ok()?;
+        let minute = hhmm.get(2..)?.parse().ok()?;
+        let time = time::Time::from_hms(hour, minute, 0).ok()?;
+        let date = day.to_date().ok()?;
+
+        // The local date is the note's day, or a neighbour when the zones
+        // disagree or the time falls before the rollover hour
+        let instants: Vec<OffsetDateTime> = [Some(date), date.next_day(), date.previous_day()]
+            .into_iter()
+            .flatten()
+            .map(|date| self.local_zone.instant_at(date.with_time(time)))
+            .collect();
+        instants
+            .iter()
+            .find(|&&instant| Moment { instant, ..*self }.day() == *day)
+            .or(instants.first())
+            .copied()
+    }
+
+    pub fn local_hhmm(&self) -> String {
+        let local = self
+            .instant
+            .to_offset(self.local_zone.offset_at(self.instant));
+        format!("{:02}{:02}", local.hour(), local.minute())
+    }
+
+    /// The instant as ISO 8601 UTC, e.g. for `created:` front matter.
+    pub fn utc_iso8601(&self) -> String {
+        let utc = self.instant.to_offset(UtcOffset::UTC);
+        utc.format(&time::format_description::well_known::Iso8601::DEFAULT)
+            .unwrap_or_else(|_| utc.to_string())
+    }
+}
+
 pub struct LocalClock;
 
 impl LocalClock {
+    #[deprecated(note = "use `Vault::now()` and `Moment::local_hhmm`")]
     pub fn now_local_hhmm() -> String {
-        let now = OffsetDateTime::now_utc();
+        Moment {
+            local_zone: Zone::system(),
+            ..Moment::utc(SystemClock.now_utc())
+        }
+        .local_hhmm()
+    }
+
+    #[deprecated(note = "use `Vault::now()` and `Moment::day`")]
+    pub fn today_utc() -> UtcDay {
+        Moment::utc(SystemClock.now_utc()).day()
+    }
 
-        let local_offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
-        let local_time = now.to_offset(local_offset);
+    #[deprecated(note = "use `Vault::now()` and `Moment::day`")]
+    pub fn today_local() -> UtcDay {
+        Moment {
+            filename_zone: Zone::system(),
+            local_zone: Zone::system(),
+            ..Moment::utc(SystemClock.now_utc())
+        }
+        .day()
+    }
 
-        format!("{:02}{:02}", local_time.hour(), local_time.minute())
+    /// The ISO week `utc` falls in; fails if `utc` is not a calendar date.
+    pub fn iso_week(utc: &UtcDay) -> Result<IsoWeek, A4Error> {
+        let (year, week, _) = utc.to_date()?.to_iso_week_date();
+        Ok(IsoWeek { year, week })
     }
+}
 
-    pub fn today_utc() -> UtcDay {
-        let now = OffsetDateTime::now_utc();
+impl UtcDay {
+    /// Parse an ISO `YYYY-MM-DD` date.
+    pub fn parse(s: &str) -> Result<Self, A4Error> {
+        let invalid = || A4Error::InvalidDate {
+            input: s.to_string(),
+        };
+
+        let mut parts = s.trim().splitn(3, '-');
+        let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return Err(invalid());
+        }
+
+        let year = year.parse().map_err(|_| invalid())?;
+        let month: u8 = month.parse().map_err(|_| invalid())?;
+        let day = day.parse().map_err(|_| invalid())?;
+
+        // Reject impossible dates such as 2025-02-30
+        let month_enum = time::Month::try_from(month).map_err(|_| invalid())?;
+        time::Date::from_calendar_date(year, month_enum, day).map_err(|_| invalid())?;
+
+        Ok(UtcDay { year, month, day })
+    }
+
+    pub fn filename(&self) -> String {
+        format!("{self}.md")
+    }
+
+    pub fn to_date(&self) -> Result<time::Date, A4Error> {
+        let invalid = || A4Error::InvalidDate {
+            input: self.to_string(),
+        };
+        let month = time::Month::try_from(self.month).map_err(|_| invalid())?;
+        time::Date::from_calendar_date(self.year, month, self.day).map_err(|_| invalid())
+    }
+
+    /// The day `days` days after this one (before, if negative).
+    pub fn offset_days(&self, days: i64) -> Result<Self, A4Error> {
+        let date = self.to_date()?;
+        let shifted = date
+            .checked_add(time::Duration::days(days))
+            .ok_or_else(|| A4Error::InvalidDate {
+                input: format!("{self} {days:+} days"),
+            })?;
+        Ok(shifted.into())
+    }
+
+    /// Every day from `from` to `to`, inclusive.
+    pub fn range(from: &UtcDay, to: &UtcDay) -> Result<Vec<UtcDay>, A4Error> {
+        let mut days = Vec::new();
+        let mut current = from.clone();
+        while &current <= to {
+            let next = current.offset_days(1)?;
+            days.push(current);
+            current = next;
+        }
+        Ok(days)
+    }
+}
+
+impl From<time::Date> for UtcDay {
+    fn from(date: time::Date) -> Self {
         UtcDay {
-            year: now.year(),
-            month: now.month() as u8,
-            day: now.day(),
+            year: date.year(),
+            month: date.month() as u8,
+            day: date.day(),
+        }
+    }
+}
+
+impl IsoWeek {
+    /// Parse an ISO week such as `2025-W36`.
+    pub fn parse(s: &str) -> Result<Self, A4Error> {
+        let invalid = || A4Error::InvalidIsoWeek {
+            input: s.to_string(),
+        };
+
+        let (year, week) = s.trim().split_once("-W").ok_or_else(invalid)?;
+        if year.len() != 4 || week.len() != 2 {
+            return Err(invalid());
         }
+        let year = year.parse().map_err(|_| invalid())?;
+        let week = week.parse().map_err(|_| invalid())?;
+
+        // Rejects week 53 in years that only have 52
+        time::Date::from_iso_week_date(year, week, time::Weekday::Monday).map_err(|_| invalid())?;
+
+        Ok(IsoWeek { year, week })
     }
 
-    pub fn iso_week(utc: &UtcDay) -> IsoWeek {
-        let date = time::Date::from_calendar_date(
-            utc.year,
-            time::Month::try_from(utc.month).unwrap(),
-            utc.day,
-        )
-        .unwrap();
+    /// Monday through Sunday of this week.
+    pub fn days(&self) -> Result<Vec<UtcDay>, A4Error> {
+        let monday = time::Date::from_iso_week_date(self.year, self.week, time::Weekday::Monday)
+            .map_err(|_| A4Error::InvalidIsoWeek {
+                input: self.to_string(),
+            })?;
+        let monday = UtcDay::from(monday);
+        UtcDay::range(&monday, &monday.offset_days(6)?)
+    }
+}
 
-        let (year, week, _) = date.to_iso_week_date();
-        IsoWeek { year, week }
+impl std::fmt::Display for IsoWeek {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-W{:02}", self.year, self.week)
     }
 }
 
-impl UtcDay {
-    pub fn filename(&self) -> String {
-        format!("{:04}-{:02}-{:02}.md", self.year, self.month, self.day)
+impl std::fmt::Display for UtcDay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_utc_day() {
+        let day = UtcDay::parse("2025-09-04").unwrap();
+        assert_eq!(
+            day,
+            UtcDay {
+                year: 2025,
+                month: 9,
+                day: 4
+            }
+        );
+        assert_eq!(day.to_string(), "2025-09-04");
+        assert!(UtcDay::parse("2025-9-4").is_err());
+        assert!(UtcDay::parse("2025-02-30").is_err());
+        assert!(UtcDay::parse("tomorrow").is_err());
+        assert!(UtcDay::parse("2025-09-03").unwrap() < day);
+    }
+
+    #[test]
+    fn test_iso_week_days() {
+        let week = IsoWeek::parse("2025-W36").unwrap();
+        assert_eq!(week.to_string(), "2025-W36");
+
+        let days = week.days().unwrap();
+        assert_eq!(days.len(), 7);
+        assert_eq!(days[0].to_string(), "2025-09-01");
+        assert_eq!(days[6].to_string(), "2025-09-07");
+        assert!(days
+            .iter()
+            .all(|d| LocalClock::iso_week(d).unwrap() == week));
+
+        // ISO week 1 of 2026 starts in December 2025
+        let days = IsoWeek::parse("2026-W01").unwrap().days().unwrap();
+        assert_eq!(days[0].to_string(), "2025-12-29");
+
+        assert!(IsoWeek::parse("2025-W53").is_err());
+        assert!(IsoWeek::parse("2025-36").is_err());
+
+        let not_a_date = UtcDay {
+            year: 2025,
+            month: 13,
+            day: 1,
+        };
+        assert!(matches!(
+            LocalClock::iso_week(&not_a_date),
+            Err(A4Error::InvalidDate { .. })
+        ));
+    }
+
+    #[test]
+    fn test_day_rollover() {
+        let instant = |hour| {
+            time::Date::from_calendar_date(2025, time::Month::September, 5)
+                .unwrap()
+                .with_hms(hour, 30, 0)
+                .unwrap()
+                .assume_utc()
+        };
+        let moment = |hour, filename_zone, local_zone, rollover_hour| Moment {
+            instant: FixedClock(instant(hour)).now_utc(),
+            filename_zone,
+            local_zone,
+            rollover_hour,
+        };
+
+        // 01:30 UTC on a UTC device
+        assert_eq!(
+            moment(1, Zone::Utc, Zone::Utc, 0).day().to_string(),
+            "2025-09-05"
+        );
+        assert_eq!(
+            moment(1, Zone::Utc, Zone::Utc, 1).day().to_string(),
+            "2025-09-05"
+        );
+        assert_eq!(
+            moment(1, Zone::Utc, Zone::Utc, 3).day().to_string(),
+            "2025-09-04"
+        );
+
+        // The rollover follows the local clock, not the filename zone: 01:30
+        // UTC is 18:30 the day before in Los Angeles (PDT), well past the
+        // rollover, so UTC filenames keep the UTC day
+        let la = Zone::named("America/Los_Angeles").unwrap();
+        assert_eq!(moment(1, Zone::Utc, la, 3).day().to_string(), "2025-09-05");
+        assert_eq!(moment(1, la, la, 3).day().to_string(), "2025-09-04");
+
+        // 08:30 UTC is 01:30 in Los Angeles, before the rollover. Local
+        // filenames move back to the previous local day; UTC filenames stay
+        // on the UTC day that was current at local midnight (07:00 UTC)
+        assert_eq!(moment(8, la, la, 0).day().to_string(), "2025-09-05");
+        assert_eq!(moment(8, la, la, 3).day().to_string(), "2025-09-04");
+        assert_eq!(moment(8, Zone::Utc, la, 3).day().to_string(), "2025-09-05");
+
+        // 16:30 UTC is 01:30 the next day in Tokyo: the rollover takes a
+        // local filename back a day, while the UTC day was already the 5th
+        let tokyo = Zone::named("Asia/Tokyo").unwrap();
+        assert_eq!(moment(16, tokyo, tokyo, 0).day().to_string(), "2025-09-06");
+        assert_eq!(moment(16, tokyo, tokyo, 3).day().to_string(), "2025-09-05");
+        assert_eq!(
+            moment(16, Zone::Utc, tokyo, 3).day().to_string(),
+            "2025-09-05"
+        );
+
+        // 00:30 UTC is 02:30 in UTC+2, so the session began on the evening
+        // of the 4th, when that was also the UTC day
+        let cest = Zone::Fixed(UtcOffset::from_hms(2, 0, 0).unwrap());
+        assert_eq!(
+            moment(0, Zone::Utc, cest, 0).day().to_string(),
+            "2025-09-05"
+        );
+        assert_eq!(
+            moment(0, Zone::Utc, cest, 3).day().to_string(),
+            "2025-09-04"
+        );
+    }
+
+    #[test]
+    fn test_moment_in_zones() {
+        // 06:15 UTC is 22:15 the previous evening in Los Angeles (PST)
+        let instant = time::Date::from_calendar_date(2025, time::Month::December, 5)
+            .unwrap()
+            .with_hms(6, 15, 0)
+            .unwrap()
+            .assume_utc();
+        let la = Zone::named("America/Los_Angeles").unwrap();
+        assert_eq!(la.name(), "America/Los_Angeles");
+
+        let moment = Moment {
+            instant: FixedClock(instant).now_utc(),
+            filename_zone: Zone::Utc,
+            local_zone: la,
+            rollover_hour: 0,
+        };
+        assert_eq!(moment.day().to_string(), "2025-12-05");
+        assert_eq!(moment.local_hhmm(), "2215");
+        assert!(moment.utc_iso8601().starts_with("2025-12-05T06:15:00"));
+
+        let moment = Moment {
+            filename_zone: la,
+            ..moment
+        };
+        assert_eq!(moment.day().to_string(), "2025-12-04");
+
+        assert!(Zone::named("utc").is_ok());
+        assert!(Zone::named("Mars/Olympus_Mons").is_err());
+    }
+
+    #[test]
+    fn test_instant_for_inverts_day_and_hhmm() {
+        let la = Zone::named("America/Los_Angeles").unwrap();
+        let tokyo = Zone::named("Asia/Tokyo").unwrap();
+        let start = time::Date::from_calendar_date(2025, time::Month::December, 5)
+            .unwrap()
+            .midnight()
+            .assume_utc();
+
+        // Every quarter hour of two days, through zones whose local date
+        // leads or trails the filename day, with and without a rollover
+        for (filename_zone, local_zone, rollover_hour) in [
+            (Zone::Utc, la, 0),
+            (Zone::Utc, la, 3),
+            (la, la, 3),
+            (Zone::Utc, tokyo, 3),
+            (tokyo, tokyo, 0),
+        ] {
+            for quarter in 0..192 {
+                let moment = Moment {
+                    instant: start + time::Duration::minutes(15 * quarter),
+                    filename_zone,
+                    local_zone,
+                    rollover_hour,
+                };
+                assert_eq!(
+                    moment.instant_for(&moment.day(), &moment.local_hhmm()),
+                    Some(moment.instant),
+                    "{} {}",
+                    moment.day(),
+                    moment.local_hhmm()
+                );
+            }
+        }
+
+        let moment = Moment::utc(start);
+        assert_eq!(moment.instant_for(&moment.day(), "2460"), None);
     }
 }
@@ -0,0 +1,1412 @@
+use crate::date::Moment;
+use crate::error::A4Error;
+use crate::front_matter::{at, parse_timestamp, yaml_range, FrontMatter};
+use crate::notes::{opens_front_matter, write_note};
+use crate::projects::{validate_slug, IndexFrontMatter, Project, ProjectDomino};
+use crate::vault::Vault;
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+const PREFIX: &str = "d";
+const MIN_HASH_LENGTH: usize = 3;
+const MAX_HASH_LENGTH: usize = 8;
+
+/// A front-matter domino joined with the `log.jsonl` entries that reference
+/// it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DominoStatus {
+    pub id: Option<String>,
+    pub task: String,
+    pub done: bool,
+    /// `ts` of the latest log entry referencing the domino, once it is done
+    pub completed_at: Option<String>,
+    /// Seconds since the domino was first seen (its first log reference or
+    /// the project's `created` date), up to completion if done
+    pub age_secs: Option<i64>,
+    pub log_refs: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+/// What `domino-id status` reports for one project.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ProjectStatus {
+    pub project: String,
+    pub path: PathBuf,
+    pub dominoes: Vec<DominoStatus>,
+}
+
+/// A problem found by [`lint_project`], at a 1-based line of `path` when
+/// known.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct LintIssue {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{line}: {}", self.path.display(), self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct LogEntry {
+    #[serde(default)]
+    ts: String,
+    #[serde(default)]
+    what: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dominoes: Option<Vec<String>>,
+}
+
+/// Every project hub in the vault with an `index.md`, sorted by slug.
+pub fn list_projects(vault: &Vault) -> Result<Vec<Project>, A4Error> {
+    let dir = vault.root().join("projects");
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut projects = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let slug = entry?.file_name().to_string_lossy().into_owned();
+        if validate_slug(&slug).is_err() {
+            continue;
+        }
+        let project = Project::resolve(vault, &slug)?;
+        if project.index.is_file() {
+            projects.push(project);
+        }
+    }
+    projects.sort_by(|a, b| a.slug.cmp(&b.slug));
+    Ok(projects)
+}
+
+/// Look a project up by slug (`gb-ppu` → `projects/gb-ppu/index.md`), or
+/// take `arg` as the path of its index when it names a Markdown file.
+pub fn find_project(vault: &Vault, arg: &str) -> Result<Project, A4Error> {
+    match project_path(arg) {
+        Some(path) => open_project_at(path),
+        None => Project::open(vault, arg),
+    }
+}
+
+/// The index `arg` names when it is a path (a `.md` file, or anything with
+/// a `/`) rather than a slug.
+pub fn project_path(arg: &str) -> Option<&Path> {
+    let path = Path::new(arg);
+    (path.extension().is_some_and(|ext| ext == "md") || arg.contains('/')).then_some(path)
+}
+
+/// The project whose index is the existing file `path`, wherever it lives.
+pub fn open_project_at(path: &Path) -> Result<Project, A4Error> {
+    if !path.is_file() {
+        return Err(A4Error::ProjectNotFound {
+            slug: path.display().to_string(),
+            path: path.to_path_buf(),
+        });
+    }
+    Ok(Project::at(path))
+}
+
+/// IDs a project uses: its front-matter dominoes and every ID its
+/// `log.jsonl` references. Unparseable front matter and log lines are
+/// skipped; [`lint_project`] reports them.
+pub fn project_ids(project: &Project) -> Result<HashSet<String>, A4Error> {
+    let mut ids = HashSet::new();
+    if project.index.exists() {
+        let raw = fs::read_to_string(&project.index)?;
+        if let Ok(dominoes) = parse_dominoes(&raw, &project.index) {
+            ids.extend(dominoes.into_iter().filter_map(|d| d.id));
+        }
+    }
+    for (_, entry) in read_log(project)? {
+        ids.extend(entry.dominoes.unwrap_or_default());
+    }
+    Ok(ids)
+}
+
+/// IDs used by any project in the vault.
+pub fn vault_ids(vault: &Vault) -> Result<HashSet<String>, A4Error> {
+    let mut ids = HashSet::new();
+    for project in list_projects(vault)? {
+        ids.extend(project_ids(&project)?);
+    }
+    Ok(ids)
+}
+
+/// IDs a new domino of `project` must avoid: the project's own, and those
+/// of every project in `vault` when there is one.
+fn taken_ids(vault: Option<&Vault>, project: &Project) -> Result<HashSet<String>, A4Error> {
+    let mut existing = match vault {
+        Some(vault) => vault_ids(vault)?,
+        None => HashSet::new(),
+    };
+    existing.extend(project_ids(project)?);
+    Ok(existing)
+}
+
+/// A new ID for `task`, unique across `project` and the vault, if any
+/// (the project may live outside it).
+pub fn generate_id(
+    vault: Option<&Vault>,
+    project: &Project,
+    task: &str,
+    now: &Moment,
+) -> Result<String, A4Error> {
+    let existing = taken_ids(vault, project)?;
+    generate_unique(&project.slug, task, now.instant, &existing)
+}
+
+/// Generate an ID for `task` and append `{id, task}` to the project's
+/// `dominoes:` list, leaving the rest of the front matter and the body
+/// untouched. Returns the new ID.
+pub fn add_domino(
+    vault: Option<&Vault>,
+    project: &Project,
+    task: &str,
+    now: &Moment,
+) -> Result<String, A4Error> {
+    let raw = fs::read_to_string(&project.index)?;
+    if parse_dominoes(&raw, &project.index)?
+        .iter()
+        .any(|d| d.task == task)
+    {
+        tracing::warn!("\"{task}\" is already a domino of {}", project.slug);
+    }
+
+    let id = generate_id(vault, project, task, now)?;
+    let updated = edit_front_matter(&raw, &project.index, |yaml| {
+        insert_domino(yaml, &id, task, &project.index)
+    })?;
+    write_note(&project.index, &updated)?;
+    Ok(id)
+}
+
+/// Set `done: true` on domino `id` and log its completion as `what`, or
+/// `Completed: <task>`. Returns the log line written.
+pub fn complete_domino(
+    project: &Project,
+    id: &str,
+    what: Option<&str>,
+    now: &Moment,
+) -> Result<String, A4Error> {
+    let raw = fs::read_to_string(&project.index)?;
+    let not_found = || A4Error::DominoNotFound {
+        id: id.to_string(),
+        path: project.index.clone(),
+    };
+    let task = parse_dominoes(&raw, &project.index)?
+        .into_iter()
+        .find(|d| d.id.as_deref() == Some(id))
+        .ok_or_else(not_found)?
+        .task;
+
+    let updated = edit_front_matter(&raw, &project.index, |yaml| {
+        mark_done(yaml, id).ok_or_else(not_found)
+    })?;
+    write_note(&project.index, &updated)?;
+
+    let what = what.map_or_else(|| format!("Completed: {task}"), str::to_string);
+    append_log(project, &what, &[id.to_string()], now)
+}
+
+/// Append one `{"ts", "what", "dominoes"?}` line to the project's
+/// `log.jsonl` with a single write, so concurrent writers never interleave.
+/// Returns the line written.
+pub fn append_log(
+    project: &Project,
+    what: &str,
+    dominoes: &[String],
+    now: &Moment,
+) -> Result<String, A4Error> {
+    let entry = LogEntry {
+        ts: log_timestamp(now.instant),
+        what: what.to_string(),
+        dominoes: (!dominoes.is_empty()).then(|| dominoes.to_vec()),
+    };
+    let line = serde_json::to_string(&entry).expect("log entries serialize");
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(project.jsonl_log())?;
+    file.write_all(format!("{line}\n").as_bytes())?;
+    Ok(line)
+}
+
+/// Join the project's front-matter dominoes with its `log.jsonl`. A domino
+/// is done when it says `done: true` or a log entry lists its ID.
+pub fn project_status(project: &Project, now: &Moment) -> Result<ProjectStatus, A4Error> {
+    let raw = fs::read_to_string(&project.index)?;
+    let front = parse_front_matter(&raw, &project.index)?;
+    let created = front.created.as_ref().and_then(parse_timestamp);
+    let log = read_log(project)?;
+
+    let dominoes = front
+        .dominoes
+        .unwrap_or_default()
+        .into_iter()
+        .map(|raw| {
+            let domino = raw.into_domino();
+            let referencing: Vec<&(Option<OffsetDateTime>, LogEntry)> = log
+                .iter()
+                .filter(|(_, entry)| {
+                    domino
+                        .id
+                        .as_ref()
+                        .is_some_and(|id| entry.dominoes.iter().flatten().any(|d| d == id))
+                })
+                .collect();
+            let log_refs = referencing.len();
+            let refs: Vec<(OffsetDateTime, &str)> = referencing
+                .iter()
+                .filter_map(|(ts, entry)| Some(((*ts)?, entry.ts.as_str())))
+                .collect();
+
+            let done = domino.done || log_refs > 0;
+            let completed = refs.iter().max_by_key(|(ts, _)| *ts).filter(|_| done);
+            let first_seen = refs.iter().map(|(ts, _)| *ts).chain(created).min();
+            let until = completed.map_or(now.instant, |(ts, _)| *ts);
+
+            DominoStatus {
+                id: domino.id,
+                task: domino.task,
+                done,
+                completed_at: completed.map(|(_, ts)| ts.to_string()),
+                age_secs: first_seen.map(|first| (until - first).whole_seconds().max(0)),
+                log_refs,
+                after: domino.after,
+                estimate: domino.estimate,
+                status: domino.status,
+            }
+        })
+        .collect();
+
+    Ok(ProjectStatus {
+        project: project.slug.clone(),
+        path: project.index.clone(),
+        dominoes,
+    })
+}
+
+/// Check a project's front matter and `log.jsonl`: YAML that does not
+/// parse, duplicate IDs, dominoes without IDs, `after` references to
+/// unknown IDs, dependency cycles, log lines that are not entries and log
+/// references to IDs the front matter does not list.
+pub fn lint_project(project: &Project) -> Result<Vec<LintIssue>, A4Error> {
+    let raw = fs::read_to_string(&project.index)?;
+    let issue = |path: &Path, line: Option<usize>, message: String| LintIssue {
+        path: path.to_path_buf(),
+        line,
+        message,
+    };
+    let mut issues = Vec::new();
+
+    let mut known_ids = None;
+    match yaml_range(&raw) {
+        None if opens_front_matter(&raw) => issues.push(issue(
+            &project.index,
+            Some(1),
+            "Front matter is never closed with `---`".to_string(),
+        )),
+        None => known_ids = Some(HashSet::new()),
+        Some(range) => {
+            let yaml = &raw[range];
+            // Front matter starts on line 2, after the opening `---`
+            match serde_yaml::from_str::<IndexFrontMatter>(yaml) {
+                Err(e) => {
+                    let message = e.to_string();
+                    let message = match (e.location(), message.rfind(" at line ")) {
+                        (Some(_), Some(at)) => message[..at].to_string(),
+                        _ => message,
+                    };
+                    issues.push(issue(
+                        &project.index,
+                        e.location().map(|l| l.line() + 1),
+                        format!("Invalid front matter: {message}"),
+                    ));
+                }
+                Ok(front) => {
+                    let dominoes: Vec<ProjectDomino> = front
+                        .dominoes
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|d| d.into_domino())
+                        .collect();
+                    let lines: Vec<&str> = yaml.split_inclusive('\n').collect();
+                    let starts: Vec<Option<usize>> = match DominoList::find(&lines) {
+                        Some(list) if list.items.len() == dominoes.len() => {
+                            list.items.iter().map(|item| Some(item.start + 2)).collect()
+                        }
+                        _ => vec![None; dominoes.len()],
+                    };
+
+                    let mut first_seen = HashMap::new();
+                    for (domino, &line) in dominoes.iter().zip(&starts) {
+                        let Some(id) = &domino.id else {
+                            issues.push(issue(
+                                &project.index,
+                                line,
+                                format!(
+                                    "Domino {:?} has no ID; run `domino-id migrate`",
+                                    domino.task
+                                ),
+                            ));
+                            continue;
+                        };
+                        match first_seen.get(id) {
+                            Some(Some(first)) => issues.push(issue(
+                                &project.index,
+                                line,
+                                format!("Duplicate domino ID {id} (first on line {first})"),
+                            )),
+                            Some(None) => issues.push(issue(
+                                &project.index,
+                                line,
+                                format!("Duplicate domino ID {id}"),
+                            )),
+                            None => {
+                                first_seen.insert(id.clone(), line);
+                            }
+                        }
+                    }
+
+                    for (domino, &line) in dominoes.iter().zip(&starts) {
+                        for dep in domino.after.iter().filter(|d| !first_seen.contains_key(*d)) {
+                            issues.push(issue(
+                                &project.index,
+                                line,
+                                format!("Domino {:?} waits on unknown domino {dep}", domino.task),
+                            ));
+                        }
+                    }
+                    if let Err(e @ A4Error::DominoCycle { .. }) = topological_order(&dominoes) {
+                        issues.push(issue(&project.index, None, e.to_string()));
+                    }
+                    known_ids = Some(first_seen.into_keys().collect());
+                }
+            }
+        }
+    }
+
+    let log_path = project.jsonl_log();
+    if log_path.exists() {
+        let log = fs::read_to_string(&log_path)?;
+        for (i, line) in log.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let line_no = Some(i + 1);
+            let entry = match serde_json::from_str::<LogEntry>(line) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    issues.push(issue(&log_path, line_no, format!("Invalid log entry: {e}")));
+                    continue;
+                }
+            };
+            if OffsetDateTime::parse(&entry.ts, &Rfc3339).is_err() {
+                issues.push(issue(
+                    &log_path,
+                    line_no,
+                    format!("Missing or invalid ts {:?}", entry.ts),
+                ));
+            }
+            let Some(known) = &known_ids else { continue };
+            for id in entry.dominoes.iter().flatten() {
+                if !known.contains(id) {
+                    issues.push(issue(
+                        &log_path,
+                        line_no,
+                        format!("Log references unknown domino {id}"),
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Give each domino without an ID a vault-unique one (project-unique
+/// without a vault), in place. Returns the `(id, task)` pairs assigned.
+pub fn migrate_project(
+    vault: Option<&Vault>,
+    project: &Project,
+    now: &Moment,
+) -> Result<Vec<(String, String)>, A4Error> {
+    let raw = fs::read_to_string(&project.index)?;
+    let dominoes = parse_dominoes(&raw, &project.index)?;
+    if dominoes.iter().all(|d| d.id.is_some()) {
+        return Ok(Vec::new());
+    }
+
+    let mut existing = taken_ids(vault, project)?;
+    let mut assigned = Vec::new();
+
+    let updated = edit_front_matter(&raw, &project.index, |yaml| {
+        assign_ids(yaml, &dominoes, &project.index, |task| {
+            let id = generate_unique(&project.slug, task, now.instant, &existing)?;
+            existing.insert(id.clone());
+            assigned.push((id.clone(), task.to_string()));
+            Ok(id)
+        })
+    })?;
+    write_note(&project.index, &updated)?;
+    Ok(assigned)
+}
+
+/// Syntax for [`render_graph`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphSyntax {
+    Dot,
+    Mermaid,
+}
+
+/// The project's dominoes, with `done` also set for those its `log.jsonl`
+/// references.
+pub fn load_dominoes(project: &Project) -> Result<Vec<ProjectDomino>, A4Error> {
+    let raw = fs::read_to_string(&project.index)?;
+    let logged: HashSet<String> = read_log(project)?
+        .into_iter()
+        .flat_map(|(_, entry)| entry.dominoes.unwrap_or_default())
+        .collect();
+    let mut dominoes = parse_dominoes(&raw, &project.index)?;
+    for domino in &mut dominoes {
+        domino.done |= domino.id.as_ref().is_some_and(|id| logged.contains(id));
+    }
+    Ok(dominoes)
+}
+
+/// Indices of `dominoes` ordered so each comes after everything in its
+/// `after` list, otherwise keeping front matter order. IDs that no domino
+/// has are ignored here; [`lint_project`] reports them.
+pub fn topological_order(dominoes: &[ProjectDomino]) -> Result<Vec<usize>, A4Error> {
+    let mut index = HashMap::new();
+    for (i, domino) in dominoes.iter().enumerate() {
+        if let Some(id) = &domino.id {
+            index.entry(id.as_str()).or_insert(i);
+        }
+    }
+    let deps: Vec<Vec<usize>> = dominoes
+        .iter()
+        .map(|d| {
+            d.after
+                .iter()
+                .filter_map(|id| index.get(id.as_str()).copied())
+                .collect()
+        })
+        .collect();
+
+    let mut placed = vec![false; dominoes.len()];
+    let mut order = Vec::with_capacity(dominoes.len());
+    while order.len() < dominoes.len() {
+        let ready = (0..dominoes.len()).find(|&i| !placed[i] && deps[i].iter().all(|&d| placed[d]));
+        match ready {
+            Some(i) => {
+                placed[i] = true;
+                order.push(i);
+            }
+            None => {
+                // Everything left waits on something unplaced; walk those
+                // edges until a domino repeats
+                let mut path = vec![(0..dominoes.len()).find(|&i| !placed[i]).unwrap()];
+                loop {
+                    let current = *path.last().unwrap();
+                    let next = *deps[current].iter().find(|&&d| !placed[d]).unwrap();
+                    if let Some(start) = path.iter().position(|&i| i == next) {
+                        let mut chain: Vec<String> = path[start..]
+                            .iter()
+                            .map(|&i| dominoes[i].id.clone().unwrap_or_default())
+                            .collect();
+                        chain.push(chain[0].clone());
+                        return Err(A4Error::DominoCycle { chain });
+                    }
+                    path.push(next);
+                }
+            }
+        }
+    }
+    Ok(order)
+}
+
+/// Open dominoes whose `after` dominoes are all done, in dependency order.
+/// Dominoes with `status: blocked` are left out.
+pub fn next_dominoes(project: &Project) -> Result<Vec<ProjectDomino>, A4Error> {
+    let dominoes = load_dominoes(project)?;
+    let done: HashSet<&str> = dominoes
+        .iter()
+        .filter(|d| d.done)
+        .filter_map(|d| d.id.as_deref())
+        .collect();
+
+    let next = topological_order(&dominoes)?
+        .into_iter()
+        .map(|i| &dominoes[i])
+        .filter(|d| {
+            !d.done
+                && d.status.as_deref() != Some("blocked")
+                && d.after.iter().all(|id| done.contains(id.as_str()))
+        })
+        .cloned()
+        .collect();
+    Ok(next)
+}
+
+/// The project's dominoes as a graph with an edge from each domino to those
+/// that wait on it, for a ```` ```mermaid ```` block or Graphviz.
+pub fn render_graph(project: &Project, syntax: GraphSyntax) -> Result<String, A4Error> {
+    let dominoes = load_dominoes(project)?;
+    topological_order(&dominoes)?;
+
+    let label = |d: &ProjectDomino| match &d.estimate {
+        Some(estimate) => format!("{} ({estimate})", d.task),
+        None => d.task.clone(),
+    };
+    let with_ids = || dominoes.iter().filter_map(|d| Some((d.id.as_deref()?, d)));
+
+    let mut out = String::new();
+    match syntax {
+        GraphSyntax::Dot => {
+            out.push_str(&format!("digraph {:?} {{\n  rankdir=LR;\n", project.slug));
+            for (id, d) in with_ids() {
+                let style = if d.done {
+                    ", style=filled, fillcolor=lightgrey"
+                } else {
+                    ""
+                };
+                out.push_str(&format!("  {id:?} [label={:?}{style}];\n", label(d)));
+            }
+            for (id, d) in with_ids() {
+                for dep in &d.after {
+                    out.push_str(&format!("  {dep:?} -> {id:?};\n"));
+                }
+            }
+            out.push_str("}\n");
+        }
+        GraphSyntax::Mermaid => {
+            // Mermaid node names cannot contain `-`
+            let node = |id: &str| id.replace('-', "_");
+            out.push_str("graph LR\n");
+            for (id, d) in with_ids() {
+                let text = format!("{id}: {}", label(d)).replace('"', "#quot;");
+                out.push_str(&format!("  {}[\"{text}\"]\n", node(id)));
+            }
+            for (id, d) in with_ids() {
+                for dep in &d.after {
+                    out.push_str(&format!("  {} --> {}\n", node(dep), node(id)));
+                }
+            }
+            let done: Vec<String> = with_ids()
+                .filter(|(_, d)| d.done)
+                .map(|(id, _)| node(id))
+                .collect();
+            if !done.is_empty() {
+                out.push_str("  classDef done fill:#eee,color:#888\n");
+                out.push_str(&format!("  class {} done\n", done.join(",")));
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// A plain-text table per project, open dominoes first: ID, state,
+/// completion time, age, log references and task.
+pub fn render_status(statuses: &[ProjectStatus]) -> String {
+    let mut out = String::new();
+    for status in statuses {
+        let done = status.dominoes.iter().filter(|d| d.done).count();
+        out.push_str(&format!(
+            "{}  {done}/{} done\n",
+            status.project,
+            status.dominoes.len()
+        ));
+
+        let mut dominoes: Vec<&DominoStatus> = status.dominoes.iter().collect();
+        dominoes.sort_by_key(|d| d.done);
+        let id_width = dominoes
+            .iter()
+            .filter_map(|d| d.id.as_ref().map(String::len))
+            .max()
+            .unwrap_or(0)
+            .max(2);
+        for d in dominoes {
+            // `2026-01-14T09:00:00Z` -> `2026-01-14 09:00`
+            let completed = d.completed_at.as_deref().map_or_else(
+                || "-".to_string(),
+                |ts| match (ts.get(..10), ts.get(11..16)) {
+                    (Some(day), Some(time)) => format!("{day} {time}"),
+                    _ => ts.to_string(),
+                },
+            );
+            out.push_str(&format!(
+                "  {:<id_width$}  {:<4}  {completed:<16}  {:>4}  {:>3}  {}\n",
+                d.id.as_deref().unwrap_or("-"),
+                if d.done { "done" } else { "open" },
+                d.age_secs.map_or_else(|| "-".to_string(), format_age),
+                d.log_refs,
+                d.task,
+            ));
+        }
+    }
+    out
+}
+
+fn format_age(secs: i64) -> String {
+    match secs {
+        s if s < 60 * 60 => format!("{}m", s / 60),
+        s if s < 24 * 60 * 60 => format!("{}h", s / (60 * 60)),
+        s => format!("{}d", s / (24 * 60 * 60)),
+    }
+}
+
+/// `2026-01-20T12:00:00Z`, the `ts` format of `log.jsonl`.
+pub(crate) fn log_timestamp(instant: OffsetDateTime) -> String {
+    instant
+        .to_offset(time::UtcOffset::UTC)
+        .replace_nanosecond(0)
+        .expect("zero is a valid nanosecond")
+        .format(&Rfc3339)
+        .expect("UTC instants format as RFC 3339")
+}
+
+/// `log.jsonl` entries with their parsed `ts`; lines that are not entries
+/// are skipped.
+fn read_log(project: &Project) -> Result<Vec<(Option<OffsetDateTime>, LogEntry)>, A4Error> {
+    let path = project.jsonl_log();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let raw = fs::read_to_string(&path)?;
+    Ok(raw
+        .lines()
+        .filter_map(|line| serde_json::from_str::<LogEntry>(line).ok())
+        .map(|entry| (OffsetDateTime::parse(&entry.ts, &Rfc3339).ok(), entry))
+        .collect())
+}
+
+fn parse_front_matter(raw: &str, path: &Path) -> Result<IndexFrontMatter, A4Error> {
+    FrontMatter::from_text(raw)
+        .and_then(|front| front.deserialize())
+        .map_err(|e| at(path, e))
+}
+
+fn parse_dominoes(raw: &str, path: &Path) -> Result<Vec<ProjectDomino>, A4Error> {
+    Ok(parse_front_matter(raw, path)?
+        .dominoes
+        .unwrap_or_default()
+        .into_iter()
+        .map(|d| d.into_domino())
+        .collect())
+}
+
+/// Apply `edit` to the front matter YAML, creating front matter if there is
+/// none, and check the result still parses as a hub's front matter.
+fn edit_front_matter<F>(raw: &str, path: &Path, edit: F) -> Result<String, A4Error>
+where
+    F: FnOnce(&str) -> Result<String, A4Error>,
+{
+    let mut front = FrontMatter::from_text(raw).map_err(|e| at(path, e))?;
+    front.edit(edit).map_err(|e| at(path, e))?;
+    front
+        .deserialize::<IndexFrontMatter>()
+        .map_err(|e| at(path, e))?;
+    Ok(front.apply_to(raw))
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// The value of an `id:` field on `line`, whether or not it opens a list
+/// item.
+fn id_field(line: &str) -> Option<&str> {
+    let field = line.trim_start();
+    let field = field.strip_prefix("- ").unwrap_or(field);
+    Some(field.strip_prefix("id:")?.trim().trim_matches(['"', '\'']))
+}
+
+/// A `- id/task` list item with its dash at column `dash`.
+fn domino_item(dash: usize, id: &str, task: &str) -> String {
+    let pad = " ".repeat(dash);
+    let task = serde_json::to_string(task).expect("strings serialize");
+    format!("{pad}- id: {id}\n{pad}  task: {task}\n")
+}
+
+/// Where the block-style `dominoes:` list sits in front matter lines.
+struct DominoList {
+    /// Line of the `dominoes:` key
+    key: usize,
+    /// Line after the last item
+    end: usize,
+    /// Column of the items' dashes
+    dash: usize,
+    items: Vec<Range<usize>>,
+}
+
+impl DominoList {
+    fn find(lines: &[&str]) -> Option<Self> {
+        let key = lines.iter().position(|l| {
+            l.strip_prefix("dominoes")
+                .is_some_and(|rest| rest.starts_with(':'))
+        })?;
+
+        // The list runs until the next top-level line
+        let mut end = key + 1;
+        while end < lines.len()
+            && (lines[end].starts_with([' ', '-']) || lines[end].trim().is_empty())
+        {
+            end += 1;
+        }
+        while end > key + 1 && lines[end - 1].trim().is_empty() {
+            end -= 1;
+        }
+        let dash = lines[key + 1..end]
+            .iter()
+            .find(|l| l.trim_start().starts_with('-'))
+            .map_or(2, |l| indent_of(l));
+
+        let starts: Vec<usize> = (key + 1..end)
+            .filter(|&i| indent_of(lines[i]) == dash && lines[i].trim_start().starts_with('-'))
+            .collect();
+        let items = starts
+            .iter()
+            .zip(starts.iter().skip(1).copied().chain([end]))
+            .map(|(&start, next)| start..next)
+            .collect();
+
+        Some(DominoList {
+            key,
+            end,
+            dash,
+            items,
+        })
+    }
+
+    /// The flow-style value after `dominoes:`, e.g. `[]`, if any.
+    fn inline_value<'a>(&self, lines: &[&'a str]) -> &'a str {
+        lines[self.key]["dominoes:".len()..].trim()
+    }
+}
+
+/// Append a `- id/task` item to the `dominoes:` list, creating the key if
+/// it is missing.
+fn insert_domino(yaml: &str, id: &str, task: &str, path: &Path) -> Result<String, A4Error> {
+    let lines: Vec<&str> = yaml.split_inclusive('\n').collect();
+    let Some(list) = DominoList::find(&lines) else {
+        let mut out = yaml.to_string();
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str("dominoes:\n");
+        out.push_str(&domino_item(2, id, task));
+        return Ok(out);
+    };
+
+    let inline = list.inline_value(&lines);
+    if !inline.is_empty() && !inline.starts_with('#') && inline != "[]" {
+        return Err(A4Error::FrontMatterParse(format!(
+            "{}: `dominoes:` must be a block list to add to it",
+            path.display()
+        )));
+    }
+
+    let mut out = lines[..list.key].concat();
+    out.push_str(if inline == "[]" {
+        "dominoes:\n"
+    } else {
+        lines[list.key]
+    });
+    out.push_str(&lines[list.key + 1..list.end].concat());
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(&domino_item(list.dash, id, task));
+    out.push_str(&lines[list.end..].concat());
+    Ok(out)
+}
+
+/// Set `done: true` on the list item whose `id` is `id`, or `None` if no
+/// item has it.
+fn mark_done(yaml: &str, id: &str) -> Option<String> {
+    let lines: Vec<&str> = yaml.split_inclusive('\n').collect();
+    let list = DominoList::find(&lines)?;
+    let item = list.items.iter().find(|item| {
+        lines[(*item).clone()]
+            .iter()
+            .any(|l| id_field(l) == Some(id))
+    })?;
+
+    let field = " ".repeat(list.dash + 2);
+    let done_line = format!("{field}done: true\n");
+    let mut out: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+    let field_named = |i: &usize, name: &str| {
+        indent_of(lines[*i]) == list.dash + 2
+            && lines[*i]
+                .trim_start()
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.starts_with(':'))
+    };
+    // A `status:` field would otherwise contradict `done: true`
+    if let Some(i) = item.clone().skip(1).find(|i| field_named(i, "status")) {
+        out[i] = format!("{field}status: done\n");
+    }
+    let existing = item.clone().skip(1).find(|i| field_named(i, "done"));
+    match existing {
+        Some(i) => out[i] = done_line,
+        None => {
+            let mut last = item.end - 1;
+            while last > item.start && lines[last].trim().is_empty() {
+                last -= 1;
+            }
+            if !out[last].ends_with('\n') {
+                out[last].push('\n');
+            }
+            out.insert(last + 1, done_line);
+        }
+    }
+    Some(out.concat())
+}
+
+/// Rewrite each list item whose domino in `dominoes` has no ID so that it
+/// has the one `assign` returns for its task.
+fn assign_ids<F>(
+    yaml: &str,
+    dominoes: &[ProjectDomino],
+    path: &Path,
+    mut assign: F,
+) -> Result<String, A4Error>
+where
+    F: FnMut(&str) -> Result<String, A4Error>,
+{
+    let lines: Vec<&str> = yaml.split_inclusive('\n').collect();
+    let list = DominoList::find(&lines)
+        .filter(|list| list.items.len() == dominoes.len())
+        .ok_or_else(|| {
+            A4Error::FrontMatterParse(format!(
+                "{}: `dominoes:` must be a block list with one line per item to migrate it",
+                path.display()
+            ))
+        })?;
+
+    let pad = " ".repeat(list.dash);
+    let mut out = lines[..list.items.first().map_or(list.end, |i| i.start)].concat();
+    for (item, domino) in list.items.iter().zip(dominoes) {
+        let first = lines[item.start].trim_start()[1..].trim_start();
+        match &domino.id {
+            Some(_) => out.push_str(&lines[item.clone()].concat()),
+            // A plain string: replace it with an id/task mapping
+            None if item.len() == 1 && serde_yaml::from_str::<String>(first).is_ok() => {
+                out.push_str(&domino_item(
+                    list.dash,
+                    &assign(&domino.task)?,
+                    &domino.task,
+                ));
+            }
+            // A mapping without `id`: put one in front of its first field
+            None if !first.starts_with('{') => {
+                out.push_str(&format!("{pad}- id: {}\n", assign(&domino.task)?));
+                out.push_str(&format!("{pad}  {first}"));
+                if !first.ends_with('\n') {
+                    out.push('\n');
+                }
+                out.push_str(&lines[item.start + 1..item.end].concat());
+            }
+            None => {
+                tracing::warn!("Leaving {:?} without an ID: edit it by hand", domino.task);
+                out.push_str(&lines[item.clone()].concat());
+            }
+        }
+    }
+    out.push_str(&lines[list.end..].concat());
+    Ok(out)
+}
+
+/// Beads-style ID generation: try nonces at growing lengths until a
+/// candidate is not in `existing`.
+fn generate_unique(
+    project_slug: &str,
+    task: &str,
+    created_at: OffsetDateTime,
+    existing: &HashSet<String>,
+) -> Result<String, A4Error> {
+    let mut length = optimal_length(existing.len());
+
+    loop {
+        // Try nonces 0..10 at this length
+        for nonce in 0..10 {
+            let id = generate_candidate(project_slug, task, created_at, nonce, length);
+            if !existing.contains(&id) {
+                return Ok(id);
+            }
+        }
+
+        // All nonces collided, increase length
+        if length < MAX_HASH_LENGTH {
+            length += 1;
+        } else {
+            // Fallback: try more nonces with max length
+            for nonce in 10..1000 {
+                let id = generate_candidate(project_slug, task, created_at, nonce, MAX_HASH_LENGTH);
+                if !existing.contains(&id) {
+                    return Ok(id);
+                }
+            }
+            return Err(A4Error::DominoIdsExhausted {
+                project: project_slug.to_string(),
+            });
+        }
+    }
+}
+
+/// Shortest hash length keeping the chance of a collision among
+/// `existing_count` IDs under 25% (birthday approximation).
+fn optimal_length(existing_count: usize) -> usize {
+    let n = existing_count as f64;
+    let max_prob = 0.25;
+
+    for len in MIN_HASH_LENGTH..=MAX_HASH_LENGTH {
+        let space = 36_f64.powi(len as i32);
+        // Birthday problem: P(collision) ≈ 1 - e^(-n²/2d)
+        let prob = 1.0 - (-n * n / (2.0 * space)).exp();
+        if prob < max_prob {
+            return len;
+        }
+    }
+    MAX_HASH_LENGTH
+}
+
+/// `d-` and `hash_length` base36 characters of the SHA-256 of
+/// `slug|task|nanos|nonce`.
+fn generate_candidate(
+    project_slug: &str,
+    task: &str,
+    created_at: OffsetDateTime,
+    nonce: u32,
+    hash_length: usize,
+) -> String {
+    let seed = format!(
+        "{project_slug}|{task}|{}|{nonce}",
+        created_at.unix_timestamp_nanos()
+    );
+    format!("{PREFIX}-{}", compute_hash(&seed, hash_length))
+}
+
+fn compute_hash(input: &str, length: usize) -> String {
+    let digest = Sha256::digest(input.as_bytes());
+
+    // Use first 8 bytes for a 64-bit integer
+    let num = digest
+        .iter()
+        .take(8)
+        .fold(0u64, |num, &byte| (num << 8) | u64::from(byte));
+
+    format!("{:0>length$}", base36_encode(num))
+        .chars()
+        .take(length)
+        .collect()
+}
+
+fn base36_encode(mut num: u64) -> String {
+    const CHARS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    if num == 0 {
+        return "0".to_string();
+    }
+    let mut chars = Vec::new();
+    while num > 0 {
+        chars.push(CHARS[(num % 36) as usize] as char);
+        num /= 36;
+    }
+    chars.into_iter().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::{Clock, FixedClock, Zone};
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn moment(day: u8, hour: u8) -> Moment {
+        let instant = time::Date::from_calendar_date(2026, time::Month::January, day)
+            .unwrap()
+            .with_hms(hour, 0, 0)
+            .unwrap()
+            .assume_utc();
+        Moment {
+            instant: FixedClock(instant).now_utc(),
+            filename_zone: Zone::Utc,
+            local_zone: Zone::Utc,
+            rollover_hour: 0,
+        }
+    }
+
+    /// A vault with `projects/<slug>/index.md` holding `index`.
+    fn vault_with(slug: &str, index: &str) -> (TempDir, Vault, Project) {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let project = Project::resolve(&vault, slug).unwrap();
+        std::fs::create_dir_all(project.index.parent().unwrap()).unwrap();
+        std::fs::write(&project.index, index).unwrap();
+        (temp_dir, vault, project)
+    }
+
+    #[test]
+    fn test_base36_and_hash() {
+        assert_eq!(base36_encode(0), "0");
+        assert_eq!(base36_encode(35), "z");
+        assert_eq!(base36_encode(36), "10");
+        assert_eq!(base36_encode(1295), "zz");
+
+        assert_eq!(compute_hash("test input", 3).len(), 3);
+        assert_eq!(compute_hash("test input", 5).len(), 5);
+        assert_eq!(compute_hash("same", 5), compute_hash("same", 5));
+        assert_ne!(compute_hash("input one", 5), compute_hash("input two", 5));
+
+        assert_eq!(optimal_length(0), MIN_HASH_LENGTH);
+        assert!(optimal_length(10000) >= optimal_length(10));
+    }
+
+    #[test]
+    fn test_generate_unique_skips_existing() {
+        let at = moment(19, 12).instant;
+        let first = generate_unique("p", "task", at, &HashSet::new()).unwrap();
+        assert!(first.starts_with("d-"));
+        assert_eq!(first.len(), 2 + MIN_HASH_LENGTH);
+
+        let taken = HashSet::from([first.clone()]);
+        let second = generate_unique("p", "task", at, &taken).unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_ids_are_unique_across_the_vault() {
+        let (_temp_dir, vault, alpha) =
+            vault_with("alpha", "---\ndominoes:\n  - id: d-abc\n    task: x\n---\n");
+        let beta = Project::resolve(&vault, "beta").unwrap();
+        std::fs::create_dir_all(beta.index.parent().unwrap()).unwrap();
+        std::fs::write(&beta.index, "# Beta\n").unwrap();
+        std::fs::write(
+            beta.jsonl_log(),
+            "{\"ts\":\"2026-01-19T12:00:00Z\",\"what\":\"w\",\"dominoes\":[\"d-xyz\"]}\n",
+        )
+        .unwrap();
+
+        let slugs: Vec<_> = list_projects(&vault)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.slug)
+            .collect();
+        assert_eq!(slugs, ["alpha", "beta"]);
+        assert_eq!(
+            vault_ids(&vault).unwrap(),
+            HashSet::from(["d-abc".to_string(), "d-xyz".to_string()])
+        );
+        assert_eq!(find_project(&vault, "alpha").unwrap(), alpha);
+        assert!(find_project(&vault, "gamma").is_err());
+    }
+
+    #[test]
+    fn test_path_projects_need_no_vault() {
+        assert_eq!(project_path("gb-ppu"), None);
+        assert!(project_path("index.md").is_some());
+        assert!(project_path("projects/gb-ppu").is_some());
+
+        let temp_dir = TempDir::new().unwrap();
+        let index = temp_dir.path().join("test-project").join("index.md");
+        assert!(open_project_at(&index).is_err());
+
+        std::fs::create_dir_all(index.parent().unwrap()).unwrap();
+        std::fs::write(
+            &index,
+            "---\nkind: project\ndominoes:\n  - simple task\n  - id: d-abc\n    task: current task\n---\n# Test\n",
+        )
+        .unwrap();
+        let project = open_project_at(&index).unwrap();
+        assert_eq!(project.slug, "test-project");
+        std::fs::write(
+            project.jsonl_log(),
+            "{\"ts\":\"2026-01-19T12:00:00Z\",\"what\":\"did stuff\",\"dominoes\":[\"d-xyz\"]}\n",
+        )
+        .unwrap();
+
+        let ids = project_ids(&project).unwrap();
+        assert_eq!(
+            ids,
+            HashSet::from(["d-abc".to_string(), "d-xyz".to_string()])
+        );
+        let id = generate_id(None, &project, "new task", &moment(19, 12)).unwrap();
+        assert!(id.starts_with("d-") && !ids.contains(&id));
+    }
+
+    #[test]
+    fn test_add_complete_and_log() {
+        let (_temp_dir, vault, project) = vault_with(
+            "gb-ppu",
+            "---\nkind: hub.project\ndominoes: []\nstatus: active # keep me\n---\n# PPU\n\nBody\n",
+        );
+
+        let first = add_domino(Some(&vault), &project, "write \"spec\"", &moment(19, 9)).unwrap();
+        let second = add_domino(Some(&vault), &project, "ship it", &moment(19, 10)).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&project.index).unwrap(),
+            format!(
+                "---\nkind: hub.project\ndominoes:\n  - id: {first}\n    task: \"write \\\"spec\\\"\"\n  - id: {second}\n    task: \"ship it\"\nstatus: active # keep me\n---\n# PPU\n\nBody\n"
+            )
+        );
+
+        let line = complete_domino(&project, &first, None, &moment(20, 12)).unwrap();
+        assert_eq!(
+            line,
+            format!(
+                "{{\"ts\":\"2026-01-20T12:00:00Z\",\"what\":\"Completed: write \\\"spec\\\"\",\"dominoes\":[\"{first}\"]}}"
+            )
+        );
+        let index = std::fs::read_to_string(&project.index).unwrap();
+        assert!(index.contains(&format!(
+            "  - id: {first}\n    task: \"write \\\"spec\\\"\"\n    done: true\n  - id: {second}"
+        )));
+        assert!(matches!(
+            complete_domino(&project, "d-nope", None, &moment(20, 12)),
+            Err(A4Error::DominoNotFound { .. })
+        ));
+
+        append_log(&project, "planned", &[], &moment(21, 8)).unwrap();
+        let log = std::fs::read_to_string(project.jsonl_log()).unwrap();
+        assert!(log.ends_with("{\"ts\":\"2026-01-21T08:00:00Z\",\"what\":\"planned\"}\n"));
+    }
+
+    #[test]
+    fn test_add_creates_front_matter() {
+        let (_temp_dir, vault, project) = vault_with("bare", "# Bare\n");
+        let id = add_domino(Some(&vault), &project, "first", &moment(19, 9)).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&project.index).unwrap(),
+            format!("---\ndominoes:\n  - id: {id}\n    task: \"first\"\n---\n# Bare\n")
+        );
+    }
+
+    #[test]
+    fn test_project_status_joins_log() {
+        let (_temp_dir, _vault, project) = vault_with(
+            "alpha",
+            "---\ncreated: 2026-01-10\ndominoes:\n  - id: d-abc\n    task: logged twice\n  - id: d-def\n    task: marked done\n    done: true\n  - id: d-ghi\n    task: still open\n  - no id\n---\n",
+        );
+        std::fs::write(
+            project.jsonl_log(),
+            concat!(
+                "{\"ts\":\"2026-01-12T09:00:00Z\",\"what\":\"started\",\"dominoes\":[\"d-abc\"]}\n",
+                "not json\n",
+                "{\"ts\":\"2026-01-14T09:00:00Z\",\"what\":\"finished\",\"dominoes\":[\"d-abc\"]}\n",
+            ),
+        )
+        .unwrap();
+
+        let status = project_status(&project, &moment(20, 0)).unwrap();
+        assert_eq!(status.project, "alpha");
+        let [abc, def, ghi, simple] = &status.dominoes[..] else {
+            panic!("expected four dominoes: {status:?}");
+        };
+        assert!(abc.done);
+        assert_eq!(abc.log_refs, 2);
+        assert_eq!(abc.completed_at.as_deref(), Some("2026-01-14T09:00:00Z"));
+        // Created on the 10th, completed on the 14th at 09:00
+        assert_eq!(abc.age_secs, Some(4 * 86400 + 9 * 3600));
+        assert!(def.done);
+        assert_eq!(def.completed_at, None);
+        assert!(!ghi.done);
+        assert_eq!(ghi.age_secs, Some(10 * 86400));
+        assert_eq!(simple.id, None);
+        assert_eq!(simple.task, "no id");
+
+        let table = render_status(&[status]);
+        assert!(table.starts_with("alpha  2/4 done\n"));
+        assert!(table.contains("  d-ghi  open  -                  10d    0  still open\n"));
+        assert!(table.contains("  d-abc  done  2026-01-14 09:00    4d    2  logged twice\n"));
+    }
+
+    #[test]
+    fn test_lint_project_reports_problems() {
+        let (_temp_dir, _vault, project) = vault_with(
+            "alpha",
+            "---\nkind: project\ndominoes:\n  - id: d-abc\n    task: first\n  - no id yet\n  - id: d-abc\n    task: copy\n---\n# Test\n",
+        );
+        std::fs::write(
+            project.jsonl_log(),
+            concat!(
+                "{\"ts\":\"2026-01-19T12:00:00Z\",\"what\":\"ok\",\"dominoes\":[\"d-abc\"]}\n",
+                "{\"ts\":\"2026-01-19T12:00:00Z\",\"what\":\"gone\",\"dominoes\":[\"d-zzz\"]}\n",
+                "{not json\n",
+                "{\"what\":\"no ts\"}\n",
+            ),
+        )
+        .unwrap();
+
+        let issues: Vec<String> = lint_project(&project)
+            .unwrap()
+            .iter()
+            .map(|issue| {
+                let file = issue.path.file_name().unwrap().to_string_lossy();
+                format!("{file}:{}: {}", issue.line.unwrap(), issue.message)
+            })
+            .collect();
+        assert_eq!(issues.len(), 5, "{issues:#?}");
+        assert_eq!(
+            issues[0],
+            "index.md:6: Domino \"no id yet\" has no ID; run `domino-id migrate`"
+        );
+        assert_eq!(
+            issues[1],
+            "index.md:7: Duplicate domino ID d-abc (first on line 4)"
+        );
+        assert_eq!(
+            issues[2],
+            "log.jsonl:2: Log references unknown domino d-zzz"
+        );
+        assert!(issues[3].starts_with("log.jsonl:3: Invalid log entry: "));
+        assert_eq!(issues[4], "log.jsonl:4: Missing or invalid ts \"\"");
+    }
+
+    #[test]
+    fn test_lint_project_reports_yaml_line() {
+        let (_temp_dir, _vault, project) = vault_with(
+            "alpha",
+            "---\nkind: project\ndominoes:\n  - id: d-abc\n   task: [oops\n---\n# Test\n",
+        );
+        let issues = lint_project(&project).unwrap();
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.starts_with("Invalid front matter: "));
+        assert!(!issues[0].message.contains(" at line "));
+        assert!(matches!(issues[0].line, Some(4..=6)), "{issues:?}");
+        assert!(matches!(
+            project_status(&project, &moment(20, 0)),
+            Err(A4Error::FrontMatterParse(_))
+        ));
+    }
+
+    #[test]
+    fn test_lint_project_reports_unclosed_front_matter() {
+        for index in [
+            "---\nkind: project\n# Test\n",
+            "---\r\nkind: project\r\n# Test\r\n",
+        ] {
+            let (_temp_dir, _vault, project) = vault_with("alpha", index);
+            let issues = lint_project(&project).unwrap();
+            assert_eq!(issues.len(), 1, "{issues:?}");
+            assert_eq!(issues[0].line, Some(1));
+            assert!(issues[0].message.contains("never closed"));
+        }
+    }
+
+    #[test]
+    fn test_migrate_project_assigns_ids_in_place() {
+        let (_temp_dir, vault, project) = vault_with(
+            "alpha",
+            "---\nkind: project\ndominoes:\n  - id: d-abc\n    task: has id\n  - \"quoted task\"\n  - bare task\n  - task: mapping\n    done: true\nstatus: active\n---\n# Test\n\n- \"not a domino\"\n",
+        );
+
+        let assigned = migrate_project(Some(&vault), &project, &moment(19, 9)).unwrap();
+        let ids: Vec<&str> = assigned.iter().map(|(id, _)| id.as_str()).collect();
+        let [quoted, bare, mapping] = ids[..] else {
+            panic!("expected three IDs: {assigned:?}");
+        };
+        assert_eq!(
+            std::fs::read_to_string(&project.index).unwrap(),
+            format!(
+                "---\nkind: project\ndominoes:\n  - id: d-abc\n    task: has id\n  - id: {quoted}\n    task: \"quoted task\"\n  - id: {bare}\n    task: \"bare task\"\n  - id: {mapping}\n    task: mapping\n    done: true\nstatus: active\n---\n# Test\n\n- \"not a domino\"\n"
+            )
+        );
+        assert!(lint_project(&project).unwrap().is_empty());
+        assert!(migrate_project(Some(&vault), &project, &moment(19, 9))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_next_and_graph_follow_after() {
+        let (_temp_dir, _vault, project) = vault_with(
+            "gb-ppu",
+            "---\ndominoes:\n  - id: d-spr\n    task: Sprites\n    after: [d-til, d-oam]\n  - id: d-til\n    task: Tiles\n    estimate: 2h\n  - id: d-oam\n    task: OAM scan\n    after: d-dma\n  - id: d-dma\n    task: DMA\n    status: done\n  - id: d-win\n    task: Window\n    status: blocked\n---\n",
+        );
+        let next: Vec<_> = next_dominoes(&project)
+            .unwrap()
+            .into_iter()
+            .map(|d| d.id.unwrap())
+            .collect();
+        assert_eq!(next, ["d-til", "d-oam"]);
+
+        let dominoes = load_dominoes(&project).unwrap();
+        let order: Vec<_> = topological_order(&dominoes)
+            .unwrap()
+            .into_iter()
+            .map(|i| dominoes[i].id.as_deref().unwrap())
+            .collect();
+        assert_eq!(order, ["d-til", "d-dma", "d-oam", "d-spr", "d-win"]);
+
+        let mermaid = render_graph(&project, GraphSyntax::Mermaid).unwrap();
+        assert!(mermaid.starts_with("graph LR\n  d_spr[\"d-spr: Sprites\"]\n"));
+        assert!(mermaid.contains("  d_til[\"d-til: Tiles (2h)\"]\n"));
+        assert!(mermaid.contains("  d_til --> d_spr\n  d_oam --> d_spr\n  d_dma --> d_oam\n"));
+        assert!(mermaid.ends_with("  class d_dma done\n"));
+
+        let dot = render_graph(&project, GraphSyntax::Dot).unwrap();
+        assert!(dot.starts_with("digraph \"gb-ppu\" {\n  rankdir=LR;\n"));
+        assert!(dot.contains("  \"d-dma\" [label=\"DMA\", style=filled, fillcolor=lightgrey];\n"));
+        assert!(dot.contains("  \"d-til\" -> \"d-spr\";\n"));
+    }
+
+    #[test]
+    fn test_cycles_are_reported() {
+        let (_temp_dir, _vault, project) = vault_with(
+            "gb-ppu",
+            "---\ndominoes:\n  - id: d-a\n    task: A\n  - id: d-b\n    task: B\n    after: [d-c]\n  - id: d-c\n    task: C\n    after: [d-b, d-x]\n---\n",
+        );
+        match next_dominoes(&project) {
+            Err(A4Error::DominoCycle { chain }) => assert_eq!(chain, ["d-b", "d-c", "d-b"]),
+            other => panic!("expected a cycle: {other:?}"),
+        }
+        let issues: Vec<String> = lint_project(&project)
+            .unwrap()
+            .iter()
+            .map(|issue| issue.message.clone())
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                "Domino \"C\" waits on unknown domino d-x",
+                "Dominoes wait on each other: d-b -> d-c -> d-b",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_complete_sets_status_done() {
+        let (_temp_dir, _vault, project) = vault_with(
+            "gb-ppu",
+            "---\ndominoes:\n  - id: d-a\n    task: A\n    status: doing\n    estimate: 3\n---\n",
+        );
+        complete_domino(&project, "d-a", None, &moment(20, 12)).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&project.index).unwrap(),
+            "---\ndominoes:\n  - id: d-a\n    task: A\n    status: done\n    estimate: 3\n    done: true\n---\n"
+        );
+        let status = project_status(&project, &moment(20, 12)).unwrap();
+        assert_eq!(status.dominoes[0].estimate.as_deref(), Some("3"));
+    }
+}
@@ -1,6 +1,18 @@
+use serde_json::{json, Value};
 use std::path::PathBuf;
 use thiserror::Error;
 
+/// Exit codes of the `a4` CLI, stable for scripts and apps that drive it.
+/// `2` is reserved for command-line usage errors.
+pub mod exit_code {
+    pub const GENERAL: i32 = 1;
+    pub const VAULT_NOT_FOUND: i32 = 3;
+    pub const INVALID_ANCHOR: i32 = 4;
+    pub const GIT_DIVERGENCE: i32 = 5;
+    pub const CONFLICT: i32 = 6;
+    pub const IO: i32 = 7;
+}
+
 #[derive(Error, Debug)]
 pub enum A4Error {
     #[error("IO error: {0}")]
@@ -21,13 +33,36 @@ pub enum A4Error {
     #[error("Path traversal attempt detected: {path}")]
     PathTraversal { path: PathBuf },
 
+    #[error("Not a Markdown note: {path}")]
+    NotANote { path: PathBuf },
+
     #[error("Invalid UTF-8 in file: {path}")]
     InvalidUtf8 { path: PathBuf },
 
+    #[error("Invalid date '{input}': expected YYYY-MM-DD")]
+    InvalidDate { input: String },
+
+    #[error("Invalid ISO week '{input}': expected YYYY-Www")]
+    InvalidIsoWeek { input: String },
+
+    #[error("Invalid journal session '{input}': expected morning or evening")]
+    InvalidJournalSession { input: String },
+
+    #[error("Unknown timezone '{name}': expected UTC or an IANA name such as America/Los_Angeles")]
+    UnknownTimezone { name: String },
+
     #[error("Template not found: {path}")]
     TemplateNotFound { path: PathBuf },
 
-    #[error("Git repository not initialized at {path}. Initialize with 'git init' and set remote, or use 'a4 sync --init' (future)")]
+    #[error("Unknown template variable '{{{{{name}}}}}' on line {line}")]
+    TemplateUnknownVariable { name: String, line: usize },
+
+    #[error("Template error on line {line}: {message}")]
+    TemplateSyntax { line: usize, message: String },
+
+    #[error(
+        "Git repository not initialized at {path}. Initialize it with 'a4 init [--remote <url>]'"
+    )]
     GitRepoNotFound { path: PathBuf },
 
     #[error("Git divergence detected: local HEAD at {local_sha}, remote at {remote_sha}. Resolve divergence via manual rebase/merge")]
@@ -36,9 +71,174 @@ pub enum A4Error {
         remote_sha: String,
     },
 
+    #[error(
+        "Rebase conflict detected when syncing with {remote_ref}. Please resolve the conflicts manually:\n\
+        1. Run 'git rebase {remote_ref}' in the vault directory\n\
+        2. Resolve any conflicts\n\
+        3. Run 'git rebase --continue' after resolving\n\
+        4. Run 'a4 sync' again to push changes"
+    )]
+    SyncConflict { remote_ref: String },
+
     #[error("No remote configured for repository")]
     NoRemote,
 
+    #[error("Invalid config {path}: {message}")]
+    Config { path: PathBuf, message: String },
+
     #[error("Failed to parse front matter: {0}")]
     FrontMatterParse(String),
+
+    #[error("Transclusion target not found: {target}")]
+    TransclusionNotFound { target: String },
+
+    #[error("Transclusion cycle detected: {}", chain.join(" -> "))]
+    TransclusionCycle { chain: Vec<String> },
+
+    #[error("Block {fragment} not found in {path}")]
+    BlockNotFound { path: PathBuf, fragment: String },
+
+    #[error("Block {fragment} in {path} has already been triaged")]
+    AlreadyTriaged { path: PathBuf, fragment: String },
+
+    #[error("No readable content found in {path}")]
+    NoReadableContent { path: PathBuf },
+
+    #[error("Invalid slug '{input}': expected lowercase letters, digits and '-' (2-64 chars)")]
+    InvalidSlug { input: String },
+
+    #[error("Project '{slug}' not found: {path} does not exist")]
+    ProjectNotFound { slug: String, path: PathBuf },
+
+    #[error("Domino {id} not found in {path}")]
+    DominoNotFound { id: String, path: PathBuf },
+
+    #[error("Dominoes wait on each other: {}", chain.join(" -> "))]
+    DominoCycle { chain: Vec<String> },
+
+    #[error("Could not generate a unique domino ID for {project} after 1000 attempts")]
+    DominoIdsExhausted { project: String },
+}
+
+impl A4Error {
+    /// Stable machine-readable name for the variant, e.g. `vault_not_found`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            A4Error::Io(_) => "io",
+            A4Error::VaultNotFound { .. } => "vault_not_found",
+            A4Error::InvalidVaultPath { .. } => "invalid_vault_path",
+            A4Error::InvalidAnchorToken { .. } => "invalid_anchor",
+            A4Error::Git(_) => "git",
+            A4Error::PathTraversal { .. } => "path_traversal",
+            A4Error::NotANote { .. } => "not_a_note",
+            A4Error::InvalidUtf8 { .. } => "invalid_utf8",
+            A4Error::InvalidDate { .. } => "invalid_date",
+            A4Error::InvalidIsoWeek { .. } => "invalid_iso_week",
+            A4Error::InvalidJournalSession { .. } => "invalid_journal_session",
+            A4Error::UnknownTimezone { .. } => "unknown_timezone",
+            A4Error::TemplateNotFound { .. } => "template_not_found",
+            A4Error::TemplateUnknownVariable { .. } => "template_unknown_variable",
+            A4Error::TemplateSyntax { .. } => "template_syntax",
+            A4Error::GitRepoNotFound { .. } => "git_repo_not_found",
+            A4Error::GitDivergence { .. } => "git_divergence",
+            A4Error::SyncConflict { .. } => "sync_conflict",
+            A4Error::NoRemote => "no_remote",
+            A4Error::Config { .. } => "config",
+            A4Error::FrontMatterParse(_) => "front_matter_parse",
+            A4Error::TransclusionNotFound { .. } => "transclusion_not_found",
+            A4Error::TransclusionCycle { .. } => "transclusion_cycle",
+            A4Error::BlockNotFound { .. } => "block_not_found",
+            A4Error::AlreadyTriaged { .. } => "already_triaged",
+            A4Error::NoReadableContent { .. } => "no_readable_content",
+            A4Error::InvalidSlug { .. } => "invalid_slug",
+            A4Error::ProjectNotFound { .. } => "project_not_found",
+            A4Error::DominoNotFound { .. } => "domino_not_found",
+            A4Error::DominoCycle { .. } => "domino_cycle",
+            A4Error::DominoIdsExhausted { .. } => "domino_ids_exhausted",
+        }
+    }
+
+    /// The CLI exit code for this error; see [`exit_code`].
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            A4Error::VaultNotFound { .. } | A4Error::InvalidVaultPath { .. } => {
+                exit_code::VAULT_NOT_FOUND
+            }
+            A4Error::InvalidAnchorToken { .. } => exit_code::INVALID_ANCHOR,
+            A4Error::GitDivergence { .. } => exit_code::GIT_DIVERGENCE,
+            A4Error::SyncConflict { .. } => exit_code::CONFLICT,
+            A4Error::Io(_) | A4Error::InvalidUtf8 { .. } => exit_code::IO,
+            _ => exit_code::GENERAL,
+        }
+    }
+
+    /// The variant's fields, for structured error output.
+    pub fn details(&self) -> Value {
+        match self {
+            A4Error::Io(e) => json!({ "kind": e.kind().to_string() }),
+            A4Error::VaultNotFound { attempts } => json!({ "attempts": attempts }),
+            A4Error::InvalidVaultPath { path }
+            | A4Error::PathTraversal { path }
+            | A4Error::NotANote { path }
+            | A4Error::InvalidUtf8 { path }
+            | A4Error::TemplateNotFound { path }
+            | A4Error::NoReadableContent { path }
+            | A4Error::GitRepoNotFound { path } => json!({ "path": path }),
+            A4Error::InvalidAnchorToken { token, reason } => {
+                json!({ "token": token, "reason": reason })
+            }
+            A4Error::Git(_) | A4Error::FrontMatterParse(_) | A4Error::NoRemote => json!({}),
+            A4Error::InvalidDate { input }
+            | A4Error::InvalidIsoWeek { input }
+            | A4Error::InvalidJournalSession { input }
+            | A4Error::InvalidSlug { input } => {
+                json!({ "input": input })
+            }
+            A4Error::UnknownTimezone { name } => json!({ "name": name }),
+            A4Error::TemplateUnknownVariable { name, line } => {
+                json!({ "name": name, "line": line })
+            }
+            A4Error::TemplateSyntax { line, .. } => json!({ "line": line }),
+            A4Error::GitDivergence {
+                local_sha,
+                remote_sha,
+            } => json!({ "local_sha": local_sha, "remote_sha": remote_sha }),
+            A4Error::SyncConflict { remote_ref } => json!({ "remote_ref": remote_ref }),
+            A4Error::Config { path, .. } => json!({ "path": path }),
+            A4Error::ProjectNotFound { slug, path } => json!({ "slug": slug, "path": path }),
+            A4Error::DominoNotFound { id, path } => json!({ "id": id, "path": path }),
+            A4Error::DominoIdsExhausted { project } => json!({ "project": project }),
+            A4Error::TransclusionNotFound { target } => json!({ "target": target }),
+            A4Error::TransclusionCycle { chain } | A4Error::DominoCycle { chain } => {
+                json!({ "chain": chain })
+            }
+            A4Error::BlockNotFound { path, fragment }
+            | A4Error::AlreadyTriaged { path, fragment } => {
+                json!({ "path": path, "fragment": fragment })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_and_details() {
+        let err = A4Error::InvalidAnchorToken {
+            token: "Focus".to_string(),
+            reason: "uppercase".to_string(),
+        };
+        assert_eq!(err.code(), "invalid_anchor");
+        assert_eq!(err.exit_code(), exit_code::INVALID_ANCHOR);
+        assert_eq!(
+            err.details(),
+            json!({ "token": "Focus", "reason": "uppercase" })
+        );
+
+        let err = A4Error::Io(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(err.exit_code(), exit_code::IO);
+        assert_eq!(A4Error::NoRemote.exit_code(), exit_code::GENERAL);
+    }
 }
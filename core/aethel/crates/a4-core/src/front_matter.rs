@@ -0,0 +1,442 @@
+//! Typed access to note front matter (protocol §6).
+//!
+//! [`FrontMatter`] keeps the YAML it was parsed from next to an
+//! order-preserving [`Mapping`] of its fields. [`FrontMatter::set`] rewrites
+//! only the lines of the field it changes, so unknown fields, comments and
+//! formatting elsewhere survive byte-for-byte; the written field wins, as in
+//! the protocol's field-level "last write wins".
+
+use crate::date::UtcDay;
+use crate::error::A4Error;
+use crate::notes::{read_note, split_front_matter, write_note, Note};
+use fs_err as fs;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use std::ops::Range;
+use std::path::Path;
+use time::format_description::well_known::{Iso8601, Rfc3339};
+use time::OffsetDateTime;
+
+/// Where a source note came from, e.g. `origin: { url: …, via: … }`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Origin {
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub via: String,
+}
+
+/// The fields of a front matter block, in file order, and the YAML they
+/// came from.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrontMatter {
+    yaml: String,
+    fields: Mapping,
+}
+
+impl FrontMatter {
+    /// Parse the YAML between the `---` delimiters; it must be a mapping
+    /// (or empty).
+    pub fn parse(yaml: &str) -> Result<Self, A4Error> {
+        let fields = match serde_yaml::from_str(yaml)
+            .map_err(|e| A4Error::FrontMatterParse(e.to_string()))?
+        {
+            Value::Mapping(fields) => fields,
+            Value::Null => Mapping::new(),
+            _ => {
+                return Err(A4Error::FrontMatterParse(
+                    "front matter must be a mapping of fields".to_string(),
+                ))
+            }
+        };
+        Ok(FrontMatter {
+            yaml: yaml.to_string(),
+            fields,
+        })
+    }
+
+    /// The front matter at the top of `raw`, the text of a whole note,
+    /// empty if it has none.
+    pub fn from_text(raw: &str) -> Result<Self, A4Error> {
+        match yaml_range(raw) {
+            Some(range) => Self::parse(&raw[range]),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// The front matter of `note`, empty if it has none.
+    pub fn from_note(note: &Note) -> Result<Self, A4Error> {
+        Self::from_text(note.front_matter.as_deref().unwrap_or_default())
+            .map_err(|e| at(&note.path, e))
+    }
+
+    /// The fields as `T`, e.g. a struct of the fields a hub note uses.
+    pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T, A4Error> {
+        serde_yaml::from_value(Value::Mapping(self.fields.clone()))
+            .map_err(|e| A4Error::FrontMatterParse(e.to_string()))
+    }
+
+    /// `raw`, the text of a whole note, with its front matter replaced by
+    /// this one, or this one added if it has none.
+    pub fn apply_to(&self, raw: &str) -> String {
+        match yaml_range(raw) {
+            Some(range) => format!("{}{}{}", &raw[..range.start], self.yaml, &raw[range.end..]),
+            None => format!("---\n{}---\n{raw}", self.yaml),
+        }
+    }
+
+    /// The YAML text, ending in a newline unless empty.
+    pub fn yaml(&self) -> &str {
+        &self.yaml
+    }
+
+    pub fn fields(&self) -> &Mapping {
+        &self.fields
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.fields.get(key)
+    }
+
+    /// A field that is a YAML string.
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key)?.as_str()
+    }
+
+    /// The document type, e.g. `capture.day` or `hub.project`.
+    pub fn kind(&self) -> Option<&str> {
+        self.get_str("kind")
+    }
+
+    /// `created:` as a timestamp; a bare date means midnight UTC.
+    pub fn created(&self) -> Option<OffsetDateTime> {
+        parse_timestamp(self.get("created")?)
+    }
+
+    /// `updated:` as a timestamp; a bare date means midnight UTC.
+    pub fn updated(&self) -> Option<OffsetDateTime> {
+        parse_timestamp(self.get("updated")?)
+    }
+
+    /// `aliases:` as a list; a single string counts as one alias.
+    pub fn aliases(&self) -> Vec<String> {
+        match self.get("aliases") {
+            Some(Value::String(alias)) => vec![alias.clone()],
+            Some(Value::Sequence(aliases)) => aliases
+                .iter()
+                .filter_map(|alias| alias.as_str().map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn origin(&self) -> Option<Origin> {
+        serde_yaml::from_value(self.get("origin")?.clone()).ok()
+    }
+
+    /// Set `key` to `value`, replacing the lines of an existing top-level
+    /// field or appending a new one, and leaving every other line as it was.
+    pub fn set(&mut self, key: &str, value: Value) -> Result<(), A4Error> {
+        let mut entry = Mapping::new();
+        entry.insert(Value::String(key.to_string()), value.clone());
+        let entry = serde_yaml::to_string(&entry)
+            .map_err(|e| A4Error::FrontMatterParse(format!("{key}: {e}")))?;
+
+        let lines: Vec<&str> = self.yaml.split_inclusive('\n').collect();
+        let yaml = match field_lines(&lines, key) {
+            Some(range) => format!(
+                "{}{entry}{}",
+                lines[..range.start].concat(),
+                lines[range.end..].concat()
+            ),
+            None if self.yaml.is_empty() || self.yaml.ends_with('\n') => {
+                format!("{}{entry}", self.yaml)
+            }
+            None => format!("{}\n{entry}", self.yaml),
+        };
+
+        let updated = Self::parse(&yaml)?;
+        if updated.get(key) != Some(&value) {
+            return Err(A4Error::FrontMatterParse(format!(
+                "`{key}` is not a plain top-level field, so it cannot be set in place"
+            )));
+        }
+        *self = updated;
+        Ok(())
+    }
+
+    pub fn set_kind(&mut self, kind: &str) -> Result<(), A4Error> {
+        self.set("kind", Value::String(kind.to_string()))
+    }
+
+    /// Set `updated:` to `instant` in UTC, to the second.
+    pub fn set_updated(&mut self, instant: OffsetDateTime) -> Result<(), A4Error> {
+        let instant = instant
+            .to_offset(time::UtcOffset::UTC)
+            .replace_nanosecond(0)
+            .expect("zero is a valid nanosecond")
+            .format(&Rfc3339)
+            .expect("UTC instants format as RFC 3339");
+        self.set("updated", Value::String(instant))
+    }
+
+    pub fn set_aliases(&mut self, aliases: &[String]) -> Result<(), A4Error> {
+        let aliases = aliases.iter().cloned().map(Value::String).collect();
+        self.set("aliases", Value::Sequence(aliases))
+    }
+
+    pub fn set_origin(&mut self, origin: &Origin) -> Result<(), A4Error> {
+        let origin = serde_yaml::to_value(origin).expect("origins serialize");
+        self.set("origin", origin)
+    }
+
+    /// Replace the YAML with `edit`'s rewrite of it, for changes inside a
+    /// field that [`FrontMatter::set`] would reformat as a whole. The result
+    /// must still be a mapping.
+    pub fn edit<F>(&mut self, edit: F) -> Result<(), A4Error>
+    where
+        F: FnOnce(&str) -> Result<String, A4Error>,
+    {
+        *self = Self::parse(&edit(&self.yaml)?)?;
+        Ok(())
+    }
+}
+
+/// The front matter of the note at `path`, empty if it has none.
+pub fn read_front_matter(path: &Path) -> Result<FrontMatter, A4Error> {
+    FrontMatter::from_note(&read_note(path)?)
+}
+
+/// Set one field of the note at `path`, adding front matter if it has none.
+/// The rest of the file is written back unchanged.
+pub fn set_front_matter_field(
+    path: &Path,
+    key: &str,
+    value: Value,
+) -> Result<FrontMatter, A4Error> {
+    let raw = fs::read_to_string(path)?;
+    let mut front_matter = FrontMatter::from_text(&raw).map_err(|e| at(path, e))?;
+    front_matter.set(key, value).map_err(|e| at(path, e))?;
+    write_note(path, &front_matter.apply_to(&raw))?;
+    Ok(front_matter)
+}
+
+/// A field value typed on the command line: YAML, so `[a, b]` is a list and
+/// `42` a number, or the text itself when it is not valid YAML.
+pub fn parse_value(text: &str) -> Value {
+    serde_yaml::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+}
+
+/// A field value for display: strings as they are, anything else as YAML.
+pub fn render_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => serde_yaml::to_string(other)
+            .map(|s| s.trim_end().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// Byte range of the YAML between the front matter delimiters, ending after
+/// its last newline.
+pub(crate) fn yaml_range(raw: &str) -> Option<Range<usize>> {
+    let front_matter = split_front_matter(raw).0?;
+    let start = front_matter.find('\n')? + 1;
+    Some(start..front_matter.len() - "---".len())
+}
+
+/// A `created:`/`updated:` value as a date or a timestamp.
+pub(crate) fn parse_timestamp(value: &Value) -> Option<OffsetDateTime> {
+    let value = value.as_str()?;
+    if let Ok(instant) = OffsetDateTime::parse(value, &Rfc3339) {
+        return Some(instant);
+    }
+    if let Ok(instant) = OffsetDateTime::parse(value, &Iso8601::DEFAULT) {
+        return Some(instant);
+    }
+    let date = UtcDay::parse(value).ok()?.to_date().ok()?;
+    Some(date.midnight().assume_utc())
+}
+
+/// `error` with `path` in front of a front matter message.
+pub(crate) fn at(path: &Path, error: A4Error) -> A4Error {
+    match error {
+        A4Error::FrontMatterParse(message) => {
+            A4Error::FrontMatterParse(format!("{}: {message}", path.display()))
+        }
+        other => other,
+    }
+}
+
+/// The lines of the top-level field `key`: its key line and any indented or
+/// column-0 list lines after it, without trailing blank lines.
+fn field_lines(lines: &[&str], key: &str) -> Option<Range<usize>> {
+    let start = lines
+        .iter()
+        .rposition(|line| top_level_key(line).as_deref() == Some(key))?;
+
+    let mut end = start + 1;
+    while end < lines.len()
+        && (lines[end].starts_with([' ', '\t', '-']) || lines[end].trim().is_empty())
+    {
+        end += 1;
+    }
+    while end > start + 1 && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+    Some(start..end)
+}
+
+/// The key of a `key: value` line at column 0, unquoted.
+fn top_level_key(line: &str) -> Option<String> {
+    if line.starts_with([' ', '\t', '-', '#']) {
+        return None;
+    }
+    let line = line.trim_end();
+    let (key, _) = line
+        .char_indices()
+        .filter(|&(i, c)| c == ':' && line[i + 1..].chars().next().is_none_or(char::is_whitespace))
+        .map(|(i, _)| line.split_at(i))
+        .next()?;
+    match key.chars().next()? {
+        '"' | '\'' => serde_yaml::from_str(key).ok(),
+        _ => Some(key.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const NOTE: &str = "---\n\
+        # written by hand\n\
+        kind: source.article\n\
+        created: 2025-09-14T07:58:12Z\n\
+        aliases:\n- State of GPT\n- sog\n\
+        origin: { url: \"https://example.com/gpt\", via: a4 }\n\
+        x-reader:   {progress: 0.4}   # unknown to a4\n\
+        ---\n# Body\n";
+
+    #[test]
+    fn test_typed_accessors() {
+        let front_matter = FrontMatter::from_text(NOTE).unwrap();
+        assert_eq!(front_matter.kind(), Some("source.article"));
+        assert_eq!(
+            front_matter.created().unwrap(),
+            OffsetDateTime::parse("2025-09-14T07:58:12Z", &Rfc3339).unwrap()
+        );
+        assert_eq!(front_matter.updated(), None);
+        assert_eq!(front_matter.aliases(), vec!["State of GPT", "sog"]);
+        assert_eq!(
+            front_matter.origin(),
+            Some(Origin {
+                url: "https://example.com/gpt".to_string(),
+                via: "a4".to_string(),
+            })
+        );
+        let keys: Vec<_> = front_matter
+            .fields()
+            .keys()
+            .filter_map(Value::as_str)
+            .collect();
+        assert_eq!(keys, ["kind", "created", "aliases", "origin", "x-reader"]);
+
+        let dated = FrontMatter::parse("updated: 2026-01-05\n").unwrap();
+        assert_eq!(
+            dated.updated().unwrap().to_string(),
+            "2026-01-05 0:00:00.0 +00:00:00"
+        );
+    }
+
+    #[test]
+    fn test_set_keeps_other_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("note.md");
+        std::fs::write(&path, NOTE).unwrap();
+
+        set_front_matter_field(&path, "aliases", parse_value("[sog]")).unwrap();
+        set_front_matter_field(&path, "kind", parse_value("memo.research")).unwrap();
+        let front_matter = set_front_matter_field(&path, "status", parse_value("draft")).unwrap();
+        assert_eq!(front_matter.aliases(), vec!["sog"]);
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "---\n\
+            # written by hand\n\
+            kind: memo.research\n\
+            created: 2025-09-14T07:58:12Z\n\
+            aliases:\n- sog\n\
+            origin: { url: \"https://example.com/gpt\", via: a4 }\n\
+            x-reader:   {progress: 0.4}   # unknown to a4\n\
+            status: draft\n\
+            ---\n# Body\n"
+        );
+        assert_eq!(read_front_matter(&path).unwrap(), front_matter);
+    }
+
+    #[test]
+    fn test_set_adds_front_matter() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("note.md");
+        std::fs::write(&path, "# Plain\n").unwrap();
+        assert!(read_front_matter(&path).unwrap().is_empty());
+
+        let mut origin = FrontMatter::default();
+        origin
+            .set_origin(&Origin {
+                url: "https://example.com".to_string(),
+                via: String::new(),
+            })
+            .unwrap();
+        set_front_matter_field(&path, "origin", origin.get("origin").unwrap().clone()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "---\norigin:\n  url: https://example.com\n  via: ''\n---\n# Plain\n"
+        );
+    }
+
+    #[test]
+    fn test_edit_and_deserialize() {
+        #[derive(Deserialize)]
+        struct Hub {
+            kind: String,
+            tags: Vec<String>,
+        }
+
+        let mut front_matter = FrontMatter::from_text(NOTE).unwrap();
+        front_matter
+            .edit(|yaml| Ok(format!("{yaml}tags:\n  - gpt # first\n")))
+            .unwrap();
+        let hub: Hub = front_matter.deserialize().unwrap();
+        assert_eq!(
+            (hub.kind.as_str(), hub.tags),
+            ("source.article", vec!["gpt".to_string()])
+        );
+
+        let updated = front_matter.apply_to(NOTE);
+        assert!(updated.starts_with("---\n# written by hand\n"));
+        assert!(updated.ends_with("tags:\n  - gpt # first\n---\n# Body\n"));
+        assert!(front_matter.edit(|_| Ok("- a list\n".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("note.md");
+        std::fs::write(&path, "---\nkind: [unclosed\n---\n").unwrap();
+        assert!(matches!(
+            read_front_matter(&path),
+            Err(A4Error::FrontMatterParse(message)) if message.starts_with(&path.display().to_string())
+        ));
+        assert!(matches!(
+            FrontMatter::parse("- a list\n"),
+            Err(A4Error::FrontMatterParse(_))
+        ));
+    }
+}
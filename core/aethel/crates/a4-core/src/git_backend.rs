@@ -1,21 +1,65 @@
 use crate::error::A4Error;
-use std::path::Path;
+use crate::merge::union_merge;
+use gix::bstr::{BString, ByteSlice};
+use gix::index::entry::{Flags, Mode, Stat};
+use gix::objs::tree::{EntryKind, EntryMode};
+use gix::objs::WriteTo;
+use gix::refs::transaction::PreviousValue;
+use gix::remote::Direction;
+use gix::ObjectId;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
 
 pub trait GitBackend {
     fn open(cwd: &Path) -> Result<Self, A4Error>
     where
         Self: Sized;
+    /// Open the repository rooted at `path`, creating it if there is none.
+    fn init(path: &Path) -> Result<Self, A4Error>
+    where
+        Self: Sized;
+    /// Add remote `name` pointing at `url`. Returns `false` if it already
+    /// exists with that URL.
+    fn ensure_remote(&mut self, name: &str, url: &str) -> Result<bool, A4Error>;
     fn stage_all(&mut self) -> Result<(), A4Error>;
     fn commit_if_needed(&mut self, message: &str) -> Result<bool, A4Error>;
+    /// Fetch `branch` from `remote` into `refs/remotes/<remote>/<branch>`, or
+    /// every branch the remote's refspecs cover when `None`.
     fn fetch(&mut self, remote: &str, branch: Option<&str>) -> Result<(), A4Error>;
-    fn fast_forward_current_branch(&mut self, remote_ref: &str) -> Result<bool, A4Error>;
+    fn fast_forward_current_branch(&mut self, remote_ref: &str) -> Result<FastForward, A4Error>;
     fn rebase_onto(&mut self, remote_ref: &str) -> Result<RebaseResult, A4Error>;
+    /// Merge `remote_ref` into HEAD, unioning notes both sides appended to
+    /// (see [`union_merge`]). Returns `false`, changing nothing, when the
+    /// sides conflict otherwise.
+    fn merge_append_only(
+        &mut self,
+        remote_ref: &str,
+        message: &str,
+        rollover_hour: u8,
+    ) -> Result<bool, A4Error>;
     fn push(&mut self, remote: &str, branch: Option<&str>, force: bool) -> Result<(), A4Error>;
     fn head_branch(&self) -> Result<String, A4Error>;
     fn diverged(&self, remote_ref: &str) -> Result<bool, A4Error>;
+    /// Commits on HEAD missing from `remote_ref`, and the reverse.
+    fn ahead_behind(&self, remote_ref: &str) -> Result<(usize, usize), A4Error>;
     fn has_uncommitted_changes(&self) -> Result<bool, A4Error>;
 }
 
+/// What [`GitBackend::fast_forward_current_branch`] found; only
+/// `FastForwarded` moved the branch.
+#[derive(Debug, PartialEq)]
+pub enum FastForward {
+    /// The remote branch does not exist yet.
+    NoRemote,
+    UpToDate,
+    /// Local has commits the remote lacks and nothing to apply.
+    Ahead,
+    FastForwarded,
+    /// Both sides have commits the other lacks.
+    Diverged,
+}
+
 #[derive(Debug, PartialEq)]
 pub enum RebaseResult {
     Success,
@@ -27,6 +71,17 @@ pub struct GixBackend {
     repo: gix::Repository,
 }
 
+/// A flattened tree: repo-relative path to mode and blob/commit id.
+type FileMap = BTreeMap<BString, (EntryMode, ObjectId)>;
+
+/// Repo-relative paths with their mode and id, in tree order, as
+/// `build_tree` takes them.
+type FileList = Vec<(BString, EntryMode, ObjectId)>;
+
+fn git_err<E: std::fmt::Display>(context: &'static str) -> impl FnOnce(E) -> A4Error {
+    move |e| A4Error::Git(format!("{context}: {e}"))
+}
+
 impl GitBackend for GixBackend {
     fn open(cwd: &Path) -> Result<Self, A4Error> {
         let repo = gix::discover(cwd).map_err(|_| A4Error::GitRepoNotFound {
@@ -36,223 +91,1269 @@ impl GitBackend for GixBackend {
         Ok(GixBackend { repo })
     }
 
-    fn stage_all(&mut self) -> Result<(), A4Error> {
-        let workdir = self
+    fn init(path: &Path) -> Result<Self, A4Error> {
+        let repo = if path.join(".git").exists() {
+            gix::open(path).map_err(git_err("Failed to open repository"))?
+        } else {
+            gix::init(path).map_err(git_err("Failed to initialize repository"))?
+        };
+
+        Ok(GixBackend { repo })
+    }
+
+    fn ensure_remote(&mut self, name: &str, url: &str) -> Result<bool, A4Error> {
+        if let Ok(existing) = self.repo.find_remote(name) {
+            let existing_url = existing
+                .url(Direction::Fetch)
+                .map(|u| u.to_bstring().to_string());
+            if existing_url.as_deref() == Some(url) {
+                return Ok(false);
+            }
+            return Err(A4Error::Git(format!(
+                "Remote {name} already exists with URL {}",
+                existing_url.unwrap_or_default()
+            )));
+        }
+
+        let config_path = self.repo.git_dir().join("config");
+        let mut config = gix::config::File::from_path_no_includes(
+            config_path.clone(),
+            gix::config::Source::Local,
+        )
+        .map_err(git_err("Failed to read repository config"))?;
+
+        self.repo
+            .remote_at(url)
+            .map_err(git_err("Invalid remote URL"))?
+            .save_as_to(name, &mut config)
+            .map_err(git_err("Failed to add remote"))?;
+
+        let mut file = std::fs::File::create(&config_path)?;
+        config.write_to(&mut file)?;
+
+        // Pick up the new remote
+        let path = self
             .repo
             .work_dir()
-            .ok_or_else(|| A4Error::Git("No working directory".to_string()))?;
+            .unwrap_or(self.repo.git_dir())
+            .to_path_buf();
+        self.repo = gix::open(path).map_err(git_err("Failed to reopen repository"))?;
 
-        // Use git command for staging - gix index API requires more setup
-        std::process::Command::new("git")
-            .arg("add")
-            .arg("-A")
-            .current_dir(workdir)
-            .output()
-            .map_err(|e| A4Error::Git(format!("Failed to stage files: {e}")))?;
+        Ok(true)
+    }
+
+    fn stage_all(&mut self) -> Result<(), A4Error> {
+        let state = self.snapshot_worktree(true)?;
+        let mut index = gix::index::File::from_state(state, self.repo.index_path());
+        index
+            .write(Default::default())
+            .map_err(git_err("Failed to write index"))?;
 
         Ok(())
     }
 
     fn commit_if_needed(&mut self, message: &str) -> Result<bool, A4Error> {
-        // Check if there are changes to commit
-        let status = std::process::Command::new("git")
-            .arg("status")
-            .arg("--porcelain")
-            .current_dir(self.repo.work_dir().unwrap())
-            .output()
-            .map_err(|e| A4Error::Git(format!("Failed to check status: {e}")))?;
-
-        if status.stdout.is_empty() {
+        let index = self.load_index()?;
+        let tree = self.build_index_tree(&index, true)?;
+
+        let parent = self.head_id()?;
+        if tree == self.commit_tree(parent)? {
             return Ok(false);
         }
 
-        // Create commit
-        std::process::Command::new("git")
-            .arg("commit")
-            .arg("-m")
-            .arg(message)
-            .current_dir(self.repo.work_dir().unwrap())
-            .output()
-            .map_err(|e| A4Error::Git(format!("Failed to commit: {e}")))?;
+        self.repo
+            .commit("HEAD", message, tree, parent)
+            .map_err(git_err("Failed to commit"))?;
 
         Ok(true)
     }
 
-    fn fetch(&mut self, remote: &str, _branch: Option<&str>) -> Result<(), A4Error> {
-        std::process::Command::new("git")
-            .arg("fetch")
-            .arg(remote)
-            .current_dir(self.repo.work_dir().unwrap())
-            .output()
-            .map_err(|e| A4Error::Git(format!("Failed to fetch: {e}")))?;
+    fn fetch(&mut self, remote: &str, branch: Option<&str>) -> Result<(), A4Error> {
+        let mut found = self
+            .repo
+            .find_remote(remote)
+            .map_err(|_| A4Error::NoRemote)?;
+
+        // gix fetches from local paths by spawning `git-upload-pack`; copy
+        // the objects directly so local remotes need no git binary
+        if let Some(path) = found.url(Direction::Fetch).and_then(|u| self.local_path(u)) {
+            return self.fetch_local(remote, &path, branch);
+        }
+
+        if let Some(branch) = branch {
+            let refspec = format!("+refs/heads/{branch}:refs/remotes/{remote}/{branch}");
+            found
+                .replace_refspecs([refspec.as_str()], Direction::Fetch)
+                .map_err(git_err("Invalid branch name"))?;
+        }
+
+        found
+            .connect(Direction::Fetch)
+            .map_err(git_err("Failed to connect to remote"))?
+            .prepare_fetch(gix::progress::Discard, Default::default())
+            .map_err(git_err("Failed to fetch"))?
+            .receive(gix::progress::Discard, &AtomicBool::new(false))
+            .map_err(git_err("Failed to fetch"))?;
 
         Ok(())
     }
 
-    fn fast_forward_current_branch(&mut self, remote_ref: &str) -> Result<bool, A4Error> {
-        // Try to merge with fast-forward only
-        let output = std::process::Command::new("git")
-            .arg("merge")
-            .arg("--ff-only")
-            .arg(remote_ref)
-            .current_dir(self.repo.work_dir().unwrap())
-            .output()
-            .map_err(|e| A4Error::Git(format!("Failed to merge: {e}")))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            if stderr.contains("Not possible to fast-forward") || stderr.contains("diverged") {
-                // Don't error here, just return false to indicate fast-forward wasn't possible
-                return Ok(false);
+    fn fast_forward_current_branch(&mut self, remote_ref: &str) -> Result<FastForward, A4Error> {
+        let Some(remote_id) = self.resolve(remote_ref) else {
+            return Ok(FastForward::NoRemote);
+        };
+        let head_id = self.head_id()?;
+
+        if let Some(head_id) = head_id {
+            if head_id == remote_id {
+                return Ok(FastForward::UpToDate);
+            }
+            match self.merge_base(head_id, remote_id)? {
+                Some(base) if base == remote_id => return Ok(FastForward::Ahead),
+                Some(base) if base == head_id => {}
+                _ => return Ok(FastForward::Diverged),
             }
-            return Err(A4Error::Git(format!("Fast-forward failed: {stderr}")));
         }
 
-        Ok(true)
+        let head_name = self
+            .repo
+            .head_name()
+            .map_err(git_err("Failed to read HEAD"))?
+            .ok_or_else(|| A4Error::Git("Cannot fast-forward a detached HEAD".to_string()))?;
+
+        let from_tree = self.commit_tree(head_id)?;
+        let to_tree = self.commit_tree(Some(remote_id))?;
+        self.checkout_tree(from_tree, to_tree)?;
+
+        let previous = match head_id {
+            Some(id) => PreviousValue::MustExistAndMatch(gix::refs::Target::Object(id)),
+            None => PreviousValue::MustNotExist,
+        };
+        self.repo
+            .reference(
+                head_name,
+                remote_id,
+                previous,
+                format!("a4: fast-forward to {remote_ref}"),
+            )
+            .map_err(git_err("Failed to update branch"))?;
+
+        Ok(FastForward::FastForwarded)
     }
 
     fn rebase_onto(&mut self, remote_ref: &str) -> Result<RebaseResult, A4Error> {
-        // Check if we're already up to date
-        let merge_base = std::process::Command::new("git")
-            .arg("merge-base")
-            .arg("HEAD")
-            .arg(remote_ref)
-            .current_dir(self.repo.work_dir().unwrap())
-            .output()
-            .map_err(|e| A4Error::Git(format!("Failed to find merge base: {e}")))?;
-
-        let merge_base_sha = String::from_utf8_lossy(&merge_base.stdout)
-            .trim()
-            .to_string();
-
-        // Check if HEAD is the same as remote_ref
-        let head_sha = std::process::Command::new("git")
-            .arg("rev-parse")
-            .arg("HEAD")
-            .current_dir(self.repo.work_dir().unwrap())
-            .output()
-            .map_err(|e| A4Error::Git(format!("Failed to get HEAD SHA: {e}")))?;
-
-        let remote_sha = std::process::Command::new("git")
-            .arg("rev-parse")
-            .arg(remote_ref)
-            .current_dir(self.repo.work_dir().unwrap())
-            .output()
-            .map_err(|e| A4Error::Git(format!("Failed to get remote SHA: {e}")))?;
-
-        let head_sha_str = String::from_utf8_lossy(&head_sha.stdout).trim().to_string();
-        let remote_sha_str = String::from_utf8_lossy(&remote_sha.stdout)
-            .trim()
-            .to_string();
-
-        // If HEAD is already at remote_ref, no rebase needed
-        if head_sha_str == remote_sha_str {
-            return Ok(RebaseResult::NoRebaseNeeded);
-        }
+        let remote_id = self
+            .resolve(remote_ref)
+            .ok_or_else(|| A4Error::Git(format!("Unknown revision {remote_ref}")))?;
+        let head_id = self
+            .head_id()?
+            .ok_or_else(|| A4Error::Git("Cannot rebase a branch with no commits".to_string()))?;
 
-        // If merge_base is the same as remote_ref, we're ahead - no rebase needed
-        if merge_base_sha == remote_sha_str {
+        // Already at the remote, or ahead of it
+        let base = self.merge_base(head_id, remote_id)?;
+        if head_id == remote_id || base == Some(remote_id) {
             return Ok(RebaseResult::NoRebaseNeeded);
         }
 
-        // Perform the rebase
-        let output = std::process::Command::new("git")
-            .arg("rebase")
-            .arg(remote_ref)
-            .current_dir(self.repo.work_dir().unwrap())
-            .output()
-            .map_err(|e| A4Error::Git(format!("Failed to rebase: {e}")))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            if stderr.contains("CONFLICT") || stderr.contains("conflict") {
-                // Abort the rebase to leave repository in clean state
-                std::process::Command::new("git")
-                    .arg("rebase")
-                    .arg("--abort")
-                    .current_dir(self.repo.work_dir().unwrap())
-                    .output()
-                    .map_err(|e| A4Error::Git(format!("Failed to abort rebase: {e}")))?;
+        // Local commits missing from the remote, oldest first
+        let mut local = Vec::new();
+        let mut next = Some(head_id);
+        while let Some(id) = next.filter(|id| Some(*id) != base) {
+            next = self
+                .find_commit(id)?
+                .parent_ids()
+                .next()
+                .map(|p| p.detach());
+            local.push(id);
+        }
+        local.reverse();
 
+        // Replay them in memory; the worktree and branch only move once every
+        // commit has applied
+        let mut onto = remote_id;
+        let mut onto_tree = self.commit_tree(Some(remote_id))?;
+        for id in local {
+            let commit = self.find_commit(id)?;
+            let parent = commit.parent_ids().next().map(|p| p.detach());
+            let Some(entries) = self.merge_trees(
+                self.commit_tree(parent)?,
+                onto_tree,
+                self.commit_tree(Some(id))?,
+                None,
+            )?
+            else {
                 return Ok(RebaseResult::Conflict);
+            };
+            onto_tree = self.build_tree(&entries, true)?;
+
+            let mut replayed = gix::objs::Commit::from(
+                commit
+                    .decode()
+                    .map_err(git_err("Failed to decode commit"))?,
+            );
+            replayed.tree = onto_tree;
+            replayed.parents = [onto].into_iter().collect();
+            if let Some(committer) = self.repo.committer() {
+                replayed.committer = committer
+                    .map_err(git_err("Invalid committer time"))?
+                    .to_owned();
             }
-            return Err(A4Error::Git(format!("Rebase failed: {stderr}")));
+            onto = self
+                .repo
+                .write_object(&replayed)
+                .map_err(git_err("Failed to write commit"))?
+                .detach();
         }
 
+        let head_name = self
+            .repo
+            .head_name()
+            .map_err(git_err("Failed to read HEAD"))?
+            .ok_or_else(|| A4Error::Git("Cannot rebase a detached HEAD".to_string()))?;
+        self.checkout_tree(self.commit_tree(Some(head_id))?, onto_tree)?;
+        self.repo
+            .reference(
+                head_name,
+                onto,
+                PreviousValue::MustExistAndMatch(gix::refs::Target::Object(head_id)),
+                format!("a4: rebase onto {remote_ref}"),
+            )
+            .map_err(git_err("Failed to update branch"))?;
+
         Ok(RebaseResult::Success)
     }
 
-    fn push(&mut self, remote: &str, branch: Option<&str>, force: bool) -> Result<(), A4Error> {
-        let mut cmd = std::process::Command::new("git");
-        cmd.arg("push");
+    fn merge_append_only(
+        &mut self,
+        remote_ref: &str,
+        message: &str,
+        rollover_hour: u8,
+    ) -> Result<bool, A4Error> {
+        let (Some(head_id), Some(remote_id)) = (self.head_id()?, self.resolve(remote_ref)) else {
+            return Ok(false);
+        };
+        let Some(base_id) = self.merge_base(head_id, remote_id)? else {
+            return Ok(false);
+        };
 
-        if force {
-            cmd.arg("--force-with-lease");
-        }
+        let head_tree = self.commit_tree(Some(head_id))?;
+        let Some(merged) = self.merge_trees(
+            self.commit_tree(Some(base_id))?,
+            head_tree,
+            self.commit_tree(Some(remote_id))?,
+            Some(rollover_hour),
+        )?
+        else {
+            return Ok(false);
+        };
 
-        cmd.arg(remote);
+        let tree = self.build_tree(&merged, true)?;
+        self.checkout_tree(head_tree, tree)?;
+        self.repo
+            .commit("HEAD", message, tree, [head_id, remote_id])
+            .map_err(git_err("Failed to commit merge"))?;
 
-        if let Some(branch) = branch {
-            cmd.arg(branch);
-        }
+        Ok(true)
+    }
+
+    fn push(&mut self, remote: &str, branch: Option<&str>, force: bool) -> Result<(), A4Error> {
+        let branch = match branch {
+            Some(branch) => branch.to_string(),
+            None => self.head_branch()?,
+        };
 
-        let output = cmd
-            .current_dir(self.repo.work_dir().unwrap())
-            .output()
-            .map_err(|e| A4Error::Git(format!("Failed to push: {e}")))?;
+        let url = self
+            .repo
+            .find_remote(remote)
+            .map_err(|_| A4Error::NoRemote)?
+            .url(Direction::Push)
+            .cloned()
+            .ok_or(A4Error::NoRemote)?;
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(A4Error::Git(format!("Push failed: {stderr}")));
+        if let Some(path) = self.local_path(&url) {
+            return self.push_local(remote, &path, &branch, force);
         }
 
-        Ok(())
+        self.push_transport(remote, &url, &branch, force)
     }
 
     fn head_branch(&self) -> Result<String, A4Error> {
-        let output = std::process::Command::new("git")
-            .arg("branch")
-            .arg("--show-current")
-            .current_dir(self.repo.work_dir().unwrap())
-            .output()
-            .map_err(|e| A4Error::Git(format!("Failed to get current branch: {e}")))?;
+        let name = self
+            .repo
+            .head_name()
+            .map_err(git_err("Failed to get current branch"))?
+            .ok_or_else(|| A4Error::Git("HEAD is detached".to_string()))?;
 
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        Ok(name.shorten().to_string())
     }
 
     fn diverged(&self, remote_ref: &str) -> Result<bool, A4Error> {
-        // Check if HEAD is an ancestor of remote_ref
-        let head_ancestor = std::process::Command::new("git")
-            .arg("merge-base")
-            .arg("--is-ancestor")
-            .arg("HEAD")
-            .arg(remote_ref)
-            .current_dir(self.repo.work_dir().unwrap())
-            .output()
-            .map_err(|e| A4Error::Git(format!("Failed to check if HEAD is ancestor: {e}")))?;
-
-        // Check if remote_ref is an ancestor of HEAD
-        let remote_ancestor = std::process::Command::new("git")
-            .arg("merge-base")
-            .arg("--is-ancestor")
-            .arg(remote_ref)
-            .arg("HEAD")
-            .current_dir(self.repo.work_dir().unwrap())
-            .output()
-            .map_err(|e| A4Error::Git(format!("Failed to check if remote is ancestor: {e}")))?;
+        let (Some(head_id), Some(remote_id)) = (self.head_id()?, self.resolve(remote_ref)) else {
+            return Ok(false);
+        };
 
         // We have diverged if neither is an ancestor of the other
-        Ok(!head_ancestor.status.success() && !remote_ancestor.status.success())
+        Ok(match self.merge_base(head_id, remote_id)? {
+            Some(base) => base != head_id && base != remote_id,
+            None => true,
+        })
+    }
+
+    fn ahead_behind(&self, remote_ref: &str) -> Result<(usize, usize), A4Error> {
+        let local = match self.head_id()? {
+            Some(id) => self.ancestors(id)?,
+            None => HashSet::new(),
+        };
+        let remote = match self.resolve(remote_ref) {
+            Some(id) => self.ancestors(id)?,
+            None => HashSet::new(),
+        };
+
+        Ok((
+            local.difference(&remote).count(),
+            remote.difference(&local).count(),
+        ))
     }
 
     fn has_uncommitted_changes(&self) -> Result<bool, A4Error> {
-        let output = std::process::Command::new("git")
-            .arg("status")
-            .arg("--porcelain")
-            .current_dir(self.repo.work_dir().unwrap())
-            .output()
-            .map_err(|e| A4Error::Git(format!("Failed to check status: {e}")))?;
-
-        Ok(!output.stdout.is_empty())
+        let state = self.snapshot_worktree(false)?;
+        let tree = self.build_index_tree(&state, false)?;
+
+        Ok(tree != self.commit_tree(self.head_id()?)?)
+    }
+}
+
+impl GixBackend {
+    fn workdir(&self) -> Result<&Path, A4Error> {
+        self.repo
+            .work_dir()
+            .ok_or_else(|| A4Error::Git("No working directory".to_string()))
+    }
+
+    /// The absolute path of a remote on the local filesystem.
+    fn local_path(&self, url: &gix::Url) -> Option<PathBuf> {
+        if url.scheme != gix::url::Scheme::File {
+            return None;
+        }
+        let path = gix::path::from_bstr(url.path.as_bstr()).into_owned();
+        if path.is_relative() {
+            Some(
+                self.repo
+                    .work_dir()
+                    .unwrap_or(self.repo.git_dir())
+                    .join(path),
+            )
+        } else {
+            Some(path)
+        }
+    }
+
+    fn head_id(&self) -> Result<Option<ObjectId>, A4Error> {
+        let head = self.repo.head().map_err(git_err("Failed to read HEAD"))?;
+        Ok(head.id().map(|id| id.detach()))
+    }
+
+    fn resolve(&self, spec: &str) -> Option<ObjectId> {
+        self.repo.rev_parse_single(spec).ok().map(|id| id.detach())
+    }
+
+    fn merge_base(&self, one: ObjectId, two: ObjectId) -> Result<Option<ObjectId>, A4Error> {
+        match self.repo.merge_base(one, two) {
+            Ok(base) => Ok(Some(base.detach())),
+            Err(gix::repository::merge_base::Error::NotFound { .. }) => Ok(None),
+            Err(e) => Err(A4Error::Git(format!("Failed to find merge base: {e}"))),
+        }
+    }
+
+    fn find_commit(&self, id: ObjectId) -> Result<gix::Commit<'_>, A4Error> {
+        self.repo
+            .find_commit(id)
+            .map_err(git_err("Failed to find commit"))
+    }
+
+    /// Three-way merge of the files in `ours` and `theirs` since `base`, in
+    /// tree order. A path changed on one side takes that side's version; one
+    /// changed differently on both sides is a conflict (`None`) unless
+    /// `union_notes` carries the day rollover hour and [`union_merge`] can
+    /// combine the Markdown note.
+    fn merge_trees(
+        &self,
+        base: ObjectId,
+        ours: ObjectId,
+        theirs: ObjectId,
+        union_notes: Option<u8>,
+    ) -> Result<Option<FileList>, A4Error> {
+        let base = self.tree_files(base)?;
+        let ours = self.tree_files(ours)?;
+        let theirs = self.tree_files(theirs)?;
+
+        let paths: std::collections::BTreeSet<&BString> =
+            ours.keys().chain(theirs.keys()).collect();
+        let mut merged = Vec::new();
+
+        for path in paths {
+            let (b, o, t) = (base.get(path), ours.get(path), theirs.get(path));
+            let entry = if o == t || t == b {
+                o
+            } else if o == b {
+                t
+            } else {
+                let (Some(&(o_mode, o_id)), Some(&(_, t_id))) = (o, t) else {
+                    // Deleted on one side, changed on the other
+                    return Ok(None);
+                };
+                let Some(rollover_hour) = union_notes else {
+                    return Ok(None);
+                };
+                if !o_mode.is_blob() || !path.ends_with(b".md") {
+                    return Ok(None);
+                }
+
+                let base_text = match b {
+                    Some(&(_, id)) => Some(self.blob_text(id)?),
+                    None => None,
+                };
+                let Some(text) = union_merge(
+                    base_text.as_deref(),
+                    &self.blob_text(o_id)?,
+                    &self.blob_text(t_id)?,
+                    rollover_hour,
+                ) else {
+                    return Ok(None);
+                };
+
+                let id = self.hash_blob(text.as_bytes(), true)?;
+                merged.push((path.clone(), o_mode, id));
+                continue;
+            };
+
+            if let Some(&(mode, id)) = entry {
+                merged.push((path.clone(), mode, id));
+            }
+        }
+
+        Ok(Some(merged))
+    }
+
+    /// `tip` and every commit reachable from it.
+    fn ancestors(&self, tip: ObjectId) -> Result<HashSet<ObjectId>, A4Error> {
+        let walk = self
+            .repo
+            .rev_walk([tip])
+            .all()
+            .map_err(git_err("Failed to walk history"))?;
+        walk.map(|info| {
+            info.map(|info| info.id)
+                .map_err(git_err("Failed to walk history"))
+        })
+        .collect()
+    }
+
+    /// The tree of `commit`, or the empty tree for an unborn branch.
+    fn commit_tree(&self, commit: Option<ObjectId>) -> Result<ObjectId, A4Error> {
+        match commit {
+            Some(id) => Ok(self
+                .repo
+                .find_commit(id)
+                .map_err(git_err("Failed to find commit"))?
+                .tree_id()
+                .map_err(git_err("Failed to read commit tree"))?
+                .detach()),
+            None => Ok(ObjectId::empty_tree(self.repo.object_hash())),
+        }
+    }
+
+    fn load_index(&self) -> Result<gix::index::File, A4Error> {
+        if self.repo.index_path().exists() {
+            self.repo
+                .open_index()
+                .map_err(git_err("Failed to open index"))
+        } else {
+            Ok(gix::index::File::from_state(
+                gix::index::State::new(self.repo.object_hash()),
+                self.repo.index_path(),
+            ))
+        }
+    }
+
+    fn blob_text(&self, id: ObjectId) -> Result<String, A4Error> {
+        let blob = self
+            .repo
+            .find_object(id)
+            .map_err(git_err("Failed to find blob"))?;
+        String::from_utf8(blob.data.clone())
+            .map_err(|_| A4Error::Git(format!("Blob {id} is not valid UTF-8")))
+    }
+
+    fn hash_blob(&self, data: &[u8], write: bool) -> Result<ObjectId, A4Error> {
+        if write {
+            Ok(self
+                .repo
+                .write_blob(data)
+                .map_err(git_err("Failed to write blob"))?
+                .detach())
+        } else {
+            Ok(gix::objs::compute_hash(
+                self.repo.object_hash(),
+                gix::objs::Kind::Blob,
+                data,
+            ))
+        }
+    }
+
+    /// Build the index `git add -A` would produce: every tracked or
+    /// non-ignored file in the worktree, re-hashing only entries whose stat
+    /// information changed. Blobs are written to the object database if
+    /// `write` is set.
+    fn snapshot_worktree(&self, write: bool) -> Result<gix::index::State, A4Error> {
+        let workdir = self.workdir()?;
+        let index = self.load_index()?;
+        let stat_options = self
+            .repo
+            .stat_options()
+            .map_err(git_err("Failed to read stat options"))?;
+        let index_secs = index.timestamp().unix_seconds();
+        let mut excludes = self
+            .repo
+            .excludes(
+                &index,
+                None,
+                gix::worktree::stack::state::ignore::Source::WorktreeThenIdMappingIfNotSkipped,
+            )
+            .map_err(git_err("Failed to load ignore rules"))?;
+
+        let mut state = gix::index::State::new(self.repo.object_hash());
+        let mut walker = walkdir::WalkDir::new(workdir)
+            .min_depth(1)
+            .sort_by_file_name()
+            .into_iter();
+
+        while let Some(entry) = walker.next() {
+            let entry = entry.map_err(git_err("Failed to walk worktree"))?;
+            let path = entry.path();
+            let rela = path.strip_prefix(workdir).unwrap_or(path);
+            let rela_path =
+                gix::path::to_unix_separators_on_windows(gix::path::into_bstr(rela)).into_owned();
+            let file_type = entry.file_type();
+
+            if entry.file_name() == ".git" {
+                if file_type.is_dir() {
+                    walker.skip_current_dir();
+                }
+                continue;
+            }
+
+            if file_type.is_dir() {
+                let tracked = index.entry_by_path(rela_path.as_ref());
+                let is_gitlink = tracked.is_some_and(|e| e.mode == Mode::COMMIT);
+                let nested_repo = path.join(".git").exists();
+
+                if is_gitlink || nested_repo {
+                    // Submodules are recorded by their commit, never walked
+                    if let Some(e) = tracked {
+                        state.dangerously_push_entry(e.stat, e.id, e.flags, e.mode, e.path(&index));
+                    }
+                    walker.skip_current_dir();
+                    continue;
+                }
+
+                let mut prefix = rela_path.clone();
+                prefix.push(b'/');
+                let has_tracked = index.prefixed_entries(prefix.as_ref()).is_some();
+                let excluded = excludes
+                    .at_path(rela, Some(Mode::DIR))
+                    .map_err(git_err("Failed to check ignore rules"))?
+                    .is_excluded();
+                if excluded && !has_tracked {
+                    walker.skip_current_dir();
+                }
+                continue;
+            }
+
+            let tracked = index.entry_by_path(rela_path.as_ref());
+            if tracked.is_none() {
+                let excluded = excludes
+                    .at_path(rela, Some(Mode::FILE))
+                    .map_err(git_err("Failed to check ignore rules"))?
+                    .is_excluded();
+                if excluded {
+                    continue;
+                }
+            }
+
+            let metadata = gix::index::fs::Metadata::from_path_no_follow(path)?;
+            let stat = Stat::from_fs(&metadata).map_err(git_err("Invalid file time"))?;
+            let mode = if metadata.is_symlink() {
+                Mode::SYMLINK
+            } else if metadata.is_executable() {
+                Mode::FILE_EXECUTABLE
+            } else {
+                Mode::FILE
+            };
+
+            let unchanged = tracked.filter(|e| {
+                e.mode == mode
+                    && e.stat.matches(&stat, stat_options)
+                    && i64::from(stat.mtime.secs) < index_secs
+            });
+
+            let id = match unchanged {
+                Some(e) => e.id,
+                None => self.hash_blob(&read_worktree_file(path, mode)?, write)?,
+            };
+
+            state.dangerously_push_entry(stat, id, Flags::empty(), mode, rela_path.as_ref());
+        }
+
+        state.sort_entries();
+        Ok(state)
+    }
+
+    /// Build tree objects for the stage-0 entries of `state`, writing them to
+    /// the object database if `write` is set. Returns the root tree id.
+    fn build_index_tree(
+        &self,
+        state: &gix::index::State,
+        write: bool,
+    ) -> Result<ObjectId, A4Error> {
+        let entries: Vec<(BString, EntryMode, ObjectId)> = state
+            .entries()
+            .iter()
+            .filter(|e| e.stage() == gix::index::entry::Stage::Unconflicted)
+            .filter_map(|e| {
+                e.mode
+                    .to_tree_entry_mode()
+                    .map(|mode| (e.path(state).to_owned(), mode, e.id))
+            })
+            .collect();
+
+        self.build_tree(&entries, write)
+    }
+
+    fn build_tree(
+        &self,
+        entries: &[(BString, EntryMode, ObjectId)],
+        write: bool,
+    ) -> Result<ObjectId, A4Error> {
+        let mut tree = gix::objs::Tree::empty();
+        let mut i = 0;
+
+        while i < entries.len() {
+            let (path, mode, id) = &entries[i];
+            let Some(slash) = path.find_byte(b'/') else {
+                tree.entries.push(gix::objs::tree::Entry {
+                    mode: *mode,
+                    filename: path.clone(),
+                    oid: *id,
+                });
+                i += 1;
+                continue;
+            };
+
+            // Index order keeps every entry of a directory contiguous
+            let dir = &path[..=slash];
+            let mut children = Vec::new();
+            while i < entries.len() && entries[i].0.starts_with(dir) {
+                let (child, mode, id) = &entries[i];
+                children.push((child[slash + 1..].into(), *mode, *id));
+                i += 1;
+            }
+
+            tree.entries.push(gix::objs::tree::Entry {
+                mode: EntryKind::Tree.into(),
+                filename: path[..slash].into(),
+                oid: self.build_tree(&children, write)?,
+            });
+        }
+
+        tree.entries.sort();
+
+        if write {
+            Ok(self
+                .repo
+                .write_object(&tree)
+                .map_err(git_err("Failed to write tree"))?
+                .detach())
+        } else {
+            let mut buf = Vec::new();
+            tree.write_to(&mut buf)?;
+            Ok(gix::objs::compute_hash(
+                self.repo.object_hash(),
+                gix::objs::Kind::Tree,
+                &buf,
+            ))
+        }
+    }
+
+    fn tree_files(&self, tree_id: ObjectId) -> Result<FileMap, A4Error> {
+        if tree_id.is_empty_tree() {
+            return Ok(FileMap::new());
+        }
+
+        let tree = self
+            .repo
+            .find_tree(tree_id)
+            .map_err(git_err("Failed to find tree"))?;
+        let mut recorder = gix::traverse::tree::Recorder::default();
+        tree.traverse()
+            .breadthfirst(&mut recorder)
+            .map_err(git_err("Failed to traverse tree"))?;
+
+        Ok(recorder
+            .records
+            .into_iter()
+            .filter(|e| !e.mode.is_tree())
+            .map(|e| (e.filepath, (e.mode, e.oid)))
+            .collect())
+    }
+
+    /// Move the worktree and index from `from` to `to`, touching only paths
+    /// that differ. Refuses to overwrite files with local modifications.
+    fn checkout_tree(&self, from: ObjectId, to: ObjectId) -> Result<(), A4Error> {
+        let workdir = self.workdir()?.to_path_buf();
+        let old = self.tree_files(from)?;
+        let new = self.tree_files(to)?;
+
+        let changed: Vec<&BString> = old
+            .keys()
+            .chain(new.keys())
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .filter(|path| old.get(*path) != new.get(*path))
+            .collect();
+
+        for path in &changed {
+            let disk_path = workdir.join(gix::path::from_bstr(path.as_bstr()));
+            let Ok(metadata) = gix::index::fs::Metadata::from_path_no_follow(&disk_path) else {
+                continue;
+            };
+            let mode = if metadata.is_symlink() {
+                Mode::SYMLINK
+            } else {
+                Mode::FILE
+            };
+            let on_disk = self.hash_blob(&read_worktree_file(&disk_path, mode)?, false)?;
+            let expected = old.get(*path).or_else(|| new.get(*path)).map(|(_, id)| *id);
+            if Some(on_disk) != expected {
+                return Err(A4Error::Git(format!(
+                    "Local changes to {path} would be overwritten by sync"
+                )));
+            }
+        }
+
+        for path in &changed {
+            let disk_path = workdir.join(gix::path::from_bstr(path.as_bstr()));
+            match new.get(*path) {
+                None => {
+                    if disk_path.symlink_metadata().is_ok() {
+                        std::fs::remove_file(&disk_path)?;
+                    }
+                    remove_empty_parents(&workdir, &disk_path);
+                }
+                Some((mode, _)) if mode.is_commit() => {}
+                Some((mode, id)) => {
+                    let blob = self
+                        .repo
+                        .find_object(*id)
+                        .map_err(git_err("Failed to find blob"))?;
+                    write_worktree_file(&disk_path, *mode, &blob.data)?;
+                }
+            }
+        }
+
+        let mut index = self.load_index()?;
+        let touched: HashSet<&BString> = changed.iter().copied().collect();
+        index.remove_entries(|_, path, _| touched.contains(&path.to_owned()));
+        for path in &changed {
+            let Some((mode, id)) = new.get(*path) else {
+                continue;
+            };
+            let disk_path = workdir.join(gix::path::from_bstr(path.as_bstr()));
+            let stat = match gix::index::fs::Metadata::from_path_no_follow(&disk_path) {
+                Ok(metadata) => Stat::from_fs(&metadata).map_err(git_err("Invalid file time"))?,
+                Err(_) => Stat::default(),
+            };
+            index.dangerously_push_entry(
+                stat,
+                *id,
+                Flags::empty(),
+                index_mode(*mode),
+                path.as_bstr(),
+            );
+        }
+        index.sort_entries();
+        index
+            .write(Default::default())
+            .map_err(git_err("Failed to write index"))?;
+
+        Ok(())
+    }
+
+    /// Fetch every branch of a remote that lives on the local filesystem into
+    /// `refs/remotes/<remote>/`, copying the objects we don't have yet.
+    fn fetch_local(
+        &self,
+        remote: &str,
+        remote_path: &Path,
+        branch: Option<&str>,
+    ) -> Result<(), A4Error> {
+        let remote_repo = gix::open(remote_path).map_err(git_err("Failed to open remote"))?;
+        let references = remote_repo
+            .references()
+            .map_err(git_err("Failed to read remote branches"))?;
+        let branches = references
+            .local_branches()
+            .map_err(git_err("Failed to read remote branches"))?;
+
+        for found in branches {
+            let found = found.map_err(git_err("Failed to read remote branch"))?;
+            let name = found.name().shorten();
+            if branch.is_some_and(|branch| name != branch) {
+                continue;
+            }
+            let Some(id) = found.target().try_id().map(|id| id.to_owned()) else {
+                continue;
+            };
+            copy_objects(&remote_repo, &self.repo, id)?;
+            self.repo
+                .reference(
+                    format!("refs/remotes/{remote}/{name}"),
+                    id,
+                    PreviousValue::Any,
+                    "a4: fetch",
+                )
+                .map_err(git_err("Failed to update tracking branch"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Push `branch` to a remote that lives on the local filesystem by copying
+    /// the missing objects and updating the remote ref directly.
+    fn push_local(
+        &self,
+        remote: &str,
+        remote_path: &Path,
+        branch: &str,
+        force: bool,
+    ) -> Result<(), A4Error> {
+        let remote_repo = gix::open(remote_path).map_err(git_err("Failed to open remote"))?;
+        let branch_ref = format!("refs/heads/{branch}");
+        let tracking_ref = format!("refs/remotes/{remote}/{branch}");
+
+        let local_id = self
+            .resolve(&branch_ref)
+            .ok_or_else(|| A4Error::Git(format!("Branch {branch} has no commits to push")))?;
+        let remote_id = remote_repo
+            .try_find_reference(branch_ref.as_str())
+            .map_err(git_err("Failed to read remote branch"))?
+            .and_then(|r| r.target().try_id().map(|id| id.to_owned()));
+
+        if remote_id == Some(local_id) {
+            return Ok(());
+        }
+        self.check_push(remote, branch, local_id, remote_id, force)?;
+
+        if remote_repo.work_dir().is_some() {
+            let checked_out = remote_repo
+                .head_name()
+                .map_err(git_err("Failed to read remote HEAD"))?;
+            if checked_out.is_some_and(|name| name.as_bstr() == branch_ref.as_str()) {
+                return Err(A4Error::Git(format!(
+                    "Push rejected: {branch} is checked out in the non-bare remote"
+                )));
+            }
+        }
+
+        copy_objects(&self.repo, &remote_repo, local_id)?;
+
+        let previous = match remote_id {
+            Some(id) => PreviousValue::MustExistAndMatch(gix::refs::Target::Object(id)),
+            None => PreviousValue::MustNotExist,
+        };
+        remote_repo
+            .reference(branch_ref.as_str(), local_id, previous, "a4: push")
+            .map_err(git_err("Failed to update remote branch"))?;
+        self.update_tracking(&tracking_ref, local_id)
+    }
+
+    /// Push `branch` over the network the way `git push` does, using gix's
+    /// transport: read the refs `git-receive-pack` advertises, then send the
+    /// ref update and a pack of the objects the remote is missing.
+    fn push_transport(
+        &self,
+        remote: &str,
+        url: &gix::Url,
+        branch: &str,
+        force: bool,
+    ) -> Result<(), A4Error> {
+        use gix::protocol::transport::client::{self, MessageKind, TransportWithoutIO, WriteMode};
+        use gix::protocol::transport::{Protocol, Service};
+        use std::io::Write;
+
+        let branch_ref = format!("refs/heads/{branch}");
+        let tracking_ref = format!("refs/remotes/{remote}/{branch}");
+        let local_id = self
+            .resolve(&branch_ref)
+            .ok_or_else(|| A4Error::Git(format!("Branch {branch} has no commits to push")))?;
+
+        // receive-pack only speaks protocol v0/v1
+        let options = client::connect::Options {
+            version: Protocol::V1,
+            ..Default::default()
+        };
+        let mut transport = client::connect(url.clone(), options)
+            .map_err(git_err("Failed to connect to remote"))?;
+        let (mut helpers, _, prompt) = self
+            .repo
+            .config_snapshot()
+            .credential_helpers(url.clone())
+            .map_err(git_err("Failed to read credential helpers"))?;
+        let handshake = gix::protocol::handshake(
+            &mut transport,
+            Service::ReceivePack,
+            move |action| helpers.invoke(action, prompt.clone()),
+            Vec::new(),
+            &mut gix::progress::Discard,
+        )
+        .map_err(git_err("Failed to connect to remote"))?;
+
+        let advertised: Vec<(BString, ObjectId)> = handshake
+            .refs
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|r| match r.unpack() {
+                (name, Some(id), _) if !id.is_null() => Some((name.to_owned(), id.to_owned())),
+                _ => None,
+            })
+            .collect();
+        let remote_id = advertised
+            .iter()
+            .find(|(name, _)| name == branch_ref.as_str())
+            .map(|(_, id)| *id);
+
+        if remote_id == Some(local_id) {
+            return self.update_tracking(&tracking_ref, local_id);
+        }
+        self.check_push(remote, branch, local_id, remote_id, force)?;
+
+        let haves: Vec<ObjectId> = advertised
+            .iter()
+            .map(|(_, id)| *id)
+            .filter(|id| self.repo.has_object(id))
+            .collect();
+        let objects = self.missing_objects(local_id, &haves)?;
+
+        let report_status = handshake.capabilities.contains("report-status");
+        let old = remote_id.unwrap_or_else(|| ObjectId::null(self.repo.object_hash()));
+        let mut command = format!("{old} {local_id} {branch_ref}\0agent=a4");
+        if report_status {
+            command.push_str(" report-status");
+        }
+        command.push('\n');
+
+        let mut send = || -> std::io::Result<Option<String>> {
+            let mut request = transport
+                .request(WriteMode::Binary, MessageKind::Flush, false)
+                .map_err(std::io::Error::other)?;
+            request.write_all(command.as_bytes())?;
+            request.write_message(MessageKind::Flush)?;
+            let (mut writer, mut reader) = request.into_parts();
+            write_pack(&self.repo, &objects, &mut writer)?;
+            writer.flush()?;
+            drop(writer);
+
+            if !report_status {
+                return Ok(None);
+            }
+            // `unpack ok`, then `ok <ref>` or `ng <ref> <reason>`
+            let mut status = String::new();
+            let mut line = String::new();
+            while reader.readline_str(&mut line)? > 0 {
+                let entry = line.trim_end();
+                if entry != "unpack ok" && entry != format!("ok {branch_ref}") {
+                    status.push_str(entry);
+                    status.push('\n');
+                }
+                line.clear();
+            }
+            Ok(Some(status))
+        };
+        match send().map_err(git_err("Push failed"))? {
+            Some(status) if !status.is_empty() => {
+                return Err(A4Error::Git(format!(
+                    "Push rejected: {}",
+                    status.trim_end()
+                )))
+            }
+            _ => {}
+        }
+
+        self.update_tracking(&tracking_ref, local_id)
+    }
+
+    /// Refuse a push that would lose remote commits: without `force` the
+    /// remote must be an ancestor of `local_id`; with it (like
+    /// `--force-with-lease`) the remote must still be where we last fetched it.
+    fn check_push(
+        &self,
+        remote: &str,
+        branch: &str,
+        local_id: ObjectId,
+        remote_id: Option<ObjectId>,
+        force: bool,
+    ) -> Result<(), A4Error> {
+        if force {
+            if remote_id != self.resolve(&format!("refs/remotes/{remote}/{branch}")) {
+                return Err(A4Error::Git(format!(
+                    "Push rejected: {remote}/{branch} changed since the last fetch"
+                )));
+            }
+        } else if let Some(remote_id) = remote_id {
+            let is_ancestor = self.repo.has_object(remote_id)
+                && self.merge_base(remote_id, local_id)? == Some(remote_id);
+            if !is_ancestor {
+                return Err(A4Error::Git(format!(
+                    "Push rejected: {remote}/{branch} is not an ancestor of {branch} (fetch first)"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn update_tracking(&self, tracking_ref: &str, id: ObjectId) -> Result<(), A4Error> {
+        self.repo
+            .reference(tracking_ref, id, PreviousValue::Any, "a4: update by push")
+            .map_err(git_err("Failed to update tracking branch"))?;
+
+        Ok(())
+    }
+
+    /// Objects reachable from `tip` that a remote holding the commits `haves`
+    /// lacks. Objects from the `haves` trees count as present; anything older
+    /// may be sent again, which the remote ignores.
+    fn missing_objects(&self, tip: ObjectId, haves: &[ObjectId]) -> Result<Vec<ObjectId>, A4Error> {
+        let mut present = HashSet::new();
+        for &have in haves {
+            present.extend(self.ancestors(have)?);
+            let mut trees = vec![self.commit_tree(Some(have))?];
+            while let Some(id) = trees.pop() {
+                if !present.insert(id) {
+                    continue;
+                }
+                for entry in self.tree_entries(id)? {
+                    if entry.mode.is_tree() {
+                        trees.push(entry.oid);
+                    } else if !entry.mode.is_commit() {
+                        present.insert(entry.oid);
+                    }
+                }
+            }
+        }
+
+        let mut missing = Vec::new();
+        let mut pending = vec![tip];
+        while let Some(id) = pending.pop() {
+            if !present.insert(id) {
+                continue;
+            }
+            let object = self
+                .repo
+                .find_object(id)
+                .map_err(git_err("Failed to find object"))?;
+            match object.kind {
+                gix::objs::Kind::Commit => {
+                    let commit = object.into_commit();
+                    let decoded = commit
+                        .decode()
+                        .map_err(git_err("Failed to decode commit"))?;
+                    pending.push(decoded.tree());
+                    pending.extend(decoded.parents());
+                }
+                gix::objs::Kind::Tree => pending.extend(
+                    self.tree_entries(id)?
+                        .into_iter()
+                        .filter(|e| !e.mode.is_commit())
+                        .map(|e| e.oid),
+                ),
+                _ => {}
+            }
+            missing.push(id);
+        }
+
+        Ok(missing)
+    }
+
+    fn tree_entries(&self, tree: ObjectId) -> Result<Vec<gix::objs::tree::Entry>, A4Error> {
+        Ok(self
+            .repo
+            .find_tree(tree)
+            .map_err(git_err("Failed to find tree"))?
+            .decode()
+            .map_err(git_err("Failed to decode tree"))?
+            .entries
+            .iter()
+            .map(|e| gix::objs::tree::Entry::from(*e))
+            .collect())
+    }
+}
+
+/// Write `objects` as a version 2 pack without deltas, as
+/// `git-receive-pack` reads it after the ref updates.
+fn write_pack(
+    repo: &gix::Repository,
+    objects: &[ObjectId],
+    out: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    use gix::features::{hash, zlib};
+    use std::io::Write;
+
+    let mut out = hash::Write::new(out, repo.object_hash());
+    out.write_all(b"PACK")?;
+    out.write_all(&2u32.to_be_bytes())?;
+    out.write_all(&(objects.len() as u32).to_be_bytes())?;
+
+    for &id in objects {
+        let object = repo.find_object(id).map_err(std::io::Error::other)?;
+        let kind: u8 = match object.kind {
+            gix::objs::Kind::Commit => 1,
+            gix::objs::Kind::Tree => 2,
+            gix::objs::Kind::Blob => 3,
+            gix::objs::Kind::Tag => 4,
+        };
+
+        // Type in bits 4-6 of the first byte, then the size 4 and 7 bits at
+        // a time, with the high bit marking continuation
+        let mut size = object.data.len();
+        let mut byte = (kind << 4) | (size & 0x0f) as u8;
+        size >>= 4;
+        while size > 0 {
+            out.write_all(&[byte | 0x80])?;
+            byte = (size & 0x7f) as u8;
+            size >>= 7;
+        }
+        out.write_all(&[byte])?;
+
+        let mut deflate = zlib::stream::deflate::Write::new(&mut out);
+        deflate.write_all(&object.data)?;
+        deflate.flush()?;
+    }
+
+    let digest = out.hash.digest();
+    out.inner.write_all(&digest)?;
+    Ok(())
+}
+
+/// Copy every object reachable from `tip` in `source` that `target` doesn't
+/// have yet.
+fn copy_objects(
+    source: &gix::Repository,
+    target: &gix::Repository,
+    tip: ObjectId,
+) -> Result<(), A4Error> {
+    use gix::objs::Write;
+
+    let mut pending = vec![tip];
+    let mut seen = HashSet::new();
+
+    while let Some(id) = pending.pop() {
+        if !seen.insert(id) || target.has_object(id) {
+            continue;
+        }
+
+        let object = source
+            .find_object(id)
+            .map_err(git_err("Failed to find object"))?;
+
+        match object.kind {
+            gix::objs::Kind::Commit => {
+                let commit = object.clone().into_commit();
+                let decoded = commit
+                    .decode()
+                    .map_err(git_err("Failed to decode commit"))?;
+                pending.push(decoded.tree());
+                pending.extend(decoded.parents());
+            }
+            gix::objs::Kind::Tree => {
+                let tree = object.clone().into_tree();
+                let decoded = tree.decode().map_err(git_err("Failed to decode tree"))?;
+                pending.extend(
+                    decoded
+                        .entries
+                        .iter()
+                        .filter(|e| !e.mode.is_commit())
+                        .map(|e| e.oid.to_owned()),
+                );
+            }
+            gix::objs::Kind::Tag => {
+                let tag = object.clone().into_tag();
+                let decoded = tag.decode().map_err(git_err("Failed to decode tag"))?;
+                pending.push(decoded.target());
+            }
+            gix::objs::Kind::Blob => {}
+        }
+
+        target
+            .objects
+            .write_buf(object.kind, &object.data)
+            .map_err(git_err("Failed to write object to remote"))?;
+    }
+
+    Ok(())
+}
+
+fn index_mode(mode: EntryMode) -> Mode {
+    match mode.kind() {
+        EntryKind::Tree => Mode::DIR,
+        EntryKind::Blob => Mode::FILE,
+        EntryKind::BlobExecutable => Mode::FILE_EXECUTABLE,
+        EntryKind::Link => Mode::SYMLINK,
+        EntryKind::Commit => Mode::COMMIT,
+    }
+}
+
+fn read_worktree_file(path: &Path, mode: Mode) -> Result<Vec<u8>, A4Error> {
+    if mode == Mode::SYMLINK {
+        let target = std::fs::read_link(path)?;
+        Ok(gix::path::into_bstr(target).into_owned().into())
+    } else {
+        Ok(std::fs::read(path)?)
+    }
+}
+
+fn write_worktree_file(path: &Path, mode: EntryMode, data: &[u8]) -> Result<(), A4Error> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if path.symlink_metadata().is_ok() {
+        std::fs::remove_file(path)?;
+    }
+
+    #[cfg(unix)]
+    if mode.is_link() {
+        let target = gix::path::from_bstr(data.as_bstr());
+        std::os::unix::fs::symlink(target, path)?;
+        return Ok(());
+    }
+
+    std::fs::write(path, data)?;
+
+    #[cfg(unix)]
+    if mode.is_executable() {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    Ok(())
+}
+
+fn remove_empty_parents(root: &Path, path: &Path) {
+    let mut current = path.parent();
+    while let Some(dir) = current {
+        if dir == root || std::fs::remove_dir(dir).is_err() {
+            break;
+        }
+        current = dir.parent();
     }
 }
@@ -0,0 +1,253 @@
+use crate::anchors::AnchorToken;
+use crate::append::{append_block, AppendOptions};
+use crate::blocks::{anchored_blocks, find_block, AnchoredBlock};
+use crate::date::{Moment, UtcDay};
+use crate::error::A4Error;
+use crate::notes::read_note;
+use crate::search::day_from_filename;
+use crate::templates::{create_from_template, TemplateContext};
+use crate::vault::Vault;
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static FILED_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Anchor prefix for pasted blocks.
+pub const PASTE_PREFIX: &str = "paste";
+/// Anchor prefix for the records `triage` appends to an inbox file.
+pub const TRIAGED_PREFIX: &str = "triaged";
+
+const PASTE_HEADING: &str = "Paste Zone";
+const TRIAGED_HEADING: &str = "Triaged";
+const PASTE_ZONE_TEMPLATE: &str = "# Paste Zone {{date}}\n";
+
+/// A block in an inbox file that has not been filed yet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InboxBlock {
+    pub path: PathBuf,
+    pub day: Option<UtcDay>,
+    pub block: AnchoredBlock,
+}
+
+/// Where [`triage`] files a block.
+pub struct TriageTarget<'a> {
+    pub file: &'a Path,
+    pub heading: &'a str,
+    /// Prefix of the fresh anchor, e.g. `idea` for `^idea-1012`.
+    pub prefix: &'a str,
+}
+
+/// Resolve `day`'s paste zone, creating it (from
+/// `routines/templates/paste-zone.md` if present) when absent.
+pub fn paste_zone(vault: &Vault, now: &Moment, day: &UtcDay) -> Result<PathBuf, A4Error> {
+    let path = vault.inbox_path(day.clone());
+    let template_path = vault.templates_dir().join("paste-zone.md");
+    let ctx = TemplateContext::for_day(vault, now, day)?;
+    create_from_template(vault, &path, &template_path, PASTE_ZONE_TEMPLATE, &ctx)?;
+    Ok(path)
+}
+
+/// Append `content` to `day`'s paste zone as a `^paste-HHMM` block.
+pub fn paste(
+    vault: &Vault,
+    now: &Moment,
+    day: &UtcDay,
+    content: &str,
+) -> Result<(PathBuf, AnchorToken), A4Error> {
+    let path = paste_zone(vault, now, day)?;
+    let device = vault.device_name();
+    let opts = AppendOptions {
+        heading: PASTE_HEADING,
+        anchor: AnchorToken::parse(&format!("{PASTE_PREFIX}-{}", now.local_hhmm()))?,
+        content,
+        device: device.as_deref(),
+    };
+    let written = append_block(vault, &path, opts)?;
+    Ok((path, written))
+}
+
+/// Every anchored block under `inbox/` without a triage record, ordered by
+/// file and then position.
+pub fn untriaged(vault: &Vault) -> Result<Vec<InboxBlock>, A4Error> {
+    let inbox = vault.root().join("inbox");
+    if !inbox.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut blocks = Vec::new();
+    for entry in walkdir::WalkDir::new(&inbox).sort_by_file_name() {
+        let entry = entry.map_err(|e| A4Error::Io(std::io::Error::other(e)))?;
+        let path = entry.path();
+        if !entry.file_type().is_file() || path.extension().is_none_or(|ext| ext != "md") {
+            continue;
+        }
+
+        let note = read_note(path)?;
+        let all = anchored_blocks(&note.body);
+        let filed = filed_markers(&all);
+        let day = day_from_filename(path);
+        blocks.extend(
+            all.into_iter()
+                .filter(|b| b.token.prefix != TRIAGED_PREFIX)
+                .filter(|b| !filed.contains(&b.token.to_marker()))
+                .map(|block| InboxBlock {
+                    path: path.to_path_buf(),
+                    day: day.clone(),
+                    block,
+                }),
+        );
+    }
+
+    Ok(blocks)
+}
+
+/// File the block at `marker` in `inbox_file` under `target`, with a fresh
+/// anchor, then append a record of where it went to the inbox file. The
+/// original block is left in place.
+pub fn triage(
+    vault: &Vault,
+    now: &Moment,
+    inbox_file: &Path,
+    marker: &AnchorToken,
+    target: TriageTarget,
+) -> Result<AnchorToken, A4Error> {
+    let note = read_note(inbox_file)?;
+    let block = find_block(&note, marker).ok_or_else(|| A4Error::BlockNotFound {
+        path: inbox_file.to_path_buf(),
+        fragment: marker.to_marker(),
+    })?;
+    if filed_markers(&anchored_blocks(&note.body)).contains(&marker.to_marker()) {
+        return Err(A4Error::AlreadyTriaged {
+            path: inbox_file.to_path_buf(),
+            fragment: marker.to_marker(),
+        });
+    }
+
+    let hhmm = now.local_hhmm();
+    let device = vault.device_name();
+    let filed = append_block(
+        vault,
+        target.file,
+        AppendOptions {
+            heading: target.heading,
+            anchor: AnchorToken::parse(&format!("{}-{hhmm}", target.prefix))?,
+            content: &block.content,
+            device: device.as_deref(),
+        },
+    )?;
+
+    let rel = target
+        .file
+        .strip_prefix(vault.root())
+        .unwrap_or(target.file);
+    let record = format!(
+        "filed {} -> [[{}#{}]]",
+        marker.to_marker(),
+        rel.with_extension("").display(),
+        filed.to_marker()
+    );
+    append_block(
+        vault,
+        inbox_file,
+        AppendOptions {
+            heading: TRIAGED_HEADING,
+            anchor: AnchorToken::parse(&format!("{TRIAGED_PREFIX}-{hhmm}"))?,
+            content: &record,
+            device: device.as_deref(),
+        },
+    )?;
+
+    Ok(filed)
+}
+
+/// Markers named by `filed ^x -> [[...]]` records.
+fn filed_markers(blocks: &[AnchoredBlock]) -> HashSet<String> {
+    let re = FILED_REGEX.get_or_init(|| Regex::new(r"^filed (\^\S+) -> \[\[").unwrap());
+    blocks
+        .iter()
+        .filter(|b| b.token.prefix == TRIAGED_PREFIX)
+        .flat_map(|b| b.content.lines())
+        .filter_map(|line| re.captures(line).map(|c| c[1].to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::{Clock, FixedClock, Zone};
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn moment(hour: u8, minute: u8) -> Moment {
+        let instant = time::Date::from_calendar_date(2025, time::Month::September, 4)
+            .unwrap()
+            .with_hms(hour, minute, 0)
+            .unwrap()
+            .assume_utc();
+        Moment {
+            instant: FixedClock(instant).now_utc(),
+            filename_zone: Zone::Utc,
+            local_zone: Zone::Utc,
+            rollover_hour: 0,
+        }
+    }
+
+    #[test]
+    fn test_paste_and_triage() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let day = UtcDay::parse("2025-09-04").unwrap();
+
+        let (inbox, first) = paste(&vault, &moment(9, 30), &day, "https://example.com").unwrap();
+        assert!(inbox.ends_with("inbox/2025/2025-09/2025-09-04--paste-zone.md"));
+        assert_eq!(first.to_marker(), "^paste-0930");
+        paste(&vault, &moment(9, 45), &day, "Call the bank").unwrap();
+
+        let pending = untriaged(&vault).unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].day, Some(day.clone()));
+        assert_eq!(pending[1].block.content, "Call the bank");
+
+        let target_file = vault.capture_day_path(day.clone());
+        let target = TriageTarget {
+            file: &target_file,
+            heading: "Tasks",
+            prefix: "task",
+        };
+        let filed = triage(
+            &vault,
+            &moment(10, 12),
+            &inbox,
+            &pending[1].block.token,
+            target,
+        )
+        .unwrap();
+        assert_eq!(filed.to_marker(), "^task-1012");
+
+        let capture = std::fs::read_to_string(&target_file).unwrap();
+        assert!(capture.contains("## Tasks\n\n^task-1012\n\nCall the bank\n"));
+
+        let inbox_text = std::fs::read_to_string(&inbox).unwrap();
+        assert!(inbox_text.contains("^paste-0945\n\nCall the bank\n"));
+        assert!(inbox_text.contains(
+            "## Triaged\n\n^triaged-1012\n\nfiled ^paste-0945 -> [[capture/2025/2025-09/2025-09-04#^task-1012]]\n"
+        ));
+
+        let pending = untriaged(&vault).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].block.token.to_marker(), "^paste-0930");
+
+        let again = TriageTarget {
+            file: &target_file,
+            heading: "Tasks",
+            prefix: "task",
+        };
+        let marker = AnchorToken::parse("paste-0945").unwrap();
+        assert!(matches!(
+            triage(&vault, &moment(10, 20), &inbox, &marker, again),
+            Err(A4Error::AlreadyTriaged { .. })
+        ));
+    }
+}
@@ -0,0 +1,643 @@
+use crate::anchors::AnchorToken;
+use crate::append::{append_block, AppendOptions};
+use crate::date::Moment;
+use crate::error::A4Error;
+use crate::notes::split_front_matter;
+use crate::templates::{create_from_template, TemplateContext};
+use crate::vault::Vault;
+use fs_err as fs;
+use regex::Regex;
+use scraper::{ElementRef, Html, Node, Selector};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static WHITESPACE_REGEX: OnceLock<Regex> = OnceLock::new();
+static BLANK_LINES_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Longest slug allowed by protocol §17.
+pub const MAX_SLUG_LEN: usize = 64;
+
+/// Elements that never hold article text.
+const SKIPPED: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "iframe", "nav", "header", "footer", "aside",
+    "form", "button", "head",
+];
+
+/// Readable content extracted from a saved page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Article {
+    pub title: Option<String>,
+    pub markdown: String,
+}
+
+pub struct IngestOptions<'a> {
+    pub url: &'a str,
+    /// `origin.via` in front matter; defaults to `a4`
+    pub via: Option<&'a str>,
+    /// Overrides the title found in the page
+    pub title: Option<&'a str>,
+}
+
+/// The note written by [`ingest_article`].
+#[derive(Debug)]
+pub struct IngestedArticle {
+    pub path: PathBuf,
+    pub slug: String,
+    pub title: String,
+    /// The `^read-HHMM` block linking to the note from today's capture note.
+    pub backref: AnchorToken,
+}
+
+/// Turn `input` into a protocol §17 slug (`[a-z0-9][a-z0-9-]{1,63}`), or
+/// `None` when it has fewer than two usable characters.
+pub fn slugify(input: &str) -> Option<String> {
+    let mut slug = String::new();
+    for c in input.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    slug.truncate(MAX_SLUG_LEN);
+    let slug = slug.trim_end_matches('-');
+    (slug.len() >= 2).then(|| slug.to_string())
+}
+
+/// Extract the title and main content of an HTML page as Markdown. Content
+/// comes from the first `<article>`, `<main>` or `[role=main]` element,
+/// falling back to `<body>`; navigation, scripts and similar chrome are
+/// dropped.
+pub fn html_to_article(html: &str) -> Article {
+    let doc = Html::parse_document(html);
+
+    let title = ["meta[property='og:title']", "title", "h1"]
+        .iter()
+        .find_map(|sel| {
+            let el = doc.select(&selector(sel)).next()?;
+            let text = match el.value().attr("content") {
+                Some(content) => content.to_string(),
+                None => el.text().collect(),
+            };
+            let text = collapse_whitespace(&text).trim().to_string();
+            (!text.is_empty()).then_some(text)
+        });
+
+    let markdown = ["article", "main", "[role='main']", "body"]
+        .iter()
+        .filter_map(|sel| doc.select(&selector(sel)).next())
+        .map(|root| {
+            let mut blocks = Vec::new();
+            render_blocks(root, &mut blocks);
+            tidy(&blocks.join("\n\n"))
+        })
+        .find(|markdown| !markdown.is_empty())
+        .unwrap_or_default();
+
+    Article { title, markdown }
+}
+
+/// Write `source` (HTML, or Markdown for `.md`/`.markdown` files) to
+/// `sources/articles/<slug>.md`; see [`write_article`].
+pub fn ingest_article(
+    vault: &Vault,
+    now: &Moment,
+    source: &Path,
+    opts: IngestOptions,
+) -> Result<IngestedArticle, A4Error> {
+    let raw = fs::read_to_string(source)?;
+    let is_markdown = source
+        .extension()
+        .is_some_and(|ext| ext == "md" || ext == "markdown");
+    let mut article = if is_markdown {
+        markdown_to_article(&raw)
+    } else {
+        html_to_article(&raw)
+    };
+    if article.markdown.is_empty() {
+        return Err(A4Error::NoReadableContent {
+            path: source.to_path_buf(),
+        });
+    }
+    if article.title.is_none() {
+        article.title = source
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned());
+    }
+
+    write_article(vault, now, article, opts)
+}
+
+/// Write `article` to `sources/articles/<slug>.md` with `kind:
+/// source.article` front matter, then link it from today's capture note with
+/// a `^read-HHMM` block. An existing note is never overwritten; the slug gets
+/// a numeric suffix.
+pub fn write_article(
+    vault: &Vault,
+    now: &Moment,
+    article: Article,
+    opts: IngestOptions,
+) -> Result<IngestedArticle, A4Error> {
+    let title = opts.title.map(str::to_string).or(article.title);
+    let base = source_slug(title.as_deref(), opts.url);
+    let title = title.unwrap_or_else(|| opts.url.to_string());
+    let (slug, path) = (1..)
+        .map(|n| match n {
+            1 => base.clone(),
+            n => format!("{}-{n}", truncate_for_suffix(&base, n)),
+        })
+        .map(|slug| {
+            let path = source_path(vault, &slug);
+            (slug, path)
+        })
+        .find(|(_, path)| !path.exists())
+        .expect("an unused slug");
+
+    let content = format!(
+        "{}\n{}\n",
+        source_header(now, &title, &opts),
+        article.markdown
+    );
+    vault.ensure_parents(&path)?;
+    fs::write(&path, content)?;
+
+    let day = now.day();
+    let capture = vault.capture_day_path(day.clone());
+    create_from_template(
+        vault,
+        &capture,
+        &vault.template_path(),
+        "\n",
+        &TemplateContext::for_day(vault, now, &day)?,
+    )?;
+
+    let link = format!("[[sources/articles/{slug}|{title}]]");
+    let device = vault.device_name();
+    let opts = AppendOptions {
+        heading: vault.config().heading_for("read").unwrap_or("Reading"),
+        anchor: AnchorToken::parse(&format!("read-{}", now.local_hhmm()))?,
+        content: &link,
+        device: device.as_deref(),
+    };
+    let backref = append_block(vault, &capture, opts)?;
+
+    Ok(IngestedArticle {
+        path,
+        slug,
+        title,
+        backref,
+    })
+}
+
+/// Append `text` as a `^hl-HHMM` quote under `## Highlights` in the source
+/// note for `opts.url`, creating the note (without a capture back-reference)
+/// if it does not exist yet. Highlights from the same title or URL land in
+/// the same note.
+pub fn append_highlight(
+    vault: &Vault,
+    now: &Moment,
+    text: &str,
+    opts: IngestOptions,
+) -> Result<(PathBuf, AnchorToken), A4Error> {
+    let path = source_path(vault, &source_slug(opts.title, opts.url));
+    let title = opts.title.unwrap_or(opts.url);
+    if !path.exists() {
+        vault.ensure_parents(&path)?;
+        fs::write(&path, source_header(now, title, &opts))?;
+    }
+
+    let quote: Vec<String> = text
+        .trim()
+        .lines()
+        .map(|line| format!("> {line}").trim_end().to_string())
+        .collect();
+    let device = vault.device_name();
+    let append = AppendOptions {
+        heading: "Highlights",
+        anchor: AnchorToken::parse(&format!("hl-{}", now.local_hhmm()))?,
+        content: &quote.join("\n"),
+        device: device.as_deref(),
+    };
+    let written = append_block(vault, &path, append)?;
+    Ok((path, written))
+}
+
+fn source_path(vault: &Vault, slug: &str) -> PathBuf {
+    vault
+        .root()
+        .join("sources")
+        .join("articles")
+        .join(format!("{slug}.md"))
+}
+
+/// Slug from the title, else from the URL without its scheme.
+fn source_slug(title: Option<&str>, url: &str) -> String {
+    let bare = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .trim_start_matches("www.");
+    title
+        .and_then(slugify)
+        .or_else(|| slugify(bare))
+        .unwrap_or_else(|| "article".to_string())
+}
+
+fn source_header(now: &Moment, title: &str, opts: &IngestOptions) -> String {
+    format!(
+        "---\nkind: source.article\ncreated: {}\norigin: {{ url: {}, via: {} }}\n---\n# {title}\n\nSource: <{}>\n",
+        now.utc_iso8601(),
+        yaml_string(opts.url),
+        yaml_string(opts.via.unwrap_or("a4")),
+        opts.url,
+    )
+}
+
+/// Markdown sources keep their body; a leading `# Title` becomes the title.
+fn markdown_to_article(raw: &str) -> Article {
+    let (_, body) = split_front_matter(raw);
+    let body = body.trim_start();
+    match body.split_once('\n') {
+        Some((first, rest)) if first.starts_with("# ") => Article {
+            title: Some(first[2..].trim().to_string()),
+            markdown: tidy(rest),
+        },
+        _ if body.starts_with("# ") => Article {
+            title: Some(body[2..].trim().to_string()),
+            markdown: String::new(),
+        },
+        _ => Article {
+            title: None,
+            markdown: tidy(body),
+        },
+    }
+}
+
+fn truncate_for_suffix(base: &str, n: usize) -> &str {
+    let room = MAX_SLUG_LEN - n.to_string().len() - 1;
+    base[..base.len().min(room)].trim_end_matches('-')
+}
+
+fn yaml_string(s: &str) -> String {
+    serde_json::Value::String(s.to_string()).to_string()
+}
+
+fn selector(sel: &str) -> Selector {
+    Selector::parse(sel).expect("valid selector")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let re = WHITESPACE_REGEX.get_or_init(|| Regex::new(r"\s+").unwrap());
+    re.replace_all(text, " ").into_owned()
+}
+
+/// Trim trailing spaces and squeeze runs of blank lines.
+fn tidy(markdown: &str) -> String {
+    let re = BLANK_LINES_REGEX.get_or_init(|| Regex::new(r"\n{3,}").unwrap());
+    let lines: Vec<&str> = markdown.lines().map(str::trim_end).collect();
+    re.replace_all(&lines.join("\n"), "\n\n").trim().to_string()
+}
+
+fn is_block(name: &str) -> bool {
+    matches!(
+        name,
+        "p" | "div"
+            | "section"
+            | "article"
+            | "main"
+            | "body"
+            | "h1"
+            | "h2"
+            | "h3"
+            | "h4"
+            | "h5"
+            | "h6"
+            | "ul"
+            | "ol"
+            | "li"
+            | "blockquote"
+            | "pre"
+            | "hr"
+            | "figure"
+            | "figcaption"
+            | "table"
+            | "tbody"
+            | "thead"
+            | "tr"
+            | "td"
+            | "th"
+            | "dl"
+            | "dt"
+            | "dd"
+    )
+}
+
+/// Render the children of `el` as Markdown blocks, wrapping runs of inline
+/// content in paragraphs.
+fn render_blocks(el: ElementRef, blocks: &mut Vec<String>) {
+    let mut inline = String::new();
+    let flush = |inline: &mut String, blocks: &mut Vec<String>| {
+        let para = inline.trim().to_string();
+        if !para.is_empty() {
+            blocks.push(para);
+        }
+        inline.clear();
+    };
+
+    for child in el.children() {
+        match child.value() {
+            Node::Text(text) => inline.push_str(&collapse_whitespace(text)),
+            Node::Element(e) if SKIPPED.contains(&e.name()) => {}
+            Node::Element(e) if is_block(e.name()) => {
+                flush(&mut inline, blocks);
+                let child = ElementRef::wrap(child).expect("element node");
+                render_block(child, blocks);
+            }
+            Node::Element(_) => {
+                let child = ElementRef::wrap(child).expect("element node");
+                inline.push_str(&render_inline(child));
+            }
+            _ => {}
+        }
+    }
+    flush(&mut inline, blocks);
+}
+
+fn render_block(el: ElementRef, blocks: &mut Vec<String>) {
+    let name = el.value().name();
+    match name {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = name[1..].parse::<usize>().unwrap_or(1);
+            let text = inline_children(el);
+            if !text.is_empty() {
+                blocks.push(format!("{} {text}", "#".repeat(level)));
+            }
+        }
+        "p" => {
+            let text = inline_children(el);
+            if !text.is_empty() {
+                blocks.push(text);
+            }
+        }
+        "ul" | "ol" => {
+            let items: Vec<String> = el
+                .children()
+                .filter_map(ElementRef::wrap)
+                .filter(|li| li.value().name() == "li")
+                .enumerate()
+                .filter_map(|(i, li)| {
+                    let marker = if name == "ol" {
+                        format!("{}. ", i + 1)
+                    } else {
+                        "- ".to_string()
+                    };
+                    let mut inner = Vec::new();
+                    render_blocks(li, &mut inner);
+                    let body = inner.join("\n");
+                    (!body.is_empty()).then(|| indent_after_first(&marker, &body))
+                })
+                .collect();
+            if !items.is_empty() {
+                blocks.push(items.join("\n"));
+            }
+        }
+        "blockquote" => {
+            let mut inner = Vec::new();
+            render_blocks(el, &mut inner);
+            let quoted: Vec<String> = inner
+                .join("\n\n")
+                .lines()
+                .map(|line| {
+                    if line.is_empty() {
+                        ">".to_string()
+                    } else {
+                        format!("> {line}")
+                    }
+                })
+                .collect();
+            if !quoted.is_empty() {
+                blocks.push(quoted.join("\n"));
+            }
+        }
+        "pre" => {
+            let code: String = el.text().collect();
+            let lang = el
+                .select(&selector("code"))
+                .next()
+                .and_then(|code| code.value().attr("class"))
+                .and_then(|class| {
+                    class
+                        .split_whitespace()
+                        .find_map(|c| c.strip_prefix("language-"))
+                })
+                .unwrap_or("");
+            blocks.push(format!("```{lang}\n{}\n```", code.trim_end_matches('\n')));
+        }
+        "hr" => blocks.push("---".to_string()),
+        _ => render_blocks(el, blocks),
+    }
+}
+
+fn indent_after_first(marker: &str, body: &str) -> String {
+    let pad = " ".repeat(marker.len());
+    body.lines()
+        .enumerate()
+        .map(|(i, line)| match i {
+            0 => format!("{marker}{line}"),
+            _ if line.is_empty() => String::new(),
+            _ => format!("{pad}{line}"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn inline_children(el: ElementRef) -> String {
+    let text: String = el
+        .children()
+        .map(|child| match child.value() {
+            Node::Text(text) => collapse_whitespace(text),
+            Node::Element(_) => ElementRef::wrap(child)
+                .map(render_inline)
+                .unwrap_or_default(),
+            _ => String::new(),
+        })
+        .collect();
+    text.lines().map(str::trim).collect::<Vec<_>>().join("\n")
+}
+
+fn render_inline(el: ElementRef) -> String {
+    let name = el.value().name();
+    if SKIPPED.contains(&name) {
+        return String::new();
+    }
+
+    match name {
+        "br" => "\n".to_string(),
+        "img" => match el.value().attr("src") {
+            Some(src) => format!("![{}]({src})", el.value().attr("alt").unwrap_or("")),
+            None => String::new(),
+        },
+        "a" => {
+            let text = inline_children(el);
+            match el.value().attr("href") {
+                Some(href)
+                    if !text.is_empty()
+                        && !href.starts_with('#')
+                        && !href.starts_with("javascript:") =>
+                {
+                    format!("[{text}]({href})")
+                }
+                _ => text,
+            }
+        }
+        "strong" | "b" => wrap_inline(el, "**"),
+        "em" | "i" => wrap_inline(el, "*"),
+        "code" => wrap_inline(el, "`"),
+        _ => inline_children(el),
+    }
+}
+
+fn wrap_inline(el: ElementRef, mark: &str) -> String {
+    let text = inline_children(el);
+    if text.is_empty() {
+        String::new()
+    } else {
+        format!("{mark}{text}{mark}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::{Clock, FixedClock, Zone};
+    use crate::vault::VaultOpts;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    const PAGE: &str = r#"<!doctype html>
+<html>
+<head>
+  <title>Ignored tab title</title>
+  <meta property="og:title" content="State of GPT: 2025 Edition">
+  <script>track()</script>
+</head>
+<body>
+  <nav><a href="/">Home</a></nav>
+  <article>
+    <h1>State of GPT</h1>
+    <p>Models are <strong>big</strong> and
+       <a href="https://example.com/scale">scaling</a> continues.</p>
+    <ul><li>Pretraining</li><li>Fine-tuning <em>matters</em></li></ul>
+    <blockquote><p>Data is the moat.</p></blockquote>
+    <pre><code class="language-python">print("hi")
+</code></pre>
+  </article>
+  <footer>Copyright</footer>
+</body>
+</html>"#;
+
+    fn moment() -> Moment {
+        let instant = time::Date::from_calendar_date(2025, time::Month::September, 14)
+            .unwrap()
+            .with_hms(10, 45, 0)
+            .unwrap()
+            .assume_utc();
+        Moment {
+            instant: FixedClock(instant).now_utc(),
+            filename_zone: Zone::Utc,
+            local_zone: Zone::Utc,
+            rollover_hour: 0,
+        }
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(
+            slugify("State of GPT: 2025 Edition").as_deref(),
+            Some("state-of-gpt-2025-edition")
+        );
+        assert_eq!(
+            slugify("  Ünïcode -- only!  ").as_deref(),
+            Some("n-code-only")
+        );
+        assert_eq!(slugify("?"), None);
+        let long = slugify(&"word ".repeat(40)).unwrap();
+        assert!(long.len() <= MAX_SLUG_LEN);
+        assert!(!long.ends_with('-'));
+    }
+
+    #[test]
+    fn test_html_to_article() {
+        let article = html_to_article(PAGE);
+        assert_eq!(article.title.as_deref(), Some("State of GPT: 2025 Edition"));
+        assert_eq!(
+            article.markdown,
+            "# State of GPT\n\n\
+             Models are **big** and [scaling](https://example.com/scale) continues.\n\n\
+             - Pretraining\n- Fine-tuning *matters*\n\n\
+             > Data is the moat.\n\n\
+             ```python\nprint(\"hi\")\n```"
+        );
+    }
+
+    #[test]
+    fn test_ingest_article_writes_source_and_backref() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let page = temp_dir.path().join("page.html");
+        std::fs::write(&page, PAGE).unwrap();
+
+        let now = moment();
+        let opts = || IngestOptions {
+            url: "https://example.com/gpt",
+            via: None,
+            title: None,
+        };
+
+        let ingested = ingest_article(&vault, &now, &page, opts()).unwrap();
+        assert_eq!(ingested.slug, "state-of-gpt-2025-edition");
+        assert_eq!(ingested.backref.to_marker(), "^read-1045");
+
+        let note = std::fs::read_to_string(&ingested.path).unwrap();
+        assert!(note.starts_with(
+            "---\nkind: source.article\ncreated: 2025-09-14T10:45:00.000000000Z\norigin: { url: \"https://example.com/gpt\", via: \"a4\" }\n---\n# State of GPT: 2025 Edition\n\nSource: <https://example.com/gpt>\n\n# State of GPT\n"
+        ));
+
+        let capture = std::fs::read_to_string(
+            vault.capture_day_path(crate::UtcDay::parse("2025-09-14").unwrap()),
+        )
+        .unwrap();
+        assert!(capture.contains(
+            "## Reading\n\n^read-1045\n\n[[sources/articles/state-of-gpt-2025-edition|State of GPT: 2025 Edition]]\n"
+        ));
+
+        // A second ingest of the same title never overwrites the first note
+        let again = ingest_article(&vault, &now, &page, opts()).unwrap();
+        assert_eq!(again.slug, "state-of-gpt-2025-edition-2");
+    }
+
+    #[test]
+    fn test_append_highlight_reuses_source_note() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let opts = || IngestOptions {
+            url: "https://www.example.com/posts/monoids",
+            via: Some("Chrome"),
+            title: None,
+        };
+
+        let (path, first) =
+            append_highlight(&vault, &moment(), "Merges are\nmonoidal.", opts()).unwrap();
+        assert!(path.ends_with("sources/articles/example-com-posts-monoids.md"));
+        assert_eq!(first.to_marker(), "^hl-1045");
+        let (again, second) = append_highlight(&vault, &moment(), "Append only.", opts()).unwrap();
+        assert_eq!(again, path);
+        assert_eq!(second.to_marker(), "^hl-1045__2");
+
+        let note = std::fs::read_to_string(&path).unwrap();
+        assert!(note.starts_with("---\nkind: source.article\n"));
+        assert!(note.contains("via: \"Chrome\""));
+        assert!(note.contains("## Highlights\n\n^hl-1045\n\n> Merges are\n> monoidal.\n"));
+        assert!(note.contains("> Append only.\n"));
+    }
+}
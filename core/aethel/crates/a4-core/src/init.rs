@@ -0,0 +1,195 @@
+use crate::config::VaultConfig;
+use crate::error::A4Error;
+use crate::git_backend::{GitBackend, GixBackend};
+use fs_err as fs;
+use std::path::{Path, PathBuf};
+
+/// Protocol version recorded in `.a4/version` (protocol §15).
+pub const PROTOCOL_VERSION: &str = "1.0.0";
+
+/// The protocol §3 directory tree, relative to the vault root.
+const VAULT_DIRS: &[&str] = &[
+    "inbox",
+    "capture",
+    "collections/weekly-plans",
+    "collections/journals",
+    "collections/research-memos",
+    "collections/essays",
+    "projects",
+    "sources/articles",
+    "sources/transcripts",
+    "sources/books",
+    "routines/templates",
+    "routines/checklists",
+    ".a4",
+];
+
+const GITIGNORE: &str = ".DS_Store
+Thumbs.db
+.obsidian/
+.a4/cache/
+.a4/tmp/
+*.swp
+*.swo
+";
+
+const README: &str = "# A4 Core Vault
+
+Plain-Markdown, Git-native personal knowledge base.
+
+- Daily-first: notes in `capture/YYYY/YYYY-MM/YYYY-MM-DD.md` (UTC filename).
+- Append-only semantics under block anchors (e.g., `^focus-0930`).
+- Front matter optional; used by apps for generated docs.
+- Large assets live in a separate `a4-assets` repo (mounted at `assets/` if desired).
+";
+
+pub const DAILY_TEMPLATE: &str = "---
+kind: capture.day
+created: {{now_utc}}
+tags: [daily]
+---
+# Daily Note {{YYYY-MM-DD}}
+
+## Intention
+^intent-{{hhmm}}
+
+## End of Day
+^eod-{{hhmm}}
+";
+
+pub const WEEKLY_TEMPLATE: &str = "---
+kind: plan.weekly
+created: {{now_utc}}
+week: {{week}}
+---
+# Week {{week}} ({{monday}} to {{sunday}})
+
+{{day_links}}
+
+## Reflection on W{{week_number}}
+
+## Big Rocks
+
+## Anchors & Timeboxes
+";
+
+#[derive(Default)]
+pub struct InitOptions<'a> {
+    /// URL for the `origin` remote.
+    pub remote: Option<&'a str>,
+}
+
+/// What `init_vault` changed; empty on an already-initialized vault.
+#[derive(Debug, Default)]
+pub struct InitReport {
+    pub created: Vec<PathBuf>,
+    pub git_initialized: bool,
+    pub remote_added: bool,
+}
+
+/// Scaffold a conformant vault at `root`: the §3 tree, the `.a4/` marker with
+/// `.a4/version`, default daily and weekly templates, a git repository and optionally an
+/// `origin` remote. Existing files are never overwritten, so running it again
+/// is a no-op.
+pub fn init_vault(root: &Path, opts: InitOptions) -> Result<InitReport, A4Error> {
+    let mut report = InitReport::default();
+
+    fs::create_dir_all(root)?;
+
+    for dir in VAULT_DIRS {
+        let path = root.join(dir);
+        if !path.exists() {
+            fs::create_dir_all(&path)?;
+            // Git does not track empty directories
+            fs::write(path.join(".gitkeep"), "")?;
+            report.created.push(path);
+        }
+    }
+
+    let config = VaultConfig::load(root)?;
+    let files = [
+        (
+            PathBuf::from(".a4/version"),
+            format!("{PROTOCOL_VERSION}\n"),
+        ),
+        (PathBuf::from(".gitignore"), GITIGNORE.to_string()),
+        (PathBuf::from("README.md"), README.to_string()),
+        (config.templates.daily, DAILY_TEMPLATE.to_string()),
+        (config.templates.weekly, WEEKLY_TEMPLATE.to_string()),
+    ];
+    for (rel, content) in files {
+        let path = root.join(rel);
+        if !path.exists() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, content)?;
+            report.created.push(path);
+        }
+    }
+
+    report.git_initialized = !root.join(".git").exists();
+    let mut backend = GixBackend::init(root)?;
+
+    if let Some(url) = opts.remote {
+        report.remote_added = backend.ensure_remote("origin", url)?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::{Vault, VaultOpts};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_init_creates_tree_and_is_idempotent() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("vault");
+
+        let report = init_vault(&root, InitOptions::default()).unwrap();
+        assert!(report.git_initialized);
+        assert!(!report.remote_added);
+        assert!(root.join("capture").is_dir());
+        assert!(root.join("collections/weekly-plans").is_dir());
+        assert!(root.join(".git").is_dir());
+        assert_eq!(
+            std::fs::read_to_string(root.join(".a4/version")).unwrap(),
+            "1.0.0\n"
+        );
+
+        let vault = Vault::open(&root, VaultOpts::default()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(vault.template_path()).unwrap(),
+            DAILY_TEMPLATE
+        );
+
+        std::fs::write(root.join("README.md"), "Mine\n").unwrap();
+        let report = init_vault(&root, InitOptions::default()).unwrap();
+        assert!(report.created.is_empty());
+        assert!(!report.git_initialized);
+        assert_eq!(
+            std::fs::read_to_string(root.join("README.md")).unwrap(),
+            "Mine\n"
+        );
+    }
+
+    #[test]
+    fn test_init_adds_remote_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let opts = || InitOptions {
+            remote: Some("https://example.com/me/a4-core.git"),
+        };
+
+        assert!(init_vault(root, opts()).unwrap().remote_added);
+        assert!(!init_vault(root, opts()).unwrap().remote_added);
+
+        let other = InitOptions {
+            remote: Some("https://example.com/other.git"),
+        };
+        assert!(init_vault(root, other).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+use crate::anchors::AnchorToken;
+use crate::append::{append_block, AppendOptions};
+use crate::date::{Moment, UtcDay};
+use crate::error::A4Error;
+use crate::templates::{create_from_template, TemplateContext};
+use crate::vault::Vault;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Used when `routines/templates/journal-<session>.md` does not exist.
+pub const JOURNAL_TEMPLATE: &str = "---
+kind: {{kind}}
+created: {{now_utc}}
+date: {{date}}
+---
+# {{session_title}} Journal {{date}}
+";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JournalSession {
+    Morning,
+    Evening,
+}
+
+impl JournalSession {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JournalSession::Morning => "morning",
+            JournalSession::Evening => "evening",
+        }
+    }
+
+    /// Front matter `kind`, e.g. `journal.morning`.
+    pub fn kind(&self) -> String {
+        format!("journal.{}", self.as_str())
+    }
+
+    /// The day a session started at `now` is filed under. Evening sessions
+    /// follow `time.day_rollover_hour` on the local clock, so with `4` one
+    /// started at 01:30 counts toward the previous day; morning sessions
+    /// always take the day that began at local midnight.
+    pub fn effective_day(&self, now: &Moment) -> UtcDay {
+        match self {
+            JournalSession::Morning => now.day_with_rollover(0),
+            JournalSession::Evening => now.day(),
+        }
+    }
+}
+
+impl FromStr for JournalSession {
+    type Err = A4Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "morning" => Ok(JournalSession::Morning),
+            "evening" => Ok(JournalSession::Evening),
+            _ => Err(A4Error::InvalidJournalSession {
+                input: s.to_string(),
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for JournalSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The session file resolved or created by [`open_journal`].
+#[derive(Debug)]
+pub struct JournalEntry {
+    pub path: PathBuf,
+    pub day: UtcDay,
+    pub session: JournalSession,
+    pub created: bool,
+    /// The back-reference block written to the day's capture note, when the
+    /// session file was created.
+    pub backref: Option<AnchorToken>,
+}
+
+/// Resolve the journal file for `session` on `day` (its effective day when
+/// `None`), creating it from the session template if absent. A new file also
+/// gets a `^journal-HHMM` block linking to it in that day's capture note.
+pub fn open_journal(
+    vault: &Vault,
+    now: &Moment,
+    session: JournalSession,
+    day: Option<UtcDay>,
+) -> Result<JournalEntry, A4Error> {
+    let day = day.unwrap_or_else(|| session.effective_day(now));
+    let path = vault.journal_path(day.clone(), session);
+
+    let mut ctx = TemplateContext::for_day(vault, now, &day)?;
+    ctx.insert("session", session.as_str());
+    ctx.insert("kind", session.kind());
+    let title = match session {
+        JournalSession::Morning => "Morning",
+        JournalSession::Evening => "Evening",
+    };
+    ctx.insert("session_title", title);
+
+    let template_path = vault.journal_template_path(session);
+    let created = create_from_template(vault, &path, &template_path, JOURNAL_TEMPLATE, &ctx)?;
+
+    let backref = if created {
+        let capture = vault.capture_day_path(day.clone());
+        create_from_template(
+            vault,
+            &capture,
+            &vault.template_path(),
+            "\n",
+            &TemplateContext::for_day(vault, now, &day)?,
+        )?;
+
+        let rel = path.strip_prefix(vault.root()).unwrap_or(&path);
+        let target = rel.with_extension("");
+        let link = format!("[[{}|{title} journal]]", target.display());
+        let anchor = AnchorToken::parse(&format!("journal-{}", now.local_hhmm()))?;
+        let device = vault.device_name();
+        let opts = AppendOptions {
+            heading: vault.config().heading_for("journal").unwrap_or("Journal"),
+            anchor,
+            content: &link,
+            device: device.as_deref(),
+        };
+        Some(append_block(vault, &capture, opts)?)
+    } else {
+        None
+    };
+
+    Ok(JournalEntry {
+        path,
+        day,
+        session,
+        created,
+        backref,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::{Clock, FixedClock, Zone};
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn moment(hour: u8) -> Moment {
+        let instant = time::Date::from_calendar_date(2025, time::Month::September, 5)
+            .unwrap()
+            .with_hms(hour, 30, 0)
+            .unwrap()
+            .assume_utc();
+        Moment {
+            instant: FixedClock(instant).now_utc(),
+            filename_zone: Zone::Utc,
+            local_zone: Zone::Utc,
+            rollover_hour: 4,
+        }
+    }
+
+    fn day(session: JournalSession, now: &Moment) -> String {
+        session.effective_day(now).to_string()
+    }
+
+    #[test]
+    fn test_effective_day() {
+        use JournalSession::{Evening, Morning};
+
+        assert_eq!(day(Evening, &moment(1)), "2025-09-04");
+        assert_eq!(day(Morning, &moment(1)), "2025-09-05");
+        assert_eq!(day(Evening, &moment(22)), "2025-09-05");
+
+        // The evening rule is the vault's rollover hour, not a fixed one
+        let midnight = Moment {
+            rollover_hour: 0,
+            ..moment(1)
+        };
+        assert_eq!(day(Evening, &midnight), "2025-09-05");
+        assert!("noon".parse::<JournalSession>().is_err());
+    }
+
+    #[test]
+    fn test_effective_day_in_local_zone() {
+        use JournalSession::{Evening, Morning};
+        let la = Zone::named("America/Los_Angeles").unwrap();
+
+        // 08:30 UTC is 01:30 in Los Angeles (PDT), before the rollover
+        let late = Moment {
+            filename_zone: la,
+            local_zone: la,
+            ..moment(8)
+        };
+        assert_eq!(day(Evening, &late), "2025-09-04");
+        assert_eq!(day(Morning, &late), "2025-09-05");
+
+        // 01:30 UTC is 18:30 the evening before in Los Angeles: past the
+        // rollover there, although it is 01:30 in UTC
+        let early = Moment {
+            filename_zone: la,
+            local_zone: la,
+            ..moment(1)
+        };
+        assert_eq!(day(Evening, &early), "2025-09-04");
+        assert_eq!(day(Morning, &early), "2025-09-04");
+        let utc_names = Moment {
+            local_zone: la,
+            ..moment(1)
+        };
+        assert_eq!(day(Evening, &utc_names), "2025-09-05");
+
+        // With UTC filenames the 01:30 session maps to the note that was
+        // current at local midnight (07:00 UTC on the 5th)
+        let late = Moment {
+            local_zone: la,
+            ..moment(8)
+        };
+        assert_eq!(day(Evening, &late), "2025-09-05");
+    }
+
+    #[test]
+    fn test_open_journal_creates_file_and_backref() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let now = moment(1);
+
+        let entry = open_journal(&vault, &now, JournalSession::Evening, None).unwrap();
+        assert!(entry.created);
+        assert!(entry
+            .path
+            .ends_with("collections/journals/2025/09/journal-2025-09-04-evening.md"));
+        let content = std::fs::read_to_string(&entry.path).unwrap();
+        assert!(content.starts_with("---\nkind: journal.evening\n"));
+        assert!(content.contains("# Evening Journal 2025-09-04\n"));
+
+        let marker = entry.backref.unwrap().to_marker();
+        assert_eq!(marker, "^journal-0130");
+        let capture =
+            std::fs::read_to_string(vault.capture_day_path(UtcDay::parse("2025-09-04").unwrap()))
+                .unwrap();
+        assert!(capture.contains(
+            "## Journal\n\n^journal-0130\n\n[[collections/journals/2025/09/journal-2025-09-04-evening|Evening journal]]\n"
+        ));
+
+        // Opening it again only resolves the path
+        let again = open_journal(&vault, &now, JournalSession::Evening, None).unwrap();
+        assert!(!again.created);
+        assert!(again.backref.is_none());
+    }
+}
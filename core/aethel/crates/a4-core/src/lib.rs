@@ -1,16 +1,53 @@
 pub mod anchors;
 pub mod append;
+pub mod blocks;
+pub mod collate;
+pub mod config;
 pub mod date;
+pub mod dominoes;
 pub mod error;
+pub mod front_matter;
 pub mod git_backend;
 pub mod headings;
+pub mod inbox;
+pub mod ingest;
+pub mod init;
+pub mod journal;
+pub mod merge;
 pub mod notes;
+pub mod projects;
+pub mod search;
+pub mod stitch;
+pub mod templates;
 pub mod util;
 pub mod vault;
 
 pub use anchors::AnchorToken;
 pub use append::{append_block, AppendOptions};
-pub use date::{IsoWeek, LocalClock, UtcDay};
+pub use blocks::{find_block, AnchoredBlock};
+pub use collate::{collate, CollatedBlock};
+pub use config::{TimezonePolicy, VaultConfig};
+pub use date::{Clock, FixedClock, IsoWeek, LocalClock, Moment, SystemClock, UtcDay, Zone};
+pub use dominoes::{
+    add_domino, complete_domino, find_project, generate_id, lint_project, list_projects,
+    migrate_project, next_dominoes, open_project_at, project_path, project_status, render_graph,
+    render_status, DominoStatus, GraphSyntax, LintIssue, ProjectStatus,
+};
 pub use error::A4Error;
-pub use git_backend::{GitBackend, RebaseResult};
+pub use front_matter::{read_front_matter, set_front_matter_field, FrontMatter, Origin};
+pub use git_backend::{FastForward, GitBackend, RebaseResult};
+pub use inbox::{paste, triage, untriaged, InboxBlock, TriageTarget};
+pub use ingest::{
+    append_highlight, ingest_article, slugify, write_article, IngestOptions, IngestedArticle,
+};
+pub use init::{init_vault, InitOptions, InitReport};
+pub use journal::{open_journal, JournalEntry, JournalSession};
+pub use merge::union_merge;
+pub use projects::{
+    create_project, log_project, project_summary, validate_slug, Project, ProjectDomino,
+    ProjectLogEntry, ProjectSummary,
+};
+pub use search::{search, SearchHit, SearchQuery};
+pub use stitch::{stitch_note, Transclusion};
+pub use templates::{render, TemplateContext};
 pub use vault::{Vault, VaultOpts, VaultRoot};
@@ -0,0 +1,277 @@
+use crate::anchors::AnchorToken;
+use crate::blocks::{parse_anchor_line, parse_heading};
+
+/// A run of appended lines that starts at an anchor marker, or the loose text
+/// before the first anchor of a section.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Chunk {
+    token: Option<AnchorToken>,
+    text: String,
+}
+
+/// Appended content under one heading. `heading` is `None` for blocks
+/// appended under whatever heading the base version ends with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Section {
+    heading: Option<String>,
+    chunks: Vec<Chunk>,
+}
+
+/// Merge two versions of an append-only note that both grew from `base`.
+///
+/// Each side's appended tail is split into anchored blocks, and blocks are
+/// unioned per heading in anchor time order. The day runs from
+/// `rollover_hour` (`time.day_rollover_hour`), so with `3` an `^eod-0030`
+/// block sorts after `^eod-2215`. Returns `None` when either side
+/// changed bytes that already existed in `base`, or when both sides wrote the
+/// same anchor with different content. When the file was added on both sides
+/// (`base` is `None`), their common leading lines are treated as the base.
+pub fn union_merge(
+    base: Option<&str>,
+    ours: &str,
+    theirs: &str,
+    rollover_hour: u8,
+) -> Option<String> {
+    if ours == theirs || Some(theirs) == base {
+        return Some(ours.to_string());
+    }
+    if Some(ours) == base {
+        return Some(theirs.to_string());
+    }
+
+    let base = match base {
+        Some(base) => base,
+        None => common_leading_lines(ours, theirs),
+    };
+    let base = base.trim_end_matches('\n');
+
+    let our_tail = appended_tail(base, ours)?;
+    let their_tail = appended_tail(base, theirs)?;
+
+    let mut sections = parse_tail(our_tail);
+    for section in parse_tail(their_tail) {
+        union_section(&mut sections, section)?;
+    }
+
+    let mut merged = base.to_string();
+    for section in &mut sections {
+        // Loose text stays first; anchored blocks are ordered by their HHMM
+        section.chunks.sort_by_key(|chunk| {
+            chunk
+                .token
+                .as_ref()
+                .map(|t| minutes_into_day(&t.hhmm, rollover_hour))
+        });
+
+        if let Some(heading) = &section.heading {
+            merged.push_str("\n\n");
+            merged.push_str(heading);
+        }
+        for chunk in &section.chunks {
+            merged.push_str("\n\n");
+            merged.push_str(&chunk.text);
+        }
+    }
+    merged.push('\n');
+
+    Some(merged)
+}
+
+/// Minutes from the start of a day that begins at `rollover_hour` to the
+/// `HHMM` time of an anchor.
+fn minutes_into_day(hhmm: &str, rollover_hour: u8) -> u32 {
+    let hours: u32 = hhmm.get(..2).and_then(|h| h.parse().ok()).unwrap_or(0);
+    let minutes: u32 = hhmm.get(2..).and_then(|m| m.parse().ok()).unwrap_or(0);
+    (hours * 60 + minutes + 24 * 60 - u32::from(rollover_hour) * 60) % (24 * 60)
+}
+
+/// The part of `version` after `base`, provided `base` was left untouched and
+/// the addition starts on a new line.
+fn appended_tail<'a>(base: &str, version: &'a str) -> Option<&'a str> {
+    let tail = version.strip_prefix(base)?;
+    if base.is_empty() || tail.is_empty() || tail.starts_with('\n') {
+        Some(tail)
+    } else {
+        None
+    }
+}
+
+fn common_leading_lines<'a>(a: &'a str, b: &str) -> &'a str {
+    let mut end = 0;
+    for ((i, x), y) in a.char_indices().zip(b.chars()) {
+        if x != y {
+            break;
+        }
+        if x == '\n' {
+            end = i + 1;
+        }
+    }
+    &a[..end]
+}
+
+fn parse_tail(tail: &str) -> Vec<Section> {
+    let mut sections = vec![Section {
+        heading: None,
+        chunks: Vec::new(),
+    }];
+    let mut current: Option<(Option<AnchorToken>, Vec<&str>)> = None;
+    let mut in_fence = false;
+
+    fn flush(current: &mut Option<(Option<AnchorToken>, Vec<&str>)>, section: &mut Section) {
+        if let Some((token, lines)) = current.take() {
+            let text = lines.join("\n").trim_matches('\n').to_string();
+            if token.is_some() || !text.trim().is_empty() {
+                section.chunks.push(Chunk { token, text });
+            }
+        }
+    }
+
+    for line in tail.lines() {
+        let trimmed = line.trim_start();
+        let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+
+        if !in_fence {
+            if parse_heading(line).is_some() {
+                flush(&mut current, sections.last_mut().unwrap());
+                sections.push(Section {
+                    heading: Some(line.trim_end().to_string()),
+                    chunks: Vec::new(),
+                });
+                continue;
+            }
+            if let Some(token) = parse_anchor_line(line) {
+                flush(&mut current, sections.last_mut().unwrap());
+                current = Some((Some(token), vec![line.trim()]));
+                continue;
+            }
+        }
+
+        if is_fence {
+            in_fence = !in_fence;
+        }
+        current.get_or_insert((None, Vec::new())).1.push(line);
+    }
+    flush(&mut current, sections.last_mut().unwrap());
+
+    sections
+}
+
+fn same_heading(a: &Option<String>, b: &Option<String>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => a.trim().eq_ignore_ascii_case(b.trim()),
+        _ => false,
+    }
+}
+
+fn union_section(sections: &mut Vec<Section>, incoming: Section) -> Option<()> {
+    let Some(existing) = sections
+        .iter_mut()
+        .find(|s| same_heading(&s.heading, &incoming.heading))
+    else {
+        sections.push(incoming);
+        return Some(());
+    };
+
+    for chunk in incoming.chunks {
+        let duplicate = existing.chunks.iter().find(|c| match &chunk.token {
+            Some(token) => c.token.as_ref() == Some(token),
+            None => c.token.is_none() && c.text == chunk.text,
+        });
+
+        match duplicate {
+            // The same anchor written twice must carry the same block
+            Some(c) if c.text != chunk.text => return None,
+            Some(_) => {}
+            None => existing.chunks.push(chunk),
+        }
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "# 2025-09-14\n\n## Focus\n\n^focus-0900\n\nMorning plan\n";
+
+    #[test]
+    fn test_union_of_blocks_under_existing_heading() {
+        let ours = format!("{BASE}\n^focus-1100__laptop\n\nLate morning\n");
+        let theirs = format!("{BASE}\n^focus-0930__iphone\n\nOn the train\n");
+
+        let merged = union_merge(Some(BASE), &ours, &theirs, 0).unwrap();
+        assert_eq!(
+            merged,
+            "# 2025-09-14\n\n## Focus\n\n^focus-0900\n\nMorning plan\n\n\
+             ^focus-0930__iphone\n\nOn the train\n\n\
+             ^focus-1100__laptop\n\nLate morning\n"
+        );
+    }
+
+    #[test]
+    fn test_union_groups_new_headings() {
+        let ours = format!("{BASE}\n## End of Day\n\n^eod-2215\n\nWrapped up\n");
+        let theirs = format!(
+            "{BASE}\n## Reading\n\n^read-1300\n\nAn article\n\n## End of Day\n\n^eod-2100__iphone\n\nTired\n"
+        );
+
+        let merged = union_merge(Some(BASE), &ours, &theirs, 0).unwrap();
+        assert_eq!(
+            merged,
+            "# 2025-09-14\n\n## Focus\n\n^focus-0900\n\nMorning plan\n\n\
+             ## End of Day\n\n^eod-2100__iphone\n\nTired\n\n^eod-2215\n\nWrapped up\n\n\
+             ## Reading\n\n^read-1300\n\nAn article\n"
+        );
+    }
+
+    #[test]
+    fn test_union_when_both_sides_create_file() {
+        let ours = "\n\n## Focus\n\n^focus-1000\n\nDesk\n";
+        let theirs = "\n\n## Focus\n\n^focus-0800__iphone\n\nCouch\n";
+
+        let merged = union_merge(None, ours, theirs, 0).unwrap();
+        assert_eq!(
+            merged,
+            "\n\n## Focus\n\n^focus-0800__iphone\n\nCouch\n\n^focus-1000\n\nDesk\n"
+        );
+    }
+
+    #[test]
+    fn test_rewritten_base_is_a_conflict() {
+        let ours = BASE.replace("Morning plan", "Edited plan");
+        let theirs = format!("{BASE}\n^focus-0930\n\nMore\n");
+        assert_eq!(union_merge(Some(BASE), &ours, &theirs, 0), None);
+
+        let ours = format!("{BASE}\n^focus-0930\n\nMine\n");
+        let theirs = format!("{BASE}\n^focus-0930\n\nTheirs\n");
+        assert_eq!(union_merge(Some(BASE), &ours, &theirs, 0), None);
+    }
+
+    #[test]
+    fn test_blocks_after_midnight_end_the_day() {
+        let ours = format!("{BASE}\n## End of Day\n\n^eod-0030\n\nStill up\n");
+        let theirs = format!("{BASE}\n## End of Day\n\n^eod-2215__iphone\n\nWinding down\n");
+
+        let merged = union_merge(Some(BASE), &ours, &theirs, 3).unwrap();
+        assert!(merged.ends_with(
+            "## End of Day\n\n^eod-2215__iphone\n\nWinding down\n\n^eod-0030\n\nStill up\n"
+        ));
+
+        // Without a rollover, 00:30 is the start of the day
+        let merged = union_merge(Some(BASE), &ours, &theirs, 0).unwrap();
+        assert!(merged.ends_with(
+            "## End of Day\n\n^eod-0030\n\nStill up\n\n^eod-2215__iphone\n\nWinding down\n"
+        ));
+    }
+
+    #[test]
+    fn test_identical_appends_are_deduplicated() {
+        let ours = format!("{BASE}\n^focus-0930\n\nSame\n");
+        let theirs = format!("{BASE}\n^focus-0930\n\nSame\n\n^focus-1000\n\nExtra\n");
+
+        let merged = union_merge(Some(BASE), &ours, &theirs, 0).unwrap();
+        assert_eq!(merged, theirs);
+    }
+}
@@ -33,8 +33,13 @@ pub fn write_note(path: &Path, body: &str) -> Result<(), A4Error> {
     Ok(())
 }
 
+/// Whether `raw` opens a front matter block, closed or not.
+pub fn opens_front_matter(raw: &str) -> bool {
+    raw.starts_with("---\n") || raw.starts_with("---\r\n")
+}
+
 pub fn split_front_matter(raw: &str) -> (Option<&str>, &str) {
-    if !raw.starts_with("---\n") && !raw.starts_with("---\r\n") {
+    if !opens_front_matter(raw) {
         return (None, raw);
     }
 
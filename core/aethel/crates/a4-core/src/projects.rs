@@ -0,0 +1,487 @@
+use crate::anchors::AnchorToken;
+use crate::append::{append_block, AppendOptions};
+use crate::blocks::anchored_blocks;
+use crate::date::{Moment, UtcDay};
+use crate::dominoes::log_timestamp;
+use crate::error::A4Error;
+use crate::front_matter::{at, FrontMatter};
+use crate::notes::read_note;
+use crate::templates::{create_from_template, TemplateContext};
+use crate::vault::Vault;
+use fs_err as fs;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+static SLUG_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Used when `routines/templates/project.md` does not exist.
+pub const PROJECT_TEMPLATE: &str = "---
+kind: hub.project
+created: {{now_utc}}
+status: active
+dominoes: []
+---
+# {{title}}
+
+## Goal
+
+## Links
+
+- [[projects/{{slug}}/log|Log]]
+";
+
+/// Check `slug` against protocol §17: `[a-z0-9][a-z0-9-]{1,63}`.
+pub fn validate_slug(slug: &str) -> Result<(), A4Error> {
+    let re = SLUG_REGEX.get_or_init(|| Regex::new(r"^[a-z0-9][a-z0-9-]{1,63}$").unwrap());
+    if re.is_match(slug) {
+        Ok(())
+    } else {
+        Err(A4Error::InvalidSlug {
+            input: slug.to_string(),
+        })
+    }
+}
+
+/// A project hub under `projects/<slug>/`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Project {
+    pub slug: String,
+    pub index: PathBuf,
+    pub log: PathBuf,
+}
+
+impl Project {
+    /// Resolve the hub for `slug`; it need not exist yet.
+    pub fn resolve(vault: &Vault, slug: &str) -> Result<Self, A4Error> {
+        validate_slug(slug)?;
+        Ok(Project {
+            slug: slug.to_string(),
+            index: vault.project_index_path(slug),
+            log: vault.project_log_path(slug),
+        })
+    }
+
+    /// Resolve the hub for `slug`, failing if its `index.md` is missing.
+    pub fn open(vault: &Vault, slug: &str) -> Result<Self, A4Error> {
+        let project = Project::resolve(vault, slug)?;
+        if !project.index.exists() {
+            return Err(A4Error::ProjectNotFound {
+                slug: slug.to_string(),
+                path: project.index,
+            });
+        }
+        Ok(project)
+    }
+
+    /// The hub whose index is `index`, wherever it lives. The slug is its
+    /// directory name.
+    pub fn at(index: &Path) -> Self {
+        let slug = index.parent().and_then(|p| p.file_name()).map_or_else(
+            || "unknown".to_string(),
+            |s| s.to_string_lossy().into_owned(),
+        );
+        Project {
+            slug,
+            index: index.to_path_buf(),
+            log: index.with_file_name("log.md"),
+        }
+    }
+
+    /// The `log.jsonl` kept beside the index by `domino-id`.
+    pub fn jsonl_log(&self) -> PathBuf {
+        self.index.with_file_name("log.jsonl")
+    }
+}
+
+/// An entry in the project's `dominoes:` front matter list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProjectDomino {
+    pub id: Option<String>,
+    pub task: String,
+    /// `done: true` or `status: done`
+    pub done: bool,
+    /// IDs of the dominoes that must fall first
+    pub after: Vec<String>,
+    /// Free-form, e.g. `2h` or `3`
+    pub estimate: Option<String>,
+    /// Free-form, e.g. `doing` or `blocked`
+    pub status: Option<String>,
+}
+
+/// One dated entry from `log.md` or `log.jsonl`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProjectLogEntry {
+    /// When the entry was written, in UTC
+    pub at: OffsetDateTime,
+    pub what: String,
+    pub dominoes: Vec<String>,
+}
+
+impl ProjectLogEntry {
+    /// `at` as RFC 3339, as `log.jsonl` writes it.
+    pub fn ts(&self) -> String {
+        log_timestamp(self.at)
+    }
+}
+
+/// What `a4 project show` prints.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProjectSummary {
+    pub project: Project,
+    pub title: String,
+    pub status: Option<String>,
+    pub open: Vec<ProjectDomino>,
+    /// Newest first
+    pub recent: Vec<ProjectLogEntry>,
+}
+
+/// Create `projects/<slug>/index.md` from the `hub.project` template if it
+/// does not exist. Returns the project and whether the index was created.
+pub fn create_project(
+    vault: &Vault,
+    now: &Moment,
+    slug: &str,
+    title: Option<&str>,
+) -> Result<(Project, bool), A4Error> {
+    let project = Project::resolve(vault, slug)?;
+    let mut ctx = TemplateContext::for_day(vault, now, &now.day())?;
+    ctx.insert("slug", slug);
+    ctx.insert("title", title.unwrap_or(slug));
+    let created = create_from_template(
+        vault,
+        &project.index,
+        &vault.project_template_path(),
+        PROJECT_TEMPLATE,
+        &ctx,
+    )?;
+    Ok((project, created))
+}
+
+/// Append `what` to the project's `log.md` as a `^log-HHMM` block under a
+/// `## YYYY-MM-DD` heading for `now`'s day. Earlier entries are never
+/// rewritten.
+pub fn log_project(
+    vault: &Vault,
+    now: &Moment,
+    project: &Project,
+    what: &str,
+) -> Result<AnchorToken, A4Error> {
+    if !project.log.exists() {
+        vault.ensure_parents(&project.log)?;
+        fs::write(&project.log, format!("# {} log\n", project.slug))?;
+    }
+
+    let day = now.day().to_string();
+    let device = vault.device_name();
+    let opts = AppendOptions {
+        heading: &day,
+        anchor: AnchorToken::parse(&format!("log-{}", now.local_hhmm()))?,
+        content: what,
+        device: device.as_deref(),
+    };
+    append_block(vault, &project.log, opts)
+}
+
+/// Title, status, open dominoes and the `limit` most recent log entries
+/// from both `log.md` and `log.jsonl`. A domino is done when its entry says
+/// `done: true` or a `log.jsonl` line lists its ID. `log.md` entries are
+/// dated by their daily heading and local `HHMM`, read through the zones
+/// and rollover of `now`, so they order correctly against UTC `log.jsonl`
+/// timestamps.
+pub fn project_summary(
+    project: &Project,
+    now: &Moment,
+    limit: usize,
+) -> Result<ProjectSummary, A4Error> {
+    let index = read_note(&project.index)?;
+    let front: IndexFrontMatter = FrontMatter::from_note(&index)?
+        .deserialize()
+        .map_err(|e| at(&project.index, e))?;
+
+    let mut log = read_log_md(project, now)?;
+    log.extend(read_log_jsonl(project)?);
+    log.sort_by(|a, b| b.at.cmp(&a.at));
+
+    let completed: HashSet<&str> = log
+        .iter()
+        .flat_map(|e| e.dominoes.iter().map(String::as_str))
+        .collect();
+    let open = front
+        .dominoes
+        .unwrap_or_default()
+        .into_iter()
+        .map(RawDomino::into_domino)
+        .filter(|d| !d.done && !d.id.as_deref().is_some_and(|id| completed.contains(id)))
+        .collect();
+
+    let title = index
+        .body
+        .lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(|t| t.trim().to_string())
+        .unwrap_or_else(|| project.slug.clone());
+
+    log.truncate(limit);
+    Ok(ProjectSummary {
+        project: project.clone(),
+        title,
+        status: front.status,
+        open,
+        recent: log,
+    })
+}
+
+fn read_log_md(project: &Project, now: &Moment) -> Result<Vec<ProjectLogEntry>, A4Error> {
+    if !project.log.exists() {
+        return Ok(Vec::new());
+    }
+
+    let note = read_note(&project.log)?;
+    Ok(anchored_blocks(&note.body)
+        .into_iter()
+        .filter_map(|block| {
+            let day = block
+                .heading_path
+                .iter()
+                .rev()
+                .find_map(|h| UtcDay::parse(h.trim()).ok())?;
+            Some(ProjectLogEntry {
+                at: now.instant_for(&day, &block.token.hhmm)?,
+                what: block.content,
+                dominoes: Vec::new(),
+            })
+        })
+        .collect())
+}
+
+fn read_log_jsonl(project: &Project) -> Result<Vec<ProjectLogEntry>, A4Error> {
+    let path = project.jsonl_log();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let raw = fs::read_to_string(&path)?;
+    // Lines without a valid `ts` cannot be placed; lint reports them
+    Ok(raw
+        .lines()
+        .filter_map(|line| serde_json::from_str::<JsonlEntry>(line).ok())
+        .filter_map(|entry| {
+            Some(ProjectLogEntry {
+                at: OffsetDateTime::parse(&entry.ts, &Rfc3339).ok()?,
+                what: entry.what,
+                dominoes: entry.dominoes.unwrap_or_default(),
+            })
+        })
+        .collect())
+}
+
+#[derive(Default, Deserialize)]
+pub(crate) struct IndexFrontMatter {
+    pub(crate) created: Option<serde_yaml::Value>,
+    pub(crate) status: Option<String>,
+    pub(crate) dominoes: Option<Vec<RawDomino>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum RawDomino {
+    Simple(String),
+    Structured {
+        id: Option<String>,
+        task: String,
+        #[serde(default)]
+        done: bool,
+        #[serde(default)]
+        after: OneOrMany,
+        estimate: Option<serde_yaml::Value>,
+        status: Option<String>,
+    },
+}
+
+/// `after: d-abc` or `after: [d-abc, d-def]`.
+#[derive(Default, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum OneOrMany {
+    #[default]
+    None,
+    One(String),
+    Many(Vec<String>),
+}
+
+impl RawDomino {
+    pub(crate) fn into_domino(self) -> ProjectDomino {
+        match self {
+            RawDomino::Simple(task) => ProjectDomino {
+                id: None,
+                task,
+                done: false,
+                after: Vec::new(),
+                estimate: None,
+                status: None,
+            },
+            RawDomino::Structured {
+                id,
+                task,
+                done,
+                after,
+                estimate,
+                status,
+            } => ProjectDomino {
+                id,
+                task,
+                done: done || status.as_deref() == Some("done"),
+                after: match after {
+                    OneOrMany::None => Vec::new(),
+                    OneOrMany::One(id) => vec![id],
+                    OneOrMany::Many(ids) => ids,
+                },
+                estimate: estimate.and_then(|value| match value {
+                    serde_yaml::Value::String(s) => Some(s),
+                    serde_yaml::Value::Number(n) => Some(n.to_string()),
+                    _ => None,
+                }),
+                status,
+            },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonlEntry {
+    ts: String,
+    what: String,
+    dominoes: Option<Vec<String>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::{Clock, FixedClock, Zone};
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn moment(day: u8, hour: u8) -> Moment {
+        let instant = time::Date::from_calendar_date(2026, time::Month::January, day)
+            .unwrap()
+            .with_hms(hour, 0, 0)
+            .unwrap()
+            .assume_utc();
+        Moment {
+            instant: FixedClock(instant).now_utc(),
+            filename_zone: Zone::Utc,
+            local_zone: Zone::Utc,
+            rollover_hour: 0,
+        }
+    }
+
+    #[test]
+    fn test_validate_slug() {
+        assert!(validate_slug("gb-ppu").is_ok());
+        assert!(validate_slug("a4").is_ok());
+        assert!(validate_slug("x").is_err());
+        assert!(validate_slug("-leading").is_err());
+        assert!(validate_slug("Has Caps").is_err());
+        assert!(validate_slug("../escape").is_err());
+    }
+
+    #[test]
+    fn test_project_new_log_and_summary() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+
+        let (project, created) =
+            create_project(&vault, &moment(19, 9), "gb-ppu", Some("Game Boy PPU")).unwrap();
+        assert!(created);
+        assert!(project.index.ends_with("projects/gb-ppu/index.md"));
+        let index = std::fs::read_to_string(&project.index).unwrap();
+        assert!(index.starts_with("---\nkind: hub.project\ncreated: 2026-01-19T09:00:00"));
+        assert!(index.contains("# Game Boy PPU\n"));
+        assert!(
+            !create_project(&vault, &moment(19, 9), "gb-ppu", None)
+                .unwrap()
+                .1
+        );
+
+        // Give the hub some dominoes, one already completed in log.jsonl
+        std::fs::write(
+            &project.index,
+            index.replace(
+                "dominoes: []",
+                "dominoes:\n  - id: d-abc\n    task: Render tiles\n  - id: d-xyz\n    task: Sprites\n  - Timing notes\n  - id: d-old\n    task: Setup\n    done: true",
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            project.jsonl_log(),
+            "{\"ts\":\"2026-01-20T12:00:00Z\",\"what\":\"Sprites work\",\"dominoes\":[\"d-xyz\"]}\n",
+        )
+        .unwrap();
+
+        log_project(&vault, &moment(19, 10), &project, "Read the pan docs").unwrap();
+        log_project(&vault, &moment(21, 8), &project, "Tile fetcher").unwrap();
+        let log = std::fs::read_to_string(&project.log).unwrap();
+        assert!(log.starts_with("# gb-ppu log\n"));
+        assert!(log.contains("## 2026-01-19\n\n^log-1000\n\nRead the pan docs\n"));
+        assert!(log.ends_with("## 2026-01-21\n\n^log-0800\n\nTile fetcher\n"));
+
+        let summary = project_summary(&project, &moment(21, 9), 2).unwrap();
+        assert_eq!(summary.title, "Game Boy PPU");
+        assert_eq!(summary.status.as_deref(), Some("active"));
+        let open: Vec<_> = summary.open.iter().map(|d| d.task.as_str()).collect();
+        assert_eq!(open, ["Render tiles", "Timing notes"]);
+        let recent: Vec<_> = summary.recent.iter().map(ProjectLogEntry::ts).collect();
+        assert_eq!(recent, ["2026-01-21T08:00:00Z", "2026-01-20T12:00:00Z"]);
+    }
+
+    #[test]
+    fn test_summary_orders_log_md_by_instant() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let (project, _) = create_project(&vault, &moment(19, 9), "gb-ppu", None).unwrap();
+
+        // 07:00 UTC on the 21st is 23:00 on the 20th in Los Angeles: filed
+        // under the UTC day as `^log-2300`, but written before the
+        // log.jsonl entry at 12:00 UTC
+        let la = Moment {
+            local_zone: Zone::named("America/Los_Angeles").unwrap(),
+            ..moment(21, 7)
+        };
+        log_project(&vault, &la, &project, "Late night").unwrap();
+        let log = std::fs::read_to_string(&project.log).unwrap();
+        assert!(log.contains("## 2026-01-21\n\n^log-2300\n"));
+        std::fs::write(
+            project.jsonl_log(),
+            "{\"ts\":\"2026-01-21T12:00:00Z\",\"what\":\"Lunch\"}\n{\"ts\":\"yesterday\",\"what\":\"?\"}\n",
+        )
+        .unwrap();
+
+        let summary = project_summary(&project, &la, 5).unwrap();
+        let recent: Vec<_> = summary
+            .recent
+            .iter()
+            .map(|e| (e.ts(), e.what.as_str()))
+            .collect();
+        assert_eq!(
+            recent,
+            [
+                ("2026-01-21T12:00:00Z".to_string(), "Lunch"),
+                ("2026-01-21T07:00:00Z".to_string(), "Late night"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_open_missing_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        assert!(matches!(
+            Project::open(&vault, "nope"),
+            Err(A4Error::ProjectNotFound { .. })
+        ));
+    }
+}
@@ -0,0 +1,272 @@
+use crate::anchors::AnchorToken;
+use crate::blocks::{parse_segments, Segment};
+use crate::date::UtcDay;
+use crate::error::A4Error;
+use crate::front_matter::{render_value, FrontMatter};
+use crate::notes::read_note;
+use crate::vault::Vault;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static DAY_FILENAME_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Filters for [`search`]. Unset fields match everything.
+#[derive(Clone, Debug, Default)]
+pub struct SearchQuery {
+    /// Case-insensitive text the block must contain.
+    pub text: Option<String>,
+    /// Only blocks under an anchor with this prefix.
+    pub prefix: Option<String>,
+    /// Inclusive range of note days; notes without a day never match.
+    pub from: Option<UtcDay>,
+    pub to: Option<UtcDay>,
+    /// Front-matter `kind` of the note.
+    pub kind: Option<String>,
+    /// Case-insensitive heading anywhere in the block's heading path.
+    pub heading: Option<String>,
+}
+
+/// One matching block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchHit {
+    /// Path relative to the vault root.
+    pub path: PathBuf,
+    /// 1-based line in the file where the block starts.
+    pub line: usize,
+    pub heading_path: Vec<String>,
+    pub anchor: Option<AnchorToken>,
+    /// UTC day of the note, from a `YYYY-MM-DD` filename or `created`.
+    pub day: Option<UtcDay>,
+    pub kind: Option<String>,
+    pub text: String,
+}
+
+impl SearchQuery {
+    fn wants_day(&self) -> bool {
+        self.from.is_some() || self.to.is_some()
+    }
+
+    fn matches_day(&self, day: Option<&UtcDay>) -> bool {
+        if !self.wants_day() {
+            return true;
+        }
+        let Some(day) = day else {
+            return false;
+        };
+        self.from.as_ref().is_none_or(|from| day >= from)
+            && self.to.as_ref().is_none_or(|to| day <= to)
+    }
+}
+
+/// Search every Markdown note in the vault. Honors `.gitignore` and skips
+/// hidden directories such as `.git` and `.a4`. Results are ordered by path,
+/// then line.
+pub fn search(vault: &Vault, query: &SearchQuery) -> Result<Vec<SearchHit>, A4Error> {
+    let walker = ignore::WalkBuilder::new(vault.root())
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+
+    let mut hits = Vec::new();
+    for entry in walker {
+        let entry = entry.map_err(|e| A4Error::Io(std::io::Error::other(e)))?;
+        let path = entry.path();
+        let is_markdown = path.extension().is_some_and(|ext| ext == "md");
+        if !entry.file_type().is_some_and(|t| t.is_file()) || !is_markdown {
+            continue;
+        }
+
+        hits.extend(search_note(vault, path, query)?);
+    }
+
+    Ok(hits)
+}
+
+fn search_note(vault: &Vault, path: &Path, query: &SearchQuery) -> Result<Vec<SearchHit>, A4Error> {
+    let note = read_note(path)?;
+    // Front matter is optional metadata: search notes whose YAML is broken
+    // as if they had none
+    let front_matter = FrontMatter::from_note(&note).unwrap_or_default();
+    let kind = front_matter.get("kind").map(render_value);
+    if query.kind.is_some() && kind != query.kind {
+        return Ok(Vec::new());
+    }
+
+    let day = day_from_filename(path).or_else(|| {
+        front_matter
+            .get("created")
+            .map(render_value)
+            .and_then(|created| UtcDay::parse(created.get(..10)?).ok())
+    });
+    if !query.matches_day(day.as_ref()) {
+        return Ok(Vec::new());
+    }
+
+    let line_offset = note
+        .front_matter
+        .as_deref()
+        .map_or(0, |fm| fm.lines().count());
+    let rel_path = path
+        .strip_prefix(vault.root())
+        .unwrap_or(path)
+        .to_path_buf();
+    let needle = query.text.as_deref().map(str::to_lowercase);
+    let heading_filter = query.heading.as_deref().map(str::to_lowercase);
+
+    let mut hits = Vec::new();
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    let mut anchor: Option<AnchorToken> = None;
+
+    for segment in parse_segments(&note.body) {
+        let (line, text) = match segment {
+            Segment::Heading { level, text, .. } => {
+                headings.retain(|(l, _)| *l < level);
+                headings.push((level, text));
+                anchor = None;
+                continue;
+            }
+            Segment::Anchor { token, .. } => {
+                anchor = Some(token);
+                continue;
+            }
+            Segment::Block { line, text } => (line, text),
+        };
+        // An anchor only owns the block right after it
+        let block_anchor = anchor.take();
+
+        if let Some(prefix) = &query.prefix {
+            if block_anchor.as_ref().is_none_or(|a| &a.prefix != prefix) {
+                continue;
+            }
+        }
+        if let Some(heading) = &heading_filter {
+            if !headings.iter().any(|(_, h)| h.to_lowercase() == *heading) {
+                continue;
+            }
+        }
+        if let Some(needle) = &needle {
+            if !text.to_lowercase().contains(needle) {
+                continue;
+            }
+        }
+
+        hits.push(SearchHit {
+            path: rel_path.clone(),
+            line: line + line_offset,
+            heading_path: headings.iter().map(|(_, h)| h.clone()).collect(),
+            anchor: block_anchor,
+            day: day.clone(),
+            kind: kind.clone(),
+            text,
+        });
+    }
+
+    Ok(hits)
+}
+
+pub(crate) fn day_from_filename(path: &Path) -> Option<UtcDay> {
+    let re = DAY_FILENAME_REGEX.get_or_init(|| Regex::new(r"^(\d{4}-\d{2}-\d{2})").unwrap());
+    let stem = path.file_stem()?.to_str()?;
+    UtcDay::parse(&re.captures(stem)?[1]).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn vault() -> (TempDir, Vault) {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(
+            root,
+            "capture/2025/2025-09/2025-09-14.md",
+            "---\nkind: capture.day\n---\n# Day\n\n## End of Day\n\n^eod-2215\n\nShipped the PPU\n\n## Focus\n\n^focus-0930\n\nPPU timing\n",
+        );
+        write(
+            root,
+            "capture/2025/2025-09/2025-09-15.md",
+            "## End of Day\n\n^eod-2300\n\nRested\n",
+        );
+        write(
+            root,
+            "sources/articles/ppu.md",
+            "---\nkind: source.article\ncreated: 2025-09-10T08:00:00Z\n---\n## Notes\n\nAll about the PPU\n",
+        );
+        write(root, "drafts/secret.md", "PPU secret\n");
+        write(root, ".gitignore", "drafts/\n");
+        write(root, ".a4/cache/index.md", "PPU cache\n");
+        let vault = Vault::open(root, VaultOpts::default()).unwrap();
+        (temp_dir, vault)
+    }
+
+    #[test]
+    fn test_text_search_respects_ignores() {
+        let (_temp_dir, vault) = vault();
+        let query = SearchQuery {
+            text: Some("ppu".to_string()),
+            ..Default::default()
+        };
+
+        let hits = search(&vault, &query).unwrap();
+        let paths: Vec<_> = hits.iter().map(|h| h.path.display().to_string()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "capture/2025/2025-09/2025-09-14.md",
+                "capture/2025/2025-09/2025-09-14.md",
+                "sources/articles/ppu.md",
+            ]
+        );
+
+        let first = &hits[0];
+        assert_eq!(first.line, 10);
+        assert_eq!(first.heading_path, vec!["Day", "End of Day"]);
+        assert_eq!(first.anchor, Some(AnchorToken::parse("eod-2215").unwrap()));
+        assert_eq!(first.day, Some(UtcDay::parse("2025-09-14").unwrap()));
+        assert_eq!(first.kind.as_deref(), Some("capture.day"));
+        assert_eq!(first.text, "Shipped the PPU");
+
+        assert_eq!(hits[2].day, Some(UtcDay::parse("2025-09-10").unwrap()));
+    }
+
+    #[test]
+    fn test_structured_filters() {
+        let (_temp_dir, vault) = vault();
+
+        let query = SearchQuery {
+            prefix: Some("eod".to_string()),
+            from: Some(UtcDay::parse("2025-09-15").unwrap()),
+            ..Default::default()
+        };
+        let hits = search(&vault, &query).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].text, "Rested");
+
+        let query = SearchQuery {
+            kind: Some("source.article".to_string()),
+            heading: Some("notes".to_string()),
+            ..Default::default()
+        };
+        let hits = search(&vault, &query).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].anchor, None);
+
+        let query = SearchQuery {
+            heading: Some("Focus".to_string()),
+            to: Some(UtcDay::parse("2025-09-14").unwrap()),
+            ..Default::default()
+        };
+        let hits = search(&vault, &query).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].text, "PPU timing");
+    }
+}
@@ -0,0 +1,434 @@
+use crate::anchors::AnchorToken;
+use crate::blocks::{find_block, parse_heading};
+use crate::date::UtcDay;
+use crate::error::A4Error;
+use crate::notes::read_note;
+use crate::util::safe_join;
+use crate::vault::Vault;
+use fs_err as fs;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static EMBED_REGEX: OnceLock<Regex> = OnceLock::new();
+static DAILY_LINK_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// A parsed `![[target#fragment]]` reference.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transclusion {
+    pub target: String,
+    pub fragment: Option<Fragment>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fragment {
+    Anchor(AnchorToken),
+    Heading(String),
+}
+
+impl Transclusion {
+    pub fn parse(link: &str) -> Result<Self, A4Error> {
+        // Drop an Obsidian display alias: `target#frag|alias`
+        let link = link.split('|').next().unwrap_or(link).trim();
+
+        let (target, fragment) = match link.split_once('#') {
+            Some((target, fragment)) => (target.trim(), Some(fragment.trim())),
+            None => (link, None),
+        };
+
+        let fragment = match fragment {
+            Some(f) if f.starts_with('^') => Some(Fragment::Anchor(AnchorToken::parse(f)?)),
+            Some(f) if !f.is_empty() => Some(Fragment::Heading(f.to_string())),
+            _ => None,
+        };
+
+        Ok(Transclusion {
+            target: target.to_string(),
+            fragment,
+        })
+    }
+}
+
+impl std::fmt::Display for Transclusion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.fragment {
+            Some(Fragment::Anchor(token)) => write!(f, "{}#{}", self.target, token.to_marker()),
+            Some(Fragment::Heading(heading)) => write!(f, "{}#{}", self.target, heading),
+            None => write!(f, "{}", self.target),
+        }
+    }
+}
+
+/// Render `path` with every `![[...]]` transclusion replaced by the content it
+/// references. Nested transclusions are expanded recursively; a reference that
+/// leads back to a block already being expanded is reported as a cycle.
+pub fn stitch_note(vault: &Vault, path: &Path) -> Result<String, A4Error> {
+    let path = fs::canonicalize(path)?;
+    let raw = fs::read_to_string(&path)?;
+    let mut stitcher = Stitcher {
+        vault,
+        stack: vec![display_path(vault, &path)],
+    };
+    stitcher.render(&raw)
+}
+
+/// Render generated Markdown (not backed by a file) the same way.
+pub fn stitch_text(vault: &Vault, text: &str) -> Result<String, A4Error> {
+    let mut stitcher = Stitcher {
+        vault,
+        stack: Vec::new(),
+    };
+    stitcher.render(text)
+}
+
+/// Resolve a wikilink target (`capture/2025/2025-09/2025-09-14`, with or
+/// without `.md`) to a file under the vault root. The daily shorthand
+/// `capture/YYYY-MM-DD` is mapped onto the capture layout.
+pub fn resolve_link(vault: &Vault, target: &str) -> Result<PathBuf, A4Error> {
+    let not_found = || A4Error::TransclusionNotFound {
+        target: target.to_string(),
+    };
+
+    let target = target.trim_start_matches('/');
+    if target.is_empty() {
+        return Err(not_found());
+    }
+
+    let mut candidates = vec![PathBuf::from(target)];
+    if !target.ends_with(".md") {
+        candidates.push(PathBuf::from(format!("{target}.md")));
+    }
+
+    for candidate in candidates {
+        let path = safe_join(vault.root(), &candidate)?;
+        if path.is_file() {
+            return Ok(path);
+        }
+    }
+
+    let daily_re = DAILY_LINK_REGEX
+        .get_or_init(|| Regex::new(r"^capture/(\d{4})-(\d{2})-(\d{2})(?:\.md)?$").unwrap());
+    if let Some(caps) = daily_re.captures(target) {
+        let day = UtcDay {
+            year: caps[1].parse().map_err(|_| not_found())?,
+            month: caps[2].parse().map_err(|_| not_found())?,
+            day: caps[3].parse().map_err(|_| not_found())?,
+        };
+        let path = vault.capture_day_path(day);
+        if path.is_file() {
+            return Ok(path);
+        }
+    }
+
+    Err(not_found())
+}
+
+struct Stitcher<'a> {
+    vault: &'a Vault,
+    stack: Vec<String>,
+}
+
+impl Stitcher<'_> {
+    fn render(&mut self, text: &str) -> Result<String, A4Error> {
+        let embed_re = EMBED_REGEX.get_or_init(|| Regex::new(r"!\[\[([^\]]+)\]\]").unwrap());
+
+        let mut output = String::with_capacity(text.len());
+        let mut in_fence = false;
+
+        for line in text.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+            }
+
+            if in_fence || !embed_re.is_match(line) {
+                output.push_str(line);
+                continue;
+            }
+
+            let mut last = 0;
+            for caps in embed_re.captures_iter(line) {
+                let whole = caps.get(0).unwrap();
+                let link = Transclusion::parse(&caps[1])?;
+                let content = self.expand(&link)?;
+
+                // A list embedded as a whole list item replaces that item, so
+                // its items become siblings instead of `- - item`
+                let mut before = &line[last..whole.start()];
+                let first = content.lines().next().unwrap_or_default();
+                if last == 0
+                    && before.ends_with([' ', '\t'])
+                    && is_list_marker(before.trim())
+                    && opens_list_item(first)
+                {
+                    before = &before[..before.len() - before.trim_start().len()];
+                }
+
+                // Keep continuation lines inside a list item or indented context
+                let indent = " ".repeat(line[..last].chars().count() + before.chars().count());
+                output.push_str(before);
+                for (i, content_line) in content.lines().enumerate() {
+                    if i > 0 {
+                        output.push('\n');
+                        if !content_line.is_empty() {
+                            output.push_str(&indent);
+                        }
+                    }
+                    output.push_str(content_line);
+                }
+                last = whole.end();
+            }
+            output.push_str(&line[last..]);
+        }
+
+        Ok(output)
+    }
+
+    fn expand(&mut self, link: &Transclusion) -> Result<String, A4Error> {
+        let path = resolve_link(self.vault, &link.target)?;
+        let key = match &link.fragment {
+            Some(_) => format!("{}#{}", display_path(self.vault, &path), fragment_key(link)),
+            None => display_path(self.vault, &path),
+        };
+
+        // Embedding a whole note also embeds every block inside it, so it
+        // cycles if any part of that note is being expanded. Another block of
+        // a note on the stack is fine unless it leads back itself.
+        let file_key = display_path(self.vault, &path);
+        let block_prefix = format!("{file_key}#");
+        let cycle = self
+            .stack
+            .iter()
+            .any(|k| k == &key || (link.fragment.is_none() && k.starts_with(&block_prefix)));
+        if cycle {
+            let mut chain = self.stack.clone();
+            chain.push(key);
+            return Err(A4Error::TransclusionCycle { chain });
+        }
+
+        let note = read_note(&path)?;
+        let extracted = match &link.fragment {
+            Some(Fragment::Anchor(token)) => find_block(&note, token).map(|block| block.content),
+            Some(Fragment::Heading(heading)) => extract_heading_section(&note.body, heading),
+            None => Some(note.body.trim_matches('\n').to_string()),
+        }
+        .ok_or_else(|| A4Error::BlockNotFound {
+            path: path.clone(),
+            fragment: fragment_key(link),
+        })?;
+
+        self.stack.push(key);
+        let rendered = self.render(&extracted);
+        self.stack.pop();
+
+        Ok(rendered?.trim_end_matches('\n').to_string())
+    }
+}
+
+/// Whether `token` is a list marker: `-`, `*`, `+`, `1.` or `1)`.
+fn is_list_marker(token: &str) -> bool {
+    matches!(token, "-" | "*" | "+")
+        || token
+            .strip_suffix(['.', ')'])
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Whether `line` starts a list item.
+fn opens_list_item(line: &str) -> bool {
+    line.trim_start()
+        .split_once([' ', '\t'])
+        .is_some_and(|(marker, _)| is_list_marker(marker))
+}
+
+fn fragment_key(link: &Transclusion) -> String {
+    match &link.fragment {
+        Some(Fragment::Anchor(token)) => token.to_marker(),
+        Some(Fragment::Heading(heading)) => heading.to_lowercase(),
+        None => String::new(),
+    }
+}
+
+fn display_path(vault: &Vault, path: &Path) -> String {
+    path.strip_prefix(vault.root())
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// The heading line plus everything up to the next heading of the same or a
+/// higher level.
+fn extract_heading_section(body: &str, heading: &str) -> Option<String> {
+    let mut lines = body.lines();
+    let mut section = Vec::new();
+    let mut level = 0;
+
+    for line in lines.by_ref() {
+        if let Some((l, text)) = parse_heading(line) {
+            if text.trim().eq_ignore_ascii_case(heading.trim()) {
+                level = l;
+                section.push(line);
+                break;
+            }
+        }
+    }
+
+    if section.is_empty() {
+        return None;
+    }
+
+    for line in lines {
+        if let Some((l, _)) = parse_heading(line) {
+            if l <= level {
+                break;
+            }
+        }
+        section.push(line);
+    }
+
+    Some(section.join("\n").trim_end_matches('\n').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, rel: &str, content: &str) -> PathBuf {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_transclusion() {
+        let link = Transclusion::parse("capture/2025-09-14#^eod-2215").unwrap();
+        assert_eq!(link.target, "capture/2025-09-14");
+        assert_eq!(
+            link.fragment,
+            Some(Fragment::Anchor(AnchorToken::parse("eod-2215").unwrap()))
+        );
+
+        let link = Transclusion::parse("projects/gb-ppu/index#Goals|goals").unwrap();
+        assert_eq!(link.target, "projects/gb-ppu/index");
+        assert_eq!(link.fragment, Some(Fragment::Heading("Goals".to_string())));
+
+        let link = Transclusion::parse("sources/articles/foo").unwrap();
+        assert_eq!(link.fragment, None);
+    }
+
+    #[test]
+    fn test_stitch_anchor_block_via_daily_shorthand() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        write(
+            vault.root(),
+            "capture/2025/2025-09/2025-09-14.md",
+            "## End of Day\n\n^eod-2215\n\n- Shipped stitch\n- Tired\n\n^eod-2300\n\nLater\n",
+        );
+        let plan = write(
+            vault.root(),
+            "collections/weekly-plans/2025/2025-W37.md",
+            "## Reflection on W37\n\n- ![[capture/2025-09-14#^eod-2215]]\n",
+        );
+
+        let rendered = stitch_note(&vault, &plan).unwrap();
+        assert_eq!(
+            rendered,
+            "## Reflection on W37\n\n- Shipped stitch\n- Tired\n"
+        );
+    }
+
+    #[test]
+    fn test_stitch_list_item_embeds() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        write(
+            vault.root(),
+            "a.md",
+            "^todo-0900\n\n1. Fetch\n2. Decode\n\n^read-1102\n\nA paragraph\nwrapped\n",
+        );
+        let doc = write(
+            vault.root(),
+            "doc.md",
+            "- Plan\n  - ![[a#^todo-0900]]\n- ![[a#^read-1102]]\n- See ![[a#^todo-0900]]\n",
+        );
+
+        // Lists replace the item they fill; anything else stays inside it
+        let rendered = stitch_note(&vault, &doc).unwrap();
+        assert_eq!(
+            rendered,
+            "- Plan\n  1. Fetch\n  2. Decode\n- A paragraph\n  wrapped\n- See 1. Fetch\n      2. Decode\n"
+        );
+    }
+
+    #[test]
+    fn test_stitch_heading_section_and_nested() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        write(vault.root(), "a.md", "^read-1102\n\nInner text\n");
+        write(
+            vault.root(),
+            "b.md",
+            "---\nkind: memo.research\n---\n# Memo\n\n## Notes\n\n![[a#^read-1102]]\n\n## Other\n\nSkip\n",
+        );
+        let doc = write(vault.root(), "doc.md", "![[b#Notes]]\n");
+
+        let rendered = stitch_note(&vault, &doc).unwrap();
+        assert_eq!(rendered, "## Notes\n\nInner text\n");
+    }
+
+    #[test]
+    fn test_stitch_detects_cycles() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        write(vault.root(), "a.md", "## A\n\n![[b#B]]\n");
+        write(vault.root(), "b.md", "## B\n\n![[a#A]]\n");
+        let doc = write(vault.root(), "doc.md", "![[a#A]]\n");
+
+        let err = stitch_note(&vault, &doc).unwrap_err();
+        assert!(matches!(err, A4Error::TransclusionCycle { .. }));
+
+        // A block embedding the whole note it lives in
+        write(vault.root(), "c.md", "## C\n\n![[c]]\n");
+        let doc = write(vault.root(), "doc.md", "![[c#C]]\n");
+        let err = stitch_note(&vault, &doc).unwrap_err();
+        assert!(matches!(err, A4Error::TransclusionCycle { .. }));
+    }
+
+    #[test]
+    fn test_stitch_embeds_other_block_of_same_note() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let doc = write(
+            vault.root(),
+            "doc.md",
+            "## Summary\n\n![[doc#^focus-0930]]\n\n## Focus\n\n^focus-0930\n\nShip stitch\n",
+        );
+        let rendered = stitch_note(&vault, &doc).unwrap();
+        assert!(rendered.starts_with("## Summary\n\nShip stitch\n"));
+
+        // Also from a block of that note embedded elsewhere
+        let plan = write(vault.root(), "plan.md", "![[doc#Summary]]\n");
+        assert_eq!(
+            stitch_note(&vault, &plan).unwrap(),
+            "## Summary\n\nShip stitch\n"
+        );
+    }
+
+    #[test]
+    fn test_stitch_ignores_code_fences_and_reports_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = Vault::open(temp_dir.path(), VaultOpts::default()).unwrap();
+        let fenced = write(vault.root(), "fenced.md", "```\n![[missing]]\n```\n");
+        assert_eq!(
+            stitch_note(&vault, &fenced).unwrap(),
+            "```\n![[missing]]\n```\n"
+        );
+
+        let doc = write(vault.root(), "doc.md", "![[missing]]\n");
+        let err = stitch_note(&vault, &doc).unwrap_err();
+        assert!(matches!(err, A4Error::TransclusionNotFound { .. }));
+    }
+}
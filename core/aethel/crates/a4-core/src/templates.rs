@@ -0,0 +1,524 @@
+use crate::date::{IsoWeek, LocalClock, Moment, UtcDay};
+use crate::error::A4Error;
+use crate::vault::Vault;
+use fs_err as fs;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static NAME_REGEX: OnceLock<Regex> = OnceLock::new();
+static DATE_MATH_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Variables available to [`render`].
+///
+/// Templates use `{{name}}` placeholders, `{{#if name}}…{{else}}…{{/if}}`
+/// blocks (taken when the variable is set and non-empty) and `{{> name}}`
+/// partials loaded from `<partials_dir>/name.md`. Any date variable can be
+/// shifted by days or weeks, e.g. `{{date-1d}}` or `{{monday+1w}}`, and vault
+/// config values are available as `{{config.<key>}}`, e.g.
+/// `{{config.sync.remote}}`.
+#[derive(Clone, Debug, Default)]
+pub struct TemplateContext {
+    vars: BTreeMap<String, String>,
+    config: Option<toml::Value>,
+    partials_dir: Option<PathBuf>,
+}
+
+impl TemplateContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Context for a daily note: date, week, weekday and month names, links to
+    /// neighbouring days, the current time, the device and vault config.
+    pub fn for_day(vault: &Vault, now: &Moment, day: &UtcDay) -> Result<Self, A4Error> {
+        let mut ctx = Self::new();
+        ctx.partials_dir = Some(vault.templates_dir().join("partials"));
+        ctx.config = toml::Value::try_from(vault.config()).ok();
+        if let Some(device) = vault.device_name() {
+            ctx.insert("device", device);
+        }
+
+        ctx.insert("now_utc", now.utc_iso8601());
+        ctx.insert("hhmm", now.local_hhmm());
+
+        ctx.insert_day(day)?;
+        Ok(ctx)
+    }
+
+    /// Context for a weekly plan: [`TemplateContext::for_day`] on the week's
+    /// Monday, plus `monday`, `sunday` and `day_links`.
+    pub fn for_week(vault: &Vault, now: &Moment, week: &IsoWeek) -> Result<Self, A4Error> {
+        let days = week.days()?;
+        let mut ctx = Self::for_day(vault, now, &days[0])?;
+
+        ctx.insert("monday", days[0].to_string());
+        ctx.insert("sunday", days[6].to_string());
+        let day_links: Vec<String> = days.iter().map(|d| format!("- {}", day_link(d))).collect();
+        ctx.insert("day_links", day_links.join("\n"));
+
+        Ok(ctx)
+    }
+
+    /// Set the date variables for `day`.
+    pub fn insert_day(&mut self, day: &UtcDay) -> Result<(), A4Error> {
+        let date = day.to_date()?;
+        let week = LocalClock::iso_week(day)?;
+        let yesterday = day.offset_days(-1)?;
+
+        self.insert("date", day.to_string());
+        // The original placeholder name, kept for existing templates
+        self.insert("YYYY-MM-DD", day.to_string());
+        self.insert("year", format!("{:04}", day.year));
+        self.insert("month", format!("{:02}", day.month));
+        self.insert("day", format!("{:02}", day.day));
+        self.insert("month_name", date.month().to_string());
+        self.insert("weekday", date.weekday().to_string());
+        self.insert("week", week.to_string());
+        self.insert("week_number", format!("{:02}", week.week));
+        self.insert("yesterday", yesterday.to_string());
+        self.insert("tomorrow", day.offset_days(1)?.to_string());
+        self.insert("today_link", day_link(day));
+        self.insert("yesterday_link", day_link(&yesterday));
+        Ok(())
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.vars.insert(name.into(), value.into());
+    }
+
+    pub fn with_partials_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.partials_dir = Some(dir.into());
+        self
+    }
+
+    /// The value of a placeholder expression, if it is defined.
+    pub fn get(&self, expr: &str) -> Option<String> {
+        if let Some(value) = self.vars.get(expr) {
+            return Some(value.clone());
+        }
+
+        if let Some(key) = expr.strip_prefix("config.") {
+            let mut value = self.config.as_ref()?;
+            for part in key.split('.') {
+                value = value.get(part)?;
+            }
+            return match value {
+                toml::Value::String(s) => Some(s.clone()),
+                toml::Value::Table(_) | toml::Value::Array(_) => None,
+                other => Some(other.to_string()),
+            };
+        }
+
+        let re =
+            DATE_MATH_REGEX.get_or_init(|| Regex::new(r"^([A-Za-z_]+)([+-])(\d+)([dw])$").unwrap());
+        let caps = re.captures(expr)?;
+        let base = UtcDay::parse(self.vars.get(&caps[1])?).ok()?;
+        let mut days: i64 = caps[3].parse().ok()?;
+        if &caps[4] == "w" {
+            days *= 7;
+        }
+        if &caps[2] == "-" {
+            days = -days;
+        }
+        base.offset_days(days).ok().map(|d| d.to_string())
+    }
+}
+
+/// Render `template` against `ctx`. Unknown variables, malformed or unbalanced
+/// tags, and missing partials are errors rather than being left in the output.
+pub fn render(template: &str, ctx: &TemplateContext) -> Result<String, A4Error> {
+    let mut out = String::new();
+    render_into(&mut out, template, ctx, &mut Vec::new())?;
+    Ok(out)
+}
+
+/// Create `path` from the template at `template_path` (or `fallback` when
+/// there is no template file), rendered against `ctx`. Returns whether the
+/// file was created; an existing file is left untouched.
+pub fn create_from_template(
+    vault: &Vault,
+    path: &Path,
+    template_path: &Path,
+    fallback: &str,
+    ctx: &TemplateContext,
+) -> Result<bool, A4Error> {
+    vault.ensure_parents(path)?;
+    if path.exists() {
+        return Ok(false);
+    }
+
+    let template = if template_path.exists() {
+        fs::read_to_string(template_path)?
+    } else {
+        fallback.to_string()
+    };
+    let content = render(&template, ctx)?;
+    fs::write(path, content)?;
+
+    Ok(true)
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Var {
+        expr: String,
+        line: usize,
+    },
+    If {
+        name: String,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Partial {
+        name: String,
+        line: usize,
+    },
+}
+
+#[derive(Debug)]
+enum Token {
+    Text(String),
+    Var(String),
+    If(String),
+    Else,
+    EndIf,
+    Partial(String),
+}
+
+fn render_into(
+    out: &mut String,
+    template: &str,
+    ctx: &TemplateContext,
+    partials: &mut Vec<String>,
+) -> Result<(), A4Error> {
+    let tokens = tokenize(template)?;
+    let mut tokens = tokens.into_iter().peekable();
+    let nodes = parse_nodes(&mut tokens, false)?;
+    render_nodes(out, &nodes, ctx, partials)
+}
+
+fn render_nodes(
+    out: &mut String,
+    nodes: &[Node],
+    ctx: &TemplateContext,
+    partials: &mut Vec<String>,
+) -> Result<(), A4Error> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var { expr, line } => {
+                let value = ctx
+                    .get(expr)
+                    .ok_or_else(|| A4Error::TemplateUnknownVariable {
+                        name: expr.clone(),
+                        line: *line,
+                    })?;
+                out.push_str(&value);
+            }
+            Node::If {
+                name,
+                then,
+                otherwise,
+            } => {
+                let truthy = ctx.get(name).is_some_and(|v| !v.is_empty());
+                let branch = if truthy { then } else { otherwise };
+                render_nodes(out, branch, ctx, partials)?;
+            }
+            Node::Partial { name, line } => {
+                if partials.contains(name) {
+                    let mut chain = partials.clone();
+                    chain.push(name.clone());
+                    return Err(A4Error::TemplateSyntax {
+                        line: *line,
+                        message: format!("partial cycle: {}", chain.join(" -> ")),
+                    });
+                }
+                let dir = ctx
+                    .partials_dir
+                    .as_ref()
+                    .ok_or_else(|| A4Error::TemplateSyntax {
+                        line: *line,
+                        message: format!("no partials directory for '{name}'"),
+                    })?;
+                let path = dir.join(format!("{name}.md"));
+                if !path.exists() {
+                    return Err(A4Error::TemplateNotFound { path });
+                }
+                let partial = fs::read_to_string(&path)?;
+
+                partials.push(name.clone());
+                render_into(out, &partial, ctx, partials)?;
+                partials.pop();
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_nodes(
+    tokens: &mut std::iter::Peekable<std::vec::IntoIter<(Token, usize)>>,
+    in_if: bool,
+) -> Result<Vec<Node>, A4Error> {
+    let mut nodes = Vec::new();
+    while let Some((token, line)) = tokens.peek() {
+        let line = *line;
+        match token {
+            Token::Else | Token::EndIf => {
+                if !in_if {
+                    let tag = if matches!(token, Token::Else) {
+                        "{{else}}"
+                    } else {
+                        "{{/if}}"
+                    };
+                    return Err(A4Error::TemplateSyntax {
+                        line,
+                        message: format!("{tag} without {{{{#if}}}}"),
+                    });
+                }
+                return Ok(nodes);
+            }
+            _ => {}
+        }
+
+        let (token, _) = tokens.next().expect("peeked");
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text)),
+            Token::Var(expr) => nodes.push(Node::Var { expr, line }),
+            Token::Partial(name) => nodes.push(Node::Partial { name, line }),
+            Token::If(name) => {
+                let then = parse_nodes(tokens, true)?;
+                let mut otherwise = Vec::new();
+                if matches!(tokens.peek(), Some((Token::Else, _))) {
+                    tokens.next();
+                    otherwise = parse_nodes(tokens, true)?;
+                }
+                match tokens.next() {
+                    Some((Token::EndIf, _)) => {}
+                    Some((Token::Else, else_line)) => {
+                        return Err(A4Error::TemplateSyntax {
+                            line: else_line,
+                            message: "duplicate {{else}}".to_string(),
+                        })
+                    }
+                    _ => {
+                        return Err(A4Error::TemplateSyntax {
+                            line,
+                            message: format!("{{{{#if {name}}}}} is never closed"),
+                        })
+                    }
+                }
+                nodes.push(Node::If {
+                    name,
+                    then,
+                    otherwise,
+                });
+            }
+            Token::Else | Token::EndIf => unreachable!("handled above"),
+        }
+    }
+
+    Ok(nodes)
+}
+
+/// Split a template into text and tags, each with its 1-based line. Block tags
+/// and partials that sit alone on a line take the whole line with them.
+fn tokenize(template: &str) -> Result<Vec<(Token, usize)>, A4Error> {
+    let name_re = NAME_REGEX.get_or_init(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_.+\-]*$").unwrap());
+    let line_at = |pos: usize| template[..pos].matches('\n').count() + 1;
+
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut rest = 0;
+
+    while let Some(offset) = template[rest..].find("{{") {
+        let start = rest + offset;
+        let line = line_at(start);
+        let inner_start = start + 2;
+        let Some(len) = template[inner_start..].find("}}") else {
+            return Err(A4Error::TemplateSyntax {
+                line,
+                message: "unclosed '{{'".to_string(),
+            });
+        };
+        let inner = template[inner_start..inner_start + len].trim();
+        let mut end = inner_start + len + 2;
+
+        let malformed = || A4Error::TemplateSyntax {
+            line,
+            message: format!("malformed placeholder '{{{{{inner}}}}}'"),
+        };
+        let token = if let Some(name) = inner.strip_prefix("#if") {
+            let name = name.trim();
+            if !name_re.is_match(name) || !inner[3..].starts_with(char::is_whitespace) {
+                return Err(malformed());
+            }
+            Token::If(name.to_string())
+        } else if inner == "else" {
+            Token::Else
+        } else if inner == "/if" {
+            Token::EndIf
+        } else if let Some(name) = inner.strip_prefix('>') {
+            let name = name.trim();
+            let valid = !name.is_empty()
+                && name
+                    .split('/')
+                    .all(|part| name_re.is_match(part) && part != "..");
+            if !valid {
+                return Err(malformed());
+            }
+            Token::Partial(name.to_string())
+        } else if name_re.is_match(inner) {
+            Token::Var(inner.to_string())
+        } else {
+            return Err(malformed());
+        };
+
+        text.push_str(&template[rest..start]);
+        if !matches!(token, Token::Var(_)) {
+            // A standalone tag swallows its line's indentation and newline
+            let line_start = template[..start].rfind('\n').map_or(0, |i| i + 1);
+            let after = &template[end..];
+            let line_end = after.find('\n').map_or(after.len(), |i| i + 1);
+            let indent = &template[line_start..start];
+            if indent.trim().is_empty() && after[..line_end].trim().is_empty() {
+                text.truncate(text.len() - indent.len());
+                end += line_end;
+            }
+        }
+
+        if !text.is_empty() {
+            tokens.push((Token::Text(std::mem::take(&mut text)), line));
+        }
+        tokens.push((token, line));
+        rest = end;
+    }
+    text.push_str(&template[rest..]);
+    if !text.is_empty() {
+        tokens.push((Token::Text(text), line_at(rest)));
+    }
+
+    Ok(tokens)
+}
+
+/// Wiki link to a day's capture note, as used by the protocol examples.
+fn day_link(day: &UtcDay) -> String {
+    format!("[[capture/{day}]]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultOpts;
+    use tempfile::TempDir;
+
+    fn day_ctx() -> TemplateContext {
+        let mut ctx = TemplateContext::new();
+        ctx.insert_day(&UtcDay::parse("2025-09-01").unwrap())
+            .unwrap();
+        ctx
+    }
+
+    #[test]
+    fn test_date_variables() {
+        let ctx = day_ctx();
+        let out = render(
+            "{{weekday}} {{day}} {{month_name}} {{year}} ({{week}}, W{{week_number}})\n\
+             prev {{date-1d}} next {{date+1w}} legacy {{YYYY-MM-DD}}\n\
+             {{yesterday_link}}\n",
+            &ctx,
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "Monday 01 September 2025 (2025-W36, W36)\n\
+             prev 2025-08-31 next 2025-09-08 legacy 2025-09-01\n\
+             [[capture/2025-08-31]]\n"
+        );
+    }
+
+    #[test]
+    fn test_conditionals() {
+        let mut ctx = day_ctx();
+        let template =
+            "# Day\n{{#if device}}\nFrom {{device}}\n{{else}}\nNo device\n{{/if}}\nend\n";
+        assert_eq!(render(template, &ctx).unwrap(), "# Day\nNo device\nend\n");
+
+        ctx.insert("device", "laptop");
+        assert_eq!(render(template, &ctx).unwrap(), "# Day\nFrom laptop\nend\n");
+
+        // Inline blocks keep surrounding text; nested blocks work
+        let inline = "a{{#if device}}-{{#if missing}}x{{else}}{{device}}{{/if}}{{/if}}-b";
+        assert_eq!(render(inline, &ctx).unwrap(), "a-laptop-b");
+    }
+
+    #[test]
+    fn test_partials_and_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let partials = root.join("routines/templates/partials");
+        std::fs::create_dir_all(&partials).unwrap();
+        std::fs::write(
+            partials.join("footer.md"),
+            "Synced to {{config.sync.remote}}\n",
+        )
+        .unwrap();
+        std::fs::write(partials.join("loop.md"), "{{> loop}}\n").unwrap();
+        std::fs::create_dir_all(root.join(".a4")).unwrap();
+        std::fs::write(
+            root.join(".a4/config.toml"),
+            "[sync]\nremote = \"upstream\"\n",
+        )
+        .unwrap();
+        let vault = Vault::open(root, VaultOpts::default()).unwrap();
+
+        let week = IsoWeek::parse("2025-W36").unwrap();
+        let ctx = TemplateContext::for_week(&vault, &vault.now(), &week).unwrap();
+        let out = render("{{monday}}..{{sunday}}\n{{> footer}}\n---\n", &ctx).unwrap();
+        assert_eq!(out, "2025-09-01..2025-09-07\nSynced to upstream\n---\n");
+        assert!(ctx
+            .get("day_links")
+            .unwrap()
+            .ends_with("- [[capture/2025-09-07]]"));
+
+        assert!(matches!(
+            render("{{> missing}}", &ctx),
+            Err(A4Error::TemplateNotFound { .. })
+        ));
+        assert!(matches!(
+            render("{{> loop}}", &ctx),
+            Err(A4Error::TemplateSyntax { .. })
+        ));
+        assert!(matches!(
+            render("{{> ../secret}}", &ctx),
+            Err(A4Error::TemplateSyntax { .. })
+        ));
+    }
+
+    #[test]
+    fn test_errors() {
+        let ctx = day_ctx();
+        let err = render("ok\n{{nope}}\n", &ctx).unwrap_err();
+        assert!(
+            matches!(err, A4Error::TemplateUnknownVariable { ref name, line: 2 } if name == "nope")
+        );
+
+        for bad in [
+            "{{date",
+            "{{}}",
+            "{{ two words }}",
+            "{{#if}}x{{/if}}",
+            "{{#if date}}x",
+            "x{{/if}}",
+            "{{else}}",
+            "{{#if date}}a{{else}}b{{else}}c{{/if}}",
+        ] {
+            assert!(
+                matches!(render(bad, &ctx), Err(A4Error::TemplateSyntax { .. })),
+                "{bad} should be rejected"
+            );
+        }
+    }
+}
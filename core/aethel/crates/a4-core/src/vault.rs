@@ -1,11 +1,14 @@
-use crate::date::UtcDay;
+use crate::config::{TimezonePolicy, VaultConfig};
+use crate::date::{Clock, IsoWeek, Moment, SystemClock, UtcDay, Zone};
 use crate::error::A4Error;
+use crate::journal::JournalSession;
 use fs_err as fs;
 use std::env;
 use std::path::{Path, PathBuf};
 
 pub struct Vault {
     root: PathBuf,
+    config: VaultConfig,
 }
 
 #[derive(Debug, Clone)]
@@ -36,10 +39,21 @@ impl Vault {
         }
 
         let root = fs::canonicalize(path)?;
-        Ok(Vault { root })
+        let config = VaultConfig::load(&root)?;
+        Ok(Vault { root, config })
     }
 
     pub fn resolve_default() -> Result<(Self, VaultRoot), A4Error> {
+        Self::search(true)
+    }
+
+    /// Find the vault like [`Vault::resolve_default`], but fail with
+    /// `VaultNotFound` instead of creating `$HOME/Documents/a4-core`.
+    pub fn find_existing() -> Result<(Self, VaultRoot), A4Error> {
+        Self::search(false)
+    }
+
+    fn search(create_default: bool) -> Result<(Self, VaultRoot), A4Error> {
         let mut attempts = Vec::new();
 
         if let Ok(vault_env) = env::var("A4_VAULT_DIR") {
@@ -79,6 +93,8 @@ impl Vault {
         if default_path.exists() {
             let vault = Self::open(&default_path, VaultOpts::default())?;
             Ok((vault, VaultRoot::Default(default_path)))
+        } else if !create_default {
+            Err(A4Error::VaultNotFound { attempts })
         } else {
             let vault = Self::open(
                 &default_path,
@@ -116,6 +132,45 @@ impl Vault {
             .join(filename)
     }
 
+    /// `collections/weekly-plans/YYYY/week-YYYY-Wnn.md`, filed under the ISO
+    /// week-numbering year.
+    pub fn weekly_plan_path(&self, week: IsoWeek) -> PathBuf {
+        self.root
+            .join("collections")
+            .join("weekly-plans")
+            .join(format!("{:04}", week.year))
+            .join(format!("week-{week}.md"))
+    }
+
+    /// `inbox/YYYY/YYYY-MM/YYYY-MM-DD--paste-zone.md` (protocol §3).
+    pub fn inbox_path(&self, day: UtcDay) -> PathBuf {
+        self.root
+            .join("inbox")
+            .join(format!("{:04}", day.year))
+            .join(format!("{:04}-{:02}", day.year, day.month))
+            .join(format!("{day}--paste-zone.md"))
+    }
+
+    /// `projects/<slug>/index.md` (protocol §7.4).
+    pub fn project_index_path(&self, slug: &str) -> PathBuf {
+        self.root.join("projects").join(slug).join("index.md")
+    }
+
+    /// `projects/<slug>/log.md`, append-only.
+    pub fn project_log_path(&self, slug: &str) -> PathBuf {
+        self.root.join("projects").join(slug).join("log.md")
+    }
+
+    /// `collections/journals/YYYY/MM/journal-YYYY-MM-DD-<session>.md`.
+    pub fn journal_path(&self, day: UtcDay, session: JournalSession) -> PathBuf {
+        self.root
+            .join("collections")
+            .join("journals")
+            .join(format!("{:04}", day.year))
+            .join(format!("{:02}", day.month))
+            .join(format!("journal-{day}-{session}.md"))
+    }
+
     pub fn ensure_parents(&self, path: &Path) -> Result<(), A4Error> {
         // For absolute paths, just use them as-is
         // For relative paths, join with vault root
@@ -141,10 +196,103 @@ impl Vault {
         &self.root
     }
 
+    pub fn config(&self) -> &VaultConfig {
+        &self.config
+    }
+
+    /// Device name used to suffix colliding anchors: `A4_DEVICE`, then the
+    /// `device` config key. Normalized to lowercase letters, digits and
+    /// hyphens.
+    pub fn device_name(&self) -> Option<String> {
+        let raw = env::var("A4_DEVICE")
+            .ok()
+            .or_else(|| self.config.device.clone())?;
+        let mut name = String::new();
+        for c in raw.trim().chars() {
+            if c.is_ascii_alphanumeric() {
+                name.push(c.to_ascii_lowercase());
+            } else if !name.is_empty() && !name.ends_with('-') {
+                name.push('-');
+            }
+        }
+        let name = name.trim_end_matches('-');
+
+        (!name.is_empty()).then(|| name.to_string())
+    }
+
+    /// Zone for local times in content: `A4_TZ`, then `[time] zone`, then the
+    /// system zone. An unknown name is warned about and skipped.
+    pub fn local_zone(&self) -> Zone {
+        let configured = env::var("A4_TZ")
+            .ok()
+            .filter(|tz| !tz.trim().is_empty())
+            .into_iter()
+            .chain(self.config.time.zone.clone());
+        for name in configured {
+            match Zone::named(&name) {
+                Ok(zone) => return zone,
+                Err(e) => tracing::warn!("{e}"),
+            }
+        }
+        Zone::system()
+    }
+
+    /// The clock's current instant under this vault's timezone policy.
+    pub fn moment(&self, clock: &dyn Clock) -> Moment {
+        let local_zone = self.local_zone();
+        let filename_zone = match &self.config.time.filename_tz {
+            TimezonePolicy::Utc => Zone::Utc,
+            TimezonePolicy::Local => local_zone,
+            // Validated when the config was loaded
+            TimezonePolicy::Zone(name) => Zone::named(name).unwrap_or(Zone::Utc),
+        };
+        Moment {
+            instant: clock.now_utc(),
+            filename_zone,
+            local_zone,
+            rollover_hour: self.config.time.day_rollover_hour,
+        }
+    }
+
+    pub fn now(&self) -> Moment {
+        self.moment(&SystemClock)
+    }
+
+    /// The day whose daily note is "today", per the `time.filename_tz` policy
+    /// and `time.day_rollover_hour`.
+    pub fn today(&self) -> UtcDay {
+        self.now().day()
+    }
+
+    /// The daily note day for `--date`/`--offset` style selection: `date` if
+    /// given, else the day of `now`, shifted by `offset` days.
+    pub fn resolve_day(
+        &self,
+        now: &Moment,
+        date: Option<UtcDay>,
+        offset: i64,
+    ) -> Result<UtcDay, A4Error> {
+        date.unwrap_or_else(|| now.day()).offset_days(offset)
+    }
+
     pub fn template_path(&self) -> PathBuf {
-        self.root
-            .join("routines")
-            .join("templates")
-            .join("daily.md")
+        self.root.join(&self.config.templates.daily)
+    }
+
+    pub fn weekly_template_path(&self) -> PathBuf {
+        self.root.join(&self.config.templates.weekly)
+    }
+
+    /// `journal-<session>.md` in the templates directory.
+    pub fn journal_template_path(&self, session: JournalSession) -> PathBuf {
+        self.templates_dir().join(format!("journal-{session}.md"))
+    }
+
+    pub fn project_template_path(&self) -> PathBuf {
+        self.templates_dir().join("project.md")
+    }
+
+    pub fn templates_dir(&self) -> PathBuf {
+        self.root.join(&self.config.templates.dir)
     }
 }
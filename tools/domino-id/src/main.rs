@@ -1,24 +1,29 @@
 //! Domino ID generator - generates unique IDs for project domino tasks.
 //!
-//! Uses beads-style ID generation: SHA256 hash of seed, base36 encoded,
-//! with adaptive length and collision checking.
-
-use chrono::{DateTime, Utc};
-use clap::{Parser, Subcommand};
-use sha2::{Digest, Sha256};
-use std::collections::HashSet;
-use std::fs;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
-
-const PREFIX: &str = "d";
-const MIN_HASH_LENGTH: usize = 3;
-const MAX_HASH_LENGTH: usize = 8;
+//! A thin command line over `a4_core::dominoes`, which `a4 domino` shares.
+//! Projects are named by slug (`gb-ppu` → `projects/gb-ppu/index.md` in the
+//! vault) or by the path of their `index.md`. Only slugs, `--all` and
+//! `--root` need a vault, and none is ever created.
+
+use a4_core::dominoes::{self, project_ids};
+use a4_core::{
+    add_domino, complete_domino, generate_id, lint_project, list_projects, migrate_project,
+    next_dominoes, open_project_at, project_path, project_status, render_graph, render_status,
+    Clock, GraphSyntax, Moment, Project, SystemClock, Vault,
+};
+use clap::{Args, Parser, Subcommand};
+use serde_json::json;
+use std::error::Error;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "domino-id")]
 #[command(about = "Generate unique domino IDs for project tasks")]
 struct Cli {
+    /// Vault root; defaults to A4_VAULT_DIR or the enclosing `.a4` vault
+    #[arg(long, global = true)]
+    root: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -27,7 +32,7 @@ struct Cli {
 enum Commands {
     /// Generate a new domino ID for a task
     Generate {
-        /// Path to the project markdown file
+        /// Project slug or path to its markdown file
         #[arg(short, long)]
         project: String,
 
@@ -37,414 +42,379 @@ enum Commands {
     },
     /// List existing domino IDs for a project
     List {
-        /// Path to the project markdown file
+        /// Project slug or path to its markdown file
         #[arg(short, long)]
         project: String,
     },
-}
-
-fn main() {
-    let cli = Cli::parse();
+    /// Generate an ID and add the task to the project's `dominoes:` list
+    Add {
+        /// Project slug or path to its markdown file
+        #[arg(short, long)]
+        project: String,
 
-    match cli.command {
-        Commands::Generate { project, task } => {
-            let project_path = Path::new(&project);
-            match generate_domino_id(project_path, &task) {
-                Ok(id) => println!("{id}"),
-                Err(e) => {
-                    eprintln!("Error: {e}");
-                    std::process::exit(1);
-                }
-            }
-        }
-        Commands::List { project } => {
-            let project_path = Path::new(&project);
-            match list_existing_ids(project_path) {
-                Ok(ids) => {
-                    for id in ids {
-                        println!("{id}");
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error: {e}");
-                    std::process::exit(1);
-                }
-            }
-        }
-    }
-}
+        /// The task description
+        #[arg(short, long)]
+        task: String,
+    },
+    /// Mark a domino done and log its completion
+    Complete {
+        /// The domino ID, e.g. d-1w3
+        id: String,
 
-/// Generate a new unique domino ID for a task.
-pub fn generate_domino_id(project_path: &Path, task: &str) -> Result<String, String> {
-    let existing_ids = list_existing_ids(project_path).unwrap_or_default();
-    let project_slug = project_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("unknown");
+        /// Project slug or path to its markdown file
+        #[arg(short, long)]
+        project: String,
 
-    let now = Utc::now();
-    let count = existing_ids.len();
+        /// Log message; defaults to "Completed: <task>"
+        #[arg(short, long)]
+        what: Option<String>,
+    },
+    /// Append a timestamped entry to the project's log.jsonl
+    Log {
+        /// What was done
+        what: String,
 
-    generate_id_with_collision_check(project_slug, task, now, count, |id| existing_ids.contains(id))
-}
+        /// Project slug or path to its markdown file
+        #[arg(short, long)]
+        project: String,
 
-/// Generate ID with collision checking.
-fn generate_id_with_collision_check<F>(
-    project_slug: &str,
-    task: &str,
-    created_at: DateTime<Utc>,
-    existing_count: usize,
-    exists: F,
-) -> Result<String, String>
-where
-    F: Fn(&str) -> bool,
-{
-    let mut length = optimal_length(existing_count);
-
-    loop {
-        // Try nonces 0..10 at this length
-        for nonce in 0..10 {
-            let id = generate_candidate(project_slug, task, created_at, nonce, length);
-            if !exists(&id) {
-                return Ok(id);
-            }
-        }
+        /// Domino IDs the entry refers to
+        #[arg(short, long = "domino")]
+        dominoes: Vec<String>,
+    },
+    /// Show each domino as open or done, joined with the log
+    Status {
+        #[command(flatten)]
+        projects: Projects,
+
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// List open dominoes whose `after` dominoes are done, in dependency
+    /// order
+    Next {
+        /// Project slug or path to its markdown file
+        #[arg(short, long)]
+        project: String,
 
-        // All nonces collided, increase length
-        if length < MAX_HASH_LENGTH {
-            length += 1;
-        } else {
-            // Fallback: try more nonces with max length
-            for nonce in 10..1000 {
-                let id = generate_candidate(project_slug, task, created_at, nonce, MAX_HASH_LENGTH);
-                if !exists(&id) {
-                    return Ok(id);
-                }
-            }
-            return Err("Could not generate unique ID after 1000 attempts".to_string());
-        }
-    }
-}
+        /// Print JSON instead of one line per domino
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the dominoes and their `after` edges as a graph
+    Graph {
+        /// Project slug or path to its markdown file
+        #[arg(short, long)]
+        project: String,
 
-/// Compute optimal hash length based on existing count.
-/// Uses birthday problem approximation.
-fn optimal_length(existing_count: usize) -> usize {
-    let n = existing_count as f64;
-    let max_prob = 0.25;
-
-    for len in MIN_HASH_LENGTH..=MAX_HASH_LENGTH {
-        let space = 36_f64.powi(len as i32);
-        // Birthday problem: P(collision) ≈ 1 - e^(-n²/2d)
-        let prob = 1.0 - (-n * n / (2.0 * space)).exp();
-        if prob < max_prob {
-            return len;
-        }
-    }
-    MAX_HASH_LENGTH
+        /// Graphviz DOT instead of Mermaid
+        #[arg(long)]
+        dot: bool,
+    },
+    /// Report malformed front matter, duplicate IDs, ID-less dominoes,
+    /// dependency cycles and bad log lines
+    Lint {
+        #[command(flatten)]
+        projects: Projects,
+
+        /// Print JSON instead of one line per problem
+        #[arg(long)]
+        json: bool,
+    },
+    /// Give every ID-less domino an ID, in place
+    Migrate {
+        #[command(flatten)]
+        projects: Projects,
+    },
 }
 
-/// Generate a candidate ID.
-fn generate_candidate(
-    project_slug: &str,
-    task: &str,
-    created_at: DateTime<Utc>,
-    nonce: u32,
-    hash_length: usize,
-) -> String {
-    let seed = generate_seed(project_slug, task, created_at, nonce);
-    let hash = compute_hash(&seed, hash_length);
-    format!("{PREFIX}-{hash}")
-}
+/// Either one project or every project in the vault.
+#[derive(Args)]
+struct Projects {
+    /// Project slug or path to its markdown file
+    #[arg(short, long, required_unless_present = "all", conflicts_with = "all")]
+    project: Option<String>,
 
-/// Generate seed string for hashing.
-fn generate_seed(project_slug: &str, task: &str, created_at: DateTime<Utc>, nonce: u32) -> String {
-    format!(
-        "{}|{}|{}|{}",
-        project_slug,
-        task,
-        created_at.timestamp_nanos_opt().unwrap_or(0),
-        nonce
-    )
+    /// Every project under `<root>/projects/`
+    #[arg(long)]
+    all: bool,
 }
 
-/// Compute base36 hash of input.
-fn compute_hash(input: &str, length: usize) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(input.as_bytes());
-    let result = hasher.finalize();
-
-    // Use first 8 bytes for a 64-bit integer
-    let mut num = 0u64;
-    for &byte in result.iter().take(8) {
-        num = (num << 8) | u64::from(byte);
-    }
-
-    let encoded = base36_encode(num);
-
-    // Pad with '0' if too short
-    let mut s = encoded;
-    while s.len() < length {
-        s = format!("0{s}");
+impl Projects {
+    fn resolve(&self, workspace: &mut Workspace) -> Result<Vec<Project>, Box<dyn Error>> {
+        match &self.project {
+            Some(project) => Ok(vec![workspace.project(project)?]),
+            None => Ok(list_projects(workspace.vault()?)?),
+        }
     }
-
-    // Take the first `length` chars
-    s.chars().take(length).collect()
 }
 
-/// Encode a u64 as base36 (0-9, a-z).
-fn base36_encode(mut num: u64) -> String {
-    const ALPHABET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
-    if num == 0 {
-        return "0".to_string();
-    }
-    let mut chars = Vec::new();
-    while num > 0 {
-        chars.push(ALPHABET[(num % 36) as usize] as char);
-        num /= 36;
-    }
-    chars.into_iter().rev().collect()
+/// The vault, resolved only once a slug, `--all` or `--root` asks for one,
+/// so that projects named by path work anywhere.
+struct Workspace {
+    root: Option<PathBuf>,
+    vault: Option<Vault>,
 }
 
-/// List all existing domino IDs for a project.
-pub fn list_existing_ids(project_path: &Path) -> Result<HashSet<String>, String> {
-    let mut ids = HashSet::new();
+impl Workspace {
+    fn new(root: Option<PathBuf>) -> Self {
+        Workspace { root, vault: None }
+    }
 
-    // 1. Parse IDs from project markdown frontmatter
-    if project_path.exists() {
-        let content = fs::read_to_string(project_path)
-            .map_err(|e| format!("Failed to read project file: {e}"))?;
-        ids.extend(extract_ids_from_frontmatter(&content));
+    fn vault(&mut self) -> Result<&Vault, Box<dyn Error>> {
+        if self.vault.is_none() {
+            let vault = match &self.root {
+                Some(root) => Vault::resolve_with_override(Some(root.clone()))?.0,
+                None => Vault::find_existing()?.0,
+            };
+            self.vault = Some(vault);
+        }
+        Ok(self.vault.as_ref().expect("vault was just resolved"))
     }
 
-    // 2. Parse IDs from log.jsonl file (in same directory as project file)
-    let log_path = project_path.parent().map(|p| p.join("log.jsonl")).unwrap_or_else(|| "log.jsonl".into());
-    if log_path.exists() {
-        let file = fs::File::open(&log_path)
-            .map_err(|e| format!("Failed to open log file: {e}"))?;
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            let line = line.map_err(|e| format!("Failed to read log line: {e}"))?;
-            if let Ok(entry) = serde_json::from_str::<LogEntry>(&line) {
-                if let Some(dominoes) = entry.dominoes {
-                    ids.extend(dominoes);
+    fn project(&mut self, arg: &str) -> Result<Project, Box<dyn Error>> {
+        match project_path(arg) {
+            Some(path) => {
+                if self.root.is_some() {
+                    self.vault()?;
                 }
+                Ok(open_project_at(path)?)
             }
+            None => Ok(Project::open(self.vault()?, arg)?),
         }
     }
 
-    Ok(ids)
+    /// Now through the vault's timezone policy, or in UTC without a vault.
+    fn now(&self) -> Moment {
+        match &self.vault {
+            Some(vault) => vault.now(),
+            None => Moment::utc(SystemClock.now_utc()),
+        }
+    }
 }
 
-/// Extract domino IDs from YAML frontmatter.
-fn extract_ids_from_frontmatter(content: &str) -> Vec<String> {
-    let mut ids = Vec::new();
+fn main() {
+    let cli = Cli::parse();
 
-    // Find YAML frontmatter between --- markers
-    if !content.starts_with("---") {
-        return ids;
+    if let Err(e) = run(cli) {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
     }
+}
 
-    let rest = &content[3..];
-    if let Some(end_idx) = rest.find("\n---") {
-        let yaml_content = &rest[..end_idx];
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let mut workspace = Workspace::new(cli.root);
 
-        if let Ok(frontmatter) = serde_yaml::from_str::<Frontmatter>(yaml_content) {
-            for domino in frontmatter.dominoes.unwrap_or_default() {
-                if let Domino::Structured { id, .. } = domino {
-                    ids.push(id);
+    match cli.command {
+        Commands::Generate { project, task } => {
+            let project = workspace.project(&project)?;
+            let now = workspace.now();
+            println!(
+                "{}",
+                generate_id(workspace.vault.as_ref(), &project, &task, &now)?
+            );
+        }
+        Commands::List { project } => {
+            let mut ids: Vec<String> = project_ids(&workspace.project(&project)?)?
+                .into_iter()
+                .collect();
+            ids.sort();
+            for id in ids {
+                println!("{id}");
+            }
+        }
+        Commands::Add { project, task } => {
+            let project = workspace.project(&project)?;
+            let now = workspace.now();
+            println!(
+                "{}",
+                add_domino(workspace.vault.as_ref(), &project, &task, &now)?
+            );
+        }
+        Commands::Complete { id, project, what } => {
+            let project = workspace.project(&project)?;
+            let line = complete_domino(&project, &id, what.as_deref(), &workspace.now())?;
+            println!("{line}");
+        }
+        Commands::Log {
+            what,
+            project,
+            dominoes,
+        } => {
+            let project = workspace.project(&project)?;
+            let line = dominoes::append_log(&project, &what, &dominoes, &workspace.now())?;
+            println!("{line}");
+        }
+        Commands::Status { projects, json } => {
+            let projects = projects.resolve(&mut workspace)?;
+            let now = workspace.now();
+            let statuses = projects
+                .iter()
+                .map(|project| project_status(project, &now))
+                .collect::<Result<Vec<_>, _>>()?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&statuses)?);
+            } else {
+                print!("{}", render_status(&statuses));
+            }
+        }
+        Commands::Next { project, json } => {
+            let next = next_dominoes(&workspace.project(&project)?)?;
+            if json {
+                let next: Vec<_> = next
+                    .iter()
+                    .map(|d| json!({ "id": d.id, "task": d.task, "estimate": d.estimate }))
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&next)?);
+            } else {
+                for d in next {
+                    let estimate = d.estimate.map(|e| format!(" ({e})"));
+                    println!(
+                        "{}\t{}{}",
+                        d.id.as_deref().unwrap_or("-"),
+                        d.task,
+                        estimate.unwrap_or_default()
+                    );
+                }
+            }
+        }
+        Commands::Graph { project, dot } => {
+            let syntax = if dot {
+                GraphSyntax::Dot
+            } else {
+                GraphSyntax::Mermaid
+            };
+            print!("{}", render_graph(&workspace.project(&project)?, syntax)?);
+        }
+        Commands::Lint { projects, json } => {
+            let mut issues = Vec::new();
+            for project in projects.resolve(&mut workspace)? {
+                issues.extend(lint_project(&project)?);
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&issues)?);
+            } else {
+                for issue in &issues {
+                    println!("{issue}");
+                }
+            }
+            if !issues.is_empty() {
+                return Err(format!("{} problem(s) found", issues.len()).into());
+            }
+        }
+        Commands::Migrate { projects } => {
+            let projects = projects.resolve(&mut workspace)?;
+            let now = workspace.now();
+            for project in projects {
+                for (id, task) in migrate_project(workspace.vault.as_ref(), &project, &now)? {
+                    println!("{}\t{id}\t{task}", project.index.display());
                 }
             }
         }
     }
-
-    ids
-}
-
-#[derive(Debug, serde::Deserialize)]
-struct Frontmatter {
-    dominoes: Option<Vec<Domino>>,
-}
-
-#[derive(Debug, serde::Deserialize)]
-#[serde(untagged)]
-enum Domino {
-    Simple(String),
-    Structured { id: String, task: String },
-}
-
-#[derive(Debug, serde::Deserialize)]
-struct LogEntry {
-    dominoes: Option<Vec<String>>,
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Write;
+    use std::fs;
     use tempfile::TempDir;
 
-    #[test]
-    fn test_base36_encode() {
-        assert_eq!(base36_encode(0), "0");
-        assert_eq!(base36_encode(35), "z");
-        assert_eq!(base36_encode(36), "10");
-        assert_eq!(base36_encode(1295), "zz");
-    }
-
-    #[test]
-    fn test_compute_hash_length() {
-        let hash = compute_hash("test input", 3);
-        assert_eq!(hash.len(), 3);
-
-        let hash = compute_hash("test input", 5);
-        assert_eq!(hash.len(), 5);
-    }
-
-    #[test]
-    fn test_compute_hash_deterministic() {
-        let hash1 = compute_hash("same input", 5);
-        let hash2 = compute_hash("same input", 5);
-        assert_eq!(hash1, hash2);
-    }
+    const INDEX: &str = r#"---
+kind: project
+dominoes:
+  - id: d-abc
+    task: "current task"
+---
+# Test
+"#;
 
-    #[test]
-    fn test_compute_hash_different_inputs() {
-        let hash1 = compute_hash("input one", 5);
-        let hash2 = compute_hash("input two", 5);
-        assert_ne!(hash1, hash2);
+    /// `<tmp>/test-project/index.md` holding [`INDEX`].
+    fn project_dir() -> (TempDir, PathBuf) {
+        let tmp_dir = TempDir::new().unwrap();
+        let index = tmp_dir.path().join("test-project").join("index.md");
+        fs::create_dir_all(index.parent().unwrap()).unwrap();
+        fs::write(&index, INDEX).unwrap();
+        (tmp_dir, index)
     }
 
     #[test]
-    fn test_optimal_length_small_db() {
-        assert_eq!(optimal_length(0), MIN_HASH_LENGTH);
-        assert_eq!(optimal_length(10), MIN_HASH_LENGTH);
+    fn test_parse_generate() {
+        let cli =
+            Cli::try_parse_from(["domino-id", "generate", "-p", "gb-ppu", "-t", "task"]).unwrap();
+        assert!(cli.root.is_none());
+        let Commands::Generate { project, task } = cli.command else {
+            panic!("expected generate");
+        };
+        assert_eq!((project.as_str(), task.as_str()), ("gb-ppu", "task"));
+
+        assert!(Cli::try_parse_from(["domino-id", "generate", "-p", "gb-ppu"]).is_err());
     }
 
     #[test]
-    fn test_optimal_length_grows() {
-        let len_small = optimal_length(10);
-        let len_large = optimal_length(10000);
-        assert!(len_large >= len_small);
+    fn test_parse_root_is_global() {
+        let cli = Cli::try_parse_from(["domino-id", "list", "-p", "x", "--root", "/v"]).unwrap();
+        assert_eq!(cli.root, Some(PathBuf::from("/v")));
     }
 
     #[test]
-    fn test_generate_candidate_format() {
-        let now = Utc::now();
-        let id = generate_candidate("test-project", "test task", now, 0, 4);
-        assert!(id.starts_with("d-"));
-        assert_eq!(id.len(), 6); // "d-" + 4 chars
+    fn test_parse_project_or_all() {
+        assert!(Cli::try_parse_from(["domino-id", "status"]).is_err());
+        assert!(Cli::try_parse_from(["domino-id", "status", "--all", "-p", "x"]).is_err());
+
+        let cli = Cli::try_parse_from(["domino-id", "lint", "--all"]).unwrap();
+        let Commands::Lint { projects, .. } = cli.command else {
+            panic!("expected lint");
+        };
+        assert!(projects.all && projects.project.is_none());
     }
 
     #[test]
-    fn test_generate_id_no_collisions() {
-        let now = Utc::now();
-        let result = generate_id_with_collision_check(
-            "test-project",
-            "test task",
-            now,
-            0,
-            |_| false, // No collisions
-        );
-        assert!(result.is_ok());
-        assert!(result.unwrap().starts_with("d-"));
-    }
+    fn test_path_project_needs_no_vault() {
+        let (_tmp_dir, index) = project_dir();
+        let mut workspace = Workspace::new(None);
 
-    #[test]
-    fn test_generate_id_with_some_collisions() {
-        use std::cell::Cell;
-        let now = Utc::now();
-        let call_count = Cell::new(0);
-        let result = generate_id_with_collision_check(
-            "test-project",
-            "test task",
-            now,
-            0,
-            |_| {
-                // First 5 calls collide, then succeed
-                let count = call_count.get();
-                call_count.set(count + 1);
-                count < 5
-            },
-        );
-        assert!(result.is_ok());
-    }
+        let project = workspace.project(index.to_str().unwrap()).unwrap();
+        assert_eq!(project.slug, "test-project");
+        assert!(workspace.vault.is_none());
 
-    #[test]
-    fn test_extract_ids_from_frontmatter_simple() {
-        let content = r#"---
-kind: project
-dominoes:
-  - "simple task"
----
-# Project
-"#;
-        let ids = extract_ids_from_frontmatter(content);
-        assert!(ids.is_empty()); // Simple strings have no ID
+        let id = generate_id(None, &project, "new task", &workspace.now()).unwrap();
+        assert!(id.starts_with("d-"));
+        assert!(id.len() >= 5);
     }
 
     #[test]
-    fn test_extract_ids_from_frontmatter_structured() {
-        let content = r#"---
-kind: project
-dominoes:
-  - id: d-abc
-    task: "structured task"
-  - id: d-xyz
-    task: "another task"
----
-# Project
-"#;
-        let ids = extract_ids_from_frontmatter(content);
-        assert_eq!(ids.len(), 2);
-        assert!(ids.contains(&"d-abc".to_string()));
-        assert!(ids.contains(&"d-xyz".to_string()));
+    fn test_missing_path_project_is_an_error() {
+        let (tmp_dir, _) = project_dir();
+        let missing = tmp_dir.path().join("other").join("index.md");
+        assert!(Workspace::new(None)
+            .project(missing.to_str().unwrap())
+            .is_err());
     }
 
     #[test]
-    fn test_list_existing_ids_with_log() {
-        let tmp_dir = TempDir::new().unwrap();
-        let project_dir = tmp_dir.path().join("test-project");
-        fs::create_dir(&project_dir).unwrap();
-        let project_path = project_dir.join("index.md");
-        let log_path = project_dir.join("log.jsonl");
-
-        // Create project file with structured domino
-        fs::write(&project_path, r#"---
-kind: project
-dominoes:
-  - id: d-abc
-    task: "current task"
----
-# Test
-"#).unwrap();
-
-        // Create log file with completed domino
-        let mut log_file = fs::File::create(&log_path).unwrap();
-        writeln!(log_file, r#"{{"ts":"2026-01-19T12:00:00Z","what":"did stuff","dominoes":["d-xyz"]}}"#).unwrap();
-
-        let ids = list_existing_ids(&project_path).unwrap();
-        assert!(ids.contains("d-abc"));
-        assert!(ids.contains("d-xyz"));
+    fn test_slug_resolves_in_root() {
+        let (tmp_dir, _) = project_dir();
+        let vault = tmp_dir.path().join("vault");
+        let index = vault.join("projects").join("gb-ppu").join("index.md");
+        fs::create_dir_all(index.parent().unwrap()).unwrap();
+        fs::write(&index, INDEX).unwrap();
+
+        let mut workspace = Workspace::new(Some(vault));
+        let project = workspace.project("gb-ppu").unwrap();
+        assert_eq!(project.slug, "gb-ppu");
+        assert!(workspace.vault.is_some());
     }
 
     #[test]
-    fn test_generate_domino_id_integration() {
+    fn test_missing_root_is_not_created() {
         let tmp_dir = TempDir::new().unwrap();
-        let project_dir = tmp_dir.path().join("test-project");
-        fs::create_dir(&project_dir).unwrap();
-        let project_path = project_dir.join("index.md");
-
-        // Create empty project file
-        fs::write(&project_path, r#"---
-kind: project
-dominoes: []
----
-# Test
-"#).unwrap();
+        let root = tmp_dir.path().join("nowhere");
 
-        let id = generate_domino_id(&project_path, "new task").unwrap();
-        assert!(id.starts_with("d-"));
-        assert!(id.len() >= 5); // d- + at least 3 chars
+        let mut workspace = Workspace::new(Some(root.clone()));
+        assert!(workspace.project("gb-ppu").is_err());
+        assert!(!root.exists());
     }
 }